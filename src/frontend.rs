@@ -15,13 +15,17 @@ use std::{
     io::{self, Stdout},
     time::Duration,
 };
-use tui::{backend::CrosstermBackend, Terminal};
+use tui::{
+    backend::{Backend, CrosstermBackend},
+    Terminal,
+};
 
 pub type CrosstermTerminal = Terminal<CrosstermBackend<Stdout>>;
 
 #[async_trait]
 pub trait App {
-    fn draw(&mut self, _: &mut CrosstermTerminal) -> Result<()>;
+    // 테스트에서는 TestBackend 에 그린다
+    fn draw<B: Backend>(&mut self, _: &mut Terminal<B>) -> Result<()>;
     async fn handle_front_event(&mut self, _: &mut Receiver<FrontendEvent>) -> EventAction;
 }
 
@@ -90,7 +94,7 @@ fn run_key_event_sender(
     exit_receiver: std::sync::mpsc::Receiver<()>,
 ) -> JoinHandle<Result<()>> {
     tokio::task::spawn_blocking(move || loop {
-        if let Ok(_) = exit_receiver.try_recv() {
+        if exit_receiver.try_recv().is_ok() {
             return Ok(());
         }
         if crossterm::event::poll(Duration::from_millis(500))? {
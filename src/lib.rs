@@ -48,7 +48,9 @@ impl<T> StatefulList<T> {
 
     fn update(&mut self, items: Vec<T>) {
         self.items = items;
-        if let Some(i) = self.state.selected() {
+        if self.items.is_empty() {
+            self.state.select(None);
+        } else if let Some(i) = self.state.selected() {
             if i >= self.items.len() {
                 self.state.select(Some(self.items.len() - 1));
             }
@@ -56,7 +58,7 @@ impl<T> StatefulList<T> {
     }
 
     fn next(&mut self) {
-        if self.items.len() == 0 {
+        if self.items.is_empty() {
             self.state.select(None);
         } else {
             let i = match self.state.selected() {
@@ -74,18 +76,12 @@ impl<T> StatefulList<T> {
     }
 
     fn previous(&mut self) {
-        if self.items.len() == 0 {
+        if self.items.is_empty() {
             self.state.select(None);
         } else {
             let i = match self.state.selected() {
-                Some(i) => {
-                    if i > 0 {
-                        i - 1
-                    } else {
-                        0
-                    }
-                }
-                None => 0,
+                Some(i) if i > 0 => i - 1,
+                _ => 0,
             };
             self.state.select(Some(i));
         }
@@ -0,0 +1,308 @@
+use async_trait::async_trait;
+use aws_sdk_s3::{
+    model::{Bucket, BucketLocationConstraint, CommonPrefix, Object},
+    output::ListObjectsV2Output,
+    types::ByteStream,
+};
+use aws_smithy_types::DateTime;
+use eyre::Result;
+use futures::TryStreamExt;
+use std::path::{Component, Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+use super::{Scheme, StorageBackend};
+use crate::s3::client::BucketWithLocation;
+
+// 로컬 디스크는 "/" 라는 하나의 bucket 이고, key 는 "/" 아래의 상대 경로다
+pub const ROOT: &str = "/";
+
+// file:// 혹은 / 로 시작하는 경로를 (bucket, key) 로 바꾼다. 디렉토리라면 key 는 '/' 로 끝난다
+pub fn parse_location(location: &str) -> Option<(String, String)> {
+    let path = location.strip_prefix("file://").unwrap_or(location);
+    let mut key = path.strip_prefix('/')?.to_owned();
+    if !key.is_empty() && !key.ends_with('/') && Path::new(path).is_dir() {
+        key.push('/');
+    }
+    Some((ROOT.to_owned(), key))
+}
+
+// 현재 디렉토리의 (bucket, prefix)
+pub fn current_dir_location() -> Result<(String, String)> {
+    let dir = std::env::current_dir()?;
+    parse_location(&format!("{}/", dir.display()))
+        .ok_or_else(|| eyre::eyre!("can't use {} as a location", dir.display()))
+}
+
+#[derive(Default)]
+pub struct LocalBackend;
+
+impl LocalBackend {
+    // key 는 ROOT 아래의 상대 경로여야 한다. .. 이나 절대 경로로 다른 위치를 가리킬 수 없다
+    fn path(bucket: &str, key: &str) -> Result<PathBuf> {
+        if bucket != ROOT {
+            return Err(eyre::eyre!("unknown local bucket: {}", bucket));
+        }
+        let escapes = Path::new(key).components().any(|c| {
+            matches!(
+                c,
+                Component::ParentDir | Component::RootDir | Component::Prefix(_)
+            )
+        });
+        if escapes {
+            return Err(eyre::eyre!("invalid local key: {}", key));
+        }
+        Ok(Path::new(ROOT).join(key))
+    }
+}
+
+// prefix 아래의 디렉토리("<key>/")와 파일 key 를 상위 디렉토리가 먼저 오도록 구한다
+fn walk(dir: &Path, prefix: &str, keys: &mut Vec<String>) -> Result<()> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)?.filter_map(|e| e.ok()).collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        // symlink 된 디렉토리는 따라가지 않는다
+        if entry.file_type()?.is_dir() {
+            let dir_prefix = format!("{}{}/", prefix, name);
+            keys.push(dir_prefix.clone());
+            walk(&entry.path(), &dir_prefix, keys)?;
+        } else if entry.path().is_file() {
+            keys.push(format!("{}{}", prefix, name));
+        }
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl StorageBackend for LocalBackend {
+    fn scheme(&self) -> Scheme {
+        Scheme::File
+    }
+
+    async fn list_buckets(&self) -> Result<Vec<BucketWithLocation>> {
+        Ok(vec![BucketWithLocation {
+            location: BucketLocationConstraint::from("local"),
+            bucket: Bucket::builder().name(ROOT).build(),
+        }])
+    }
+
+    async fn list_objects(&self, bucket: &str, prefix: &str) -> Result<ListObjectsV2Output> {
+        let mut read_dir = tokio::fs::read_dir(Self::path(bucket, prefix)?).await?;
+        let mut common_prefixes = vec![];
+        let mut contents = vec![];
+        while let Some(entry) = read_dir.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // 깨진 symlink 처럼 metadata 를 구할 수 없는 항목은 건너뛴다
+            let metadata = match tokio::fs::metadata(entry.path()).await {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            if metadata.is_dir() {
+                common_prefixes.push(
+                    CommonPrefix::builder()
+                        .prefix(format!("{}{}/", prefix, name))
+                        .build(),
+                );
+            } else {
+                contents.push(
+                    Object::builder()
+                        .key(format!("{}{}", prefix, name))
+                        .size(metadata.len() as i64)
+                        .set_last_modified(metadata.modified().ok().map(DateTime::from))
+                        .build(),
+                );
+            }
+        }
+
+        // read_dir 의 순서는 정해져 있지 않으므로 S3 처럼 key 순서로 돌려준다
+        common_prefixes.sort_by(|a, b| a.prefix().cmp(&b.prefix()));
+        contents.sort_by(|a, b| a.key().cmp(&b.key()));
+
+        Ok(ListObjectsV2Output::builder()
+            .name(bucket)
+            .prefix(prefix)
+            .set_common_prefixes(Some(common_prefixes))
+            .set_contents(Some(contents))
+            .build())
+    }
+
+    // 전체 key 를 한 페이지로 돌려준다
+    async fn list_keys_page(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        _continuation_token: Option<String>,
+    ) -> Result<(Vec<String>, Option<String>)> {
+        let dir = Self::path(bucket, prefix)?;
+        let prefix = prefix.to_owned();
+        let keys = tokio::task::spawn_blocking(move || {
+            let mut keys = vec![];
+            walk(&dir, &prefix, &mut keys).map(|_| keys)
+        })
+        .await??;
+        Ok((keys, None))
+    }
+
+    async fn head_object(&self, bucket: &str, key: &str) -> Result<Object> {
+        let metadata = tokio::fs::metadata(Self::path(bucket, key)?).await?;
+        Ok(Object::builder()
+            .key(key)
+            .size(metadata.len() as i64)
+            .set_last_modified(metadata.modified().ok().map(DateTime::from))
+            .build())
+    }
+
+    async fn get_object(&self, bucket: &str, key: &str) -> Result<Vec<u8>> {
+        Ok(tokio::fs::read(Self::path(bucket, key)?).await?)
+    }
+
+    async fn get_object_stream(&self, bucket: &str, key: &str) -> Result<(ByteStream, i64)> {
+        let path = Self::path(bucket, key)?;
+        let size = tokio::fs::metadata(&path).await?.len() as i64;
+        Ok((ByteStream::from_path(path).await?, size))
+    }
+
+    async fn put_object_stream(
+        &self,
+        bucket: &str,
+        key: &str,
+        mut body: ByteStream,
+        _size: i64,
+    ) -> Result<()> {
+        let path = Self::path(bucket, key)?;
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let mut file = tokio::fs::File::create(&path).await?;
+        while let Some(chunk) = body.try_next().await? {
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+        Ok(())
+    }
+
+    // '/' 로 끝나는 key 는 디렉토리를 만든다
+    async fn put_object(&self, bucket: &str, key: &str, body: Vec<u8>) -> Result<()> {
+        let path = Self::path(bucket, key)?;
+        if key.ends_with('/') {
+            tokio::fs::create_dir_all(&path).await?;
+            return Ok(());
+        }
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(&path, body).await?;
+        Ok(())
+    }
+
+    // '/' 로 끝나는 key 는 비어있는 디렉토리만 지운다
+    async fn delete_object(&self, bucket: &str, key: &str) -> Result<()> {
+        let path = Self::path(bucket, key)?;
+        if key.ends_with('/') {
+            tokio::fs::remove_dir(&path).await?;
+        } else {
+            tokio::fs::remove_file(&path).await?;
+        }
+        Ok(())
+    }
+
+    async fn copy_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        bucket: &str,
+        key: &str,
+    ) -> Result<()> {
+        if key.ends_with('/') {
+            return self.put_object(bucket, key, vec![]).await;
+        }
+        let path = Self::path(bucket, key)?;
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::copy(Self::path(source_bucket, source_key)?, &path).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_backend() {
+        let dir = std::env::temp_dir().join(format!("tui-s3-local-{}", std::process::id()));
+        let (bucket, prefix) = parse_location(&format!("{}/", dir.display())).unwrap();
+        assert_eq!(bucket, ROOT);
+
+        let backend = LocalBackend;
+        backend
+            .put_object(&bucket, &format!("{}a/b.txt", prefix), b"hello".to_vec())
+            .await
+            .unwrap();
+        backend
+            .copy_object(
+                &bucket,
+                &format!("{}a/b.txt", prefix),
+                &bucket,
+                &format!("{}c.txt", prefix),
+            )
+            .await
+            .unwrap();
+
+        let output = backend.list_objects(&bucket, &prefix).await.unwrap();
+        let prefixes: Vec<_> = output
+            .common_prefixes()
+            .unwrap()
+            .iter()
+            .map(|p| p.prefix().unwrap().to_owned())
+            .collect();
+        assert_eq!(prefixes, vec![format!("{}a/", prefix)]);
+        let object = &output.contents().unwrap()[0];
+        assert_eq!(object.key(), Some(format!("{}c.txt", prefix).as_str()));
+        assert_eq!(object.size(), 5);
+        let head = backend
+            .head_object(&bucket, &format!("{}a/b.txt", prefix))
+            .await
+            .unwrap();
+        assert_eq!(head.size(), 5);
+
+        let (keys, token) = backend
+            .list_keys_page(&bucket, &prefix, None)
+            .await
+            .unwrap();
+        let relative: Vec<_> = keys.iter().map(|k| &k[prefix.len()..]).collect();
+        assert_eq!(relative, vec!["a/", "a/b.txt", "c.txt"]);
+        assert_eq!(token, None);
+
+        // 디렉토리 경로라면 '/' 를 붙인다
+        assert_eq!(
+            parse_location(&format!("file://{}/a", dir.display())).map(|l| l.1),
+            Some(format!("{}a/", prefix))
+        );
+        assert_eq!(parse_location("s3://bucket/a"), None);
+
+        for key in keys.iter().rev() {
+            backend.delete_object(&bucket, key).await.unwrap();
+        }
+        backend.delete_object(&bucket, &prefix).await.unwrap();
+        assert!(!dir.exists());
+
+        // key 로 다른 디렉토리를 가리킬 수 없다
+        let escaping = format!("{}../escaped.txt", prefix);
+        let e = backend
+            .put_object(&bucket, &escaping, b"hello".to_vec())
+            .await
+            .unwrap_err();
+        assert_eq!(e.to_string(), format!("invalid local key: {}", escaping));
+        assert!(backend
+            .copy_object(&bucket, "etc/hostname", &bucket, &escaping)
+            .await
+            .is_err());
+        assert!(backend
+            .copy_object(&bucket, "../etc/hostname", &bucket, "tmp/hostname")
+            .await
+            .is_err());
+        assert!(!dir.with_file_name("escaped.txt").exists());
+    }
+}
@@ -0,0 +1,273 @@
+use async_trait::async_trait;
+use aws_sdk_s3::{
+    model::{Bucket, BucketLocationConstraint, CommonPrefix, Object},
+    output::ListObjectsV2Output,
+};
+use aws_smithy_types::DateTime;
+use eyre::Result;
+use std::{collections::BTreeMap, sync::Mutex};
+
+use super::{Scheme, StorageBackend};
+use crate::s3::client::BucketWithLocation;
+
+// list_keys_page 한 페이지의 최대 key 수 (s3 와 같다)
+const MAX_KEYS: usize = 1000;
+
+#[derive(Clone, Debug)]
+struct MemoryObject {
+    body: Vec<u8>,
+    last_modified: DateTime,
+}
+
+impl MemoryObject {
+    fn new(body: Vec<u8>) -> Self {
+        Self {
+            body,
+            last_modified: DateTime::from(std::time::SystemTime::now()),
+        }
+    }
+
+    fn to_object(&self, key: &str) -> Object {
+        Object::builder()
+            .key(key)
+            .size(self.body.len() as i64)
+            .last_modified(self.last_modified)
+            .build()
+    }
+}
+
+// AWS 없이 controller 와 view model 을 구동하기 위한 저장소. bucket 이름 순서로 정렬된다
+#[derive(Default)]
+pub struct MemoryBackend {
+    buckets: Mutex<BTreeMap<String, BTreeMap<String, MemoryObject>>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn create_bucket(&self, bucket: &str) {
+        self.buckets
+            .lock()
+            .unwrap()
+            .entry(bucket.to_owned())
+            .or_default();
+    }
+
+    // bucket 이 없다면 만든다
+    pub fn insert(&self, bucket: &str, key: &str, body: impl Into<Vec<u8>>) {
+        self.insert_at(
+            bucket,
+            key,
+            body,
+            DateTime::from(std::time::SystemTime::now()),
+        );
+    }
+
+    // 수정 시각을 정해서 넣는다. 화면을 비교하는 테스트에서 사용한다
+    pub fn insert_at(
+        &self,
+        bucket: &str,
+        key: &str,
+        body: impl Into<Vec<u8>>,
+        last_modified: DateTime,
+    ) {
+        self.buckets
+            .lock()
+            .unwrap()
+            .entry(bucket.to_owned())
+            .or_default()
+            .insert(
+                key.to_owned(),
+                MemoryObject {
+                    body: body.into(),
+                    last_modified,
+                },
+            );
+    }
+
+    fn object(&self, bucket: &str, key: &str) -> Result<MemoryObject> {
+        self.buckets
+            .lock()
+            .unwrap()
+            .get(bucket)
+            .ok_or_else(|| eyre::eyre!("NoSuchBucket: {}", bucket))?
+            .get(key)
+            .cloned()
+            .ok_or_else(|| eyre::eyre!("NoSuchKey: {}", key))
+    }
+}
+
+#[async_trait]
+impl StorageBackend for MemoryBackend {
+    fn scheme(&self) -> Scheme {
+        Scheme::S3
+    }
+
+    async fn list_buckets(&self) -> Result<Vec<BucketWithLocation>> {
+        Ok(self
+            .buckets
+            .lock()
+            .unwrap()
+            .keys()
+            .map(|name| BucketWithLocation {
+                location: BucketLocationConstraint::from("memory"),
+                bucket: Bucket::builder().name(name).build(),
+            })
+            .collect())
+    }
+
+    async fn list_objects(&self, bucket: &str, prefix: &str) -> Result<ListObjectsV2Output> {
+        let buckets = self.buckets.lock().unwrap();
+        let objects = buckets
+            .get(bucket)
+            .ok_or_else(|| eyre::eyre!("NoSuchBucket: {}", bucket))?;
+
+        let mut common_prefixes: Vec<String> = vec![];
+        let mut contents = vec![];
+        for (key, object) in objects.range(prefix.to_owned()..) {
+            let rest = match key.strip_prefix(prefix) {
+                Some(rest) => rest,
+                None => break,
+            };
+            match rest.find('/') {
+                Some(i) => {
+                    let common_prefix = format!("{}{}", prefix, &rest[..i + 1]);
+                    if common_prefixes.last() != Some(&common_prefix) {
+                        common_prefixes.push(common_prefix);
+                    }
+                }
+                None => contents.push(object.to_object(key)),
+            }
+        }
+
+        Ok(ListObjectsV2Output::builder()
+            .name(bucket)
+            .prefix(prefix)
+            .set_common_prefixes(Some(
+                common_prefixes
+                    .into_iter()
+                    .map(|p| CommonPrefix::builder().prefix(p).build())
+                    .collect(),
+            ))
+            .set_contents(Some(contents))
+            .build())
+    }
+
+    // continuation token 은 이전 페이지의 마지막 key 다
+    async fn list_keys_page(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        continuation_token: Option<String>,
+    ) -> Result<(Vec<String>, Option<String>)> {
+        let buckets = self.buckets.lock().unwrap();
+        let objects = buckets
+            .get(bucket)
+            .ok_or_else(|| eyre::eyre!("NoSuchBucket: {}", bucket))?;
+
+        let mut keys: Vec<_> = objects
+            .range(prefix.to_owned()..)
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(prefix))
+            .filter(|key| continuation_token.as_ref().is_none_or(|t| *key > t))
+            .take(MAX_KEYS + 1)
+            .cloned()
+            .collect();
+        let next_continuation_token = if keys.len() > MAX_KEYS {
+            keys.pop();
+            keys.last().cloned()
+        } else {
+            None
+        };
+        Ok((keys, next_continuation_token))
+    }
+
+    async fn head_object(&self, bucket: &str, key: &str) -> Result<Object> {
+        Ok(self.object(bucket, key)?.to_object(key))
+    }
+
+    async fn get_object(&self, bucket: &str, key: &str) -> Result<Vec<u8>> {
+        Ok(self.object(bucket, key)?.body)
+    }
+
+    async fn put_object(&self, bucket: &str, key: &str, body: Vec<u8>) -> Result<()> {
+        self.buckets
+            .lock()
+            .unwrap()
+            .get_mut(bucket)
+            .ok_or_else(|| eyre::eyre!("NoSuchBucket: {}", bucket))?
+            .insert(key.to_owned(), MemoryObject::new(body));
+        Ok(())
+    }
+
+    // s3 와 같이 없는 key 를 지워도 성공한다
+    async fn delete_object(&self, bucket: &str, key: &str) -> Result<()> {
+        self.buckets
+            .lock()
+            .unwrap()
+            .get_mut(bucket)
+            .ok_or_else(|| eyre::eyre!("NoSuchBucket: {}", bucket))?
+            .remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_backend() {
+        let backend = MemoryBackend::new();
+        backend.insert("bucket", "a/b/c.txt", "c");
+        backend.insert("bucket", "a/d.txt", "dd");
+        backend.insert("bucket", "e.txt", "e");
+        backend.create_bucket("empty");
+
+        let names = backend.list_bucket_names().await.unwrap();
+        assert_eq!(names, vec!["bucket", "empty"]);
+
+        let output = backend.list_objects("bucket", "a/").await.unwrap();
+        assert_eq!(
+            backend.list_common_prefixes("bucket", "a/").await.unwrap(),
+            vec!["a/b/"]
+        );
+        let keys: Vec<_> = output
+            .contents()
+            .unwrap()
+            .iter()
+            .map(|o| (o.key().unwrap(), o.size()))
+            .collect();
+        assert_eq!(keys, vec![("a/d.txt", 2)]);
+
+        backend
+            .copy_object("bucket", "a/d.txt", "empty", "d.txt")
+            .await
+            .unwrap();
+        backend.delete_object("bucket", "a/d.txt").await.unwrap();
+        assert_eq!(backend.get_object("empty", "d.txt").await.unwrap(), b"dd");
+        assert_eq!(
+            backend.head_object("empty", "d.txt").await.unwrap().size(),
+            2
+        );
+        assert!(backend.head_object("bucket", "a/d.txt").await.is_err());
+
+        // 한 페이지보다 많은 key 는 나눠서 조회한다
+        for i in 0..MAX_KEYS + 5 {
+            backend.insert("empty", &format!("many/{:05}", i), "");
+        }
+        let (first, token) = backend
+            .list_keys_page("empty", "many/", None)
+            .await
+            .unwrap();
+        assert_eq!(first.len(), MAX_KEYS);
+        let (second, token) = backend
+            .list_keys_page("empty", "many/", token)
+            .await
+            .unwrap();
+        assert_eq!(second.len(), 5);
+        assert_eq!(token, None);
+    }
+}
@@ -0,0 +1,166 @@
+use async_trait::async_trait;
+use aws_sdk_s3::{
+    model::{BucketLocationConstraint, Object},
+    output::ListObjectsV2Output,
+    types::ByteStream,
+};
+use eyre::Result;
+use std::time::Duration;
+
+use super::client::{BucketWithLocation, S3ClientConfig};
+
+pub mod local;
+pub mod memory;
+pub mod unconnected;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scheme {
+    S3,
+    // 로컬 디스크. bucket 은 항상 "/" 이다
+    File,
+}
+
+impl Scheme {
+    // bucket/key 혹은 /key 형태의 경로
+    pub fn path(&self, bucket: &str, key: &str) -> String {
+        match self {
+            Scheme::S3 => format!("{}/{}", bucket, key),
+            Scheme::File => format!("{}{}", bucket, key),
+        }
+    }
+
+    pub fn uri(&self, bucket: &str, key: &str) -> String {
+        match self {
+            Scheme::S3 => format!("s3://{}", self.path(bucket, key)),
+            Scheme::File => format!("file://{}", self.path(bucket, key)),
+        }
+    }
+}
+
+// 목록을 조회하고 object 를 읽고 쓰는 저장소. prefix 는 '/' 로 끝나는 key 이다
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    fn scheme(&self) -> Scheme;
+
+    // s3 연결 설정. s3 가 아니라면 None
+    fn connection(&self) -> Option<&S3ClientConfig> {
+        None
+    }
+
+    // credential 을 준 provider 의 이름. 아직 요청하지 않았거나 s3 가 아니라면 None
+    fn credentials_source(&self) -> Option<String> {
+        None
+    }
+
+    // 요청을 보낼 수 있다. 연결하지 못한 tab 이라면 false 이고 이동할 때 다시 연결한다
+    fn is_connected(&self) -> bool {
+        true
+    }
+
+    // tls 인증서를 검증한다. no_verify_ssl 로 연결했다면 false
+    fn verifies_tls(&self) -> bool {
+        true
+    }
+
+    // location 이 LOCATION_RESOLVING 인 bucket 은 bucket_location 으로 다시 조회한다
+    async fn list_buckets(&self) -> Result<Vec<BucketWithLocation>>;
+
+    async fn bucket_location(&self, bucket: &str) -> Result<BucketLocationConstraint> {
+        Err(eyre::eyre!("{}: location isn't available", bucket))
+    }
+
+    // prefix 바로 아래의 common prefix 와 object
+    async fn list_objects(&self, bucket: &str, prefix: &str) -> Result<ListObjectsV2Output>;
+
+    // list_objects 의 한 페이지와 다음 페이지의 continuation token. 목록은 페이지마다 보여준다.
+    // 나눠서 조회하지 않는 저장소는 전체 목록을 한 페이지로 돌려준다
+    async fn list_objects_page(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        _continuation_token: Option<String>,
+    ) -> Result<(ListObjectsV2Output, Option<String>)> {
+        Ok((self.list_objects(bucket, prefix).await?, None))
+    }
+
+    // delimiter 없이 prefix 아래의 모든 key 를 한 페이지씩 조회한다
+    async fn list_keys_page(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        continuation_token: Option<String>,
+    ) -> Result<(Vec<String>, Option<String>)>;
+
+    // 내용 없이 key, 크기, 수정 시각을 구한다
+    async fn head_object(&self, bucket: &str, key: &str) -> Result<Object>;
+
+    async fn get_object(&self, bucket: &str, key: &str) -> Result<Vec<u8>>;
+
+    async fn put_object(&self, bucket: &str, key: &str, body: Vec<u8>) -> Result<()>;
+
+    // 내용과 크기. 다른 저장소로 옮길 때 object 전체를 메모리에 올리지 않도록 나눠서 읽는다
+    async fn get_object_stream(&self, bucket: &str, key: &str) -> Result<(ByteStream, i64)> {
+        let body = self.get_object(bucket, key).await?;
+        let size = body.len() as i64;
+        Ok((ByteStream::from(body), size))
+    }
+
+    // size 바이트인 body 를 받는 대로 쓴다
+    async fn put_object_stream(
+        &self,
+        bucket: &str,
+        key: &str,
+        body: ByteStream,
+        _size: i64,
+    ) -> Result<()> {
+        let body = body.collect().await?.into_bytes().to_vec();
+        self.put_object(bucket, key, body).await
+    }
+
+    async fn delete_object(&self, bucket: &str, key: &str) -> Result<()>;
+
+    async fn copy_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        bucket: &str,
+        key: &str,
+    ) -> Result<()> {
+        let body = self.get_object(source_bucket, source_key).await?;
+        self.put_object(bucket, key, body).await
+    }
+
+    async fn list_bucket_names(&self) -> Result<Vec<String>> {
+        Ok(self
+            .list_buckets()
+            .await?
+            .iter()
+            .filter_map(|b| b.bucket.name().map(|n| n.to_owned()))
+            .collect())
+    }
+
+    async fn list_common_prefixes(&self, bucket: &str, prefix: &str) -> Result<Vec<String>> {
+        Ok(self
+            .list_objects(bucket, prefix)
+            .await?
+            .common_prefixes()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|p| p.prefix().map(|p| p.to_owned()))
+            .collect())
+    }
+
+    // prefix 처럼 보이도록 크기가 0 인 "<key>/" object 를 만든다
+    async fn create_folder(&self, bucket: &str, prefix: &str) -> Result<()> {
+        self.put_object(bucket, prefix, vec![]).await
+    }
+
+    async fn presign_get_object(
+        &self,
+        _bucket: &str,
+        _key: &str,
+        _expires_in: Duration,
+    ) -> Result<String> {
+        Err(eyre::eyre!("presign is only supported for s3"))
+    }
+}
@@ -0,0 +1,71 @@
+use async_trait::async_trait;
+use aws_sdk_s3::{model::Object, output::ListObjectsV2Output};
+use eyre::Result;
+
+use super::{Scheme, StorageBackend};
+use crate::s3::client::{BucketWithLocation, S3ClientConfig};
+
+// 연결하지 못한 s3 tab 의 저장소. 연결 설정과 실패한 이유를 가지고 있다가 다음에 이동할 때 다시
+// 연결한다. 모든 요청은 실패한다
+pub struct UnconnectedBackend {
+    config: S3ClientConfig,
+    reason: String,
+}
+
+impl UnconnectedBackend {
+    pub fn new(config: S3ClientConfig, reason: String) -> Self {
+        Self { config, reason }
+    }
+
+    fn error<T>(&self) -> Result<T> {
+        Err(eyre::eyre!("not connected: {}", self.reason))
+    }
+}
+
+#[async_trait]
+impl StorageBackend for UnconnectedBackend {
+    fn scheme(&self) -> Scheme {
+        Scheme::S3
+    }
+
+    fn connection(&self) -> Option<&S3ClientConfig> {
+        Some(&self.config)
+    }
+
+    fn is_connected(&self) -> bool {
+        false
+    }
+
+    async fn list_buckets(&self) -> Result<Vec<BucketWithLocation>> {
+        self.error()
+    }
+
+    async fn list_objects(&self, _bucket: &str, _prefix: &str) -> Result<ListObjectsV2Output> {
+        self.error()
+    }
+
+    async fn list_keys_page(
+        &self,
+        _bucket: &str,
+        _prefix: &str,
+        _continuation_token: Option<String>,
+    ) -> Result<(Vec<String>, Option<String>)> {
+        self.error()
+    }
+
+    async fn head_object(&self, _bucket: &str, _key: &str) -> Result<Object> {
+        self.error()
+    }
+
+    async fn get_object(&self, _bucket: &str, _key: &str) -> Result<Vec<u8>> {
+        self.error()
+    }
+
+    async fn put_object(&self, _bucket: &str, _key: &str, _body: Vec<u8>) -> Result<()> {
+        self.error()
+    }
+
+    async fn delete_object(&self, _bucket: &str, _key: &str) -> Result<()> {
+        self.error()
+    }
+}
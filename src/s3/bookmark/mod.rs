@@ -0,0 +1,123 @@
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use super::config::Config;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub bucket: String,
+    // '/' 로 끝나거나 비어 있다면 prefix, 아니라면 object key
+    #[serde(default)]
+    pub key: String,
+    pub profile: Option<String>,
+    pub endpoint_url: Option<String>,
+    // 공개된 bucket 이라 서명하지 않고 요청한다
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_sign_request: bool,
+}
+
+impl Bookmark {
+    pub fn is_prefix(&self) -> bool {
+        self.key.is_empty() || self.key.ends_with('/')
+    }
+
+    pub fn s3_uri(&self) -> String {
+        format!("s3://{}/{}", self.bucket, self.key)
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Bookmarks {
+    #[serde(default, rename = "bookmark")]
+    bookmarks: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    pub fn path() -> Option<PathBuf> {
+        Config::dir().map(|dir| dir.join("bookmarks.toml"))
+    }
+
+    // 파일이 없다면 빈 목록을 사용한다
+    pub fn load() -> Result<Bookmarks> {
+        match Self::path() {
+            Some(path) if path.exists() => {
+                let text = std::fs::read_to_string(&path)?;
+                toml::from_str(&text).map_err(|e| eyre::eyre!("{}: {}", path.display(), e))
+            }
+            _ => Ok(Bookmarks::default()),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().ok_or_else(|| eyre::eyre!("can't find the config directory"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn bookmarks(&self) -> &Vec<Bookmark> {
+        &self.bookmarks
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Bookmark> {
+        self.bookmarks.iter().find(|b| b.name == name)
+    }
+
+    // 같은 이름의 bookmark 는 덮어쓰고, 이름 순서를 유지한다
+    pub fn insert(&mut self, bookmark: Bookmark) {
+        self.remove(&bookmark.name);
+        let i = self.bookmarks.partition_point(|b| b.name < bookmark.name);
+        self.bookmarks.insert(i, bookmark);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Bookmark> {
+        let i = self.bookmarks.iter().position(|b| b.name == name)?;
+        Some(self.bookmarks.remove(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bookmarks() {
+        let bookmark = |name: &str, key: &str| Bookmark {
+            name: name.to_owned(),
+            bucket: "bucket".to_owned(),
+            key: key.to_owned(),
+            profile: None,
+            endpoint_url: None,
+            no_sign_request: false,
+        };
+
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.insert(bookmark("logs", "logs/"));
+        bookmarks.insert(bookmark("app", "app/config.json"));
+        bookmarks.insert(Bookmark {
+            profile: Some("prod".to_owned()),
+            ..bookmark("logs", "logs/2024/")
+        });
+        bookmarks.insert(Bookmark {
+            no_sign_request: true,
+            ..bookmark("open", "data/")
+        });
+
+        let names: Vec<_> = bookmarks.bookmarks().iter().map(|b| &b.name).collect();
+        assert_eq!(names, vec!["app", "logs", "open"]);
+        assert_eq!(bookmarks.get("logs").unwrap().key, "logs/2024/");
+        assert!(!bookmarks.get("app").unwrap().is_prefix());
+
+        let text = toml::to_string(&bookmarks).unwrap();
+        // 서명하는 bookmark 에는 no_sign_request 를 쓰지 않는다
+        assert_eq!(text.matches("no_sign_request").count(), 1);
+        assert_eq!(toml::from_str::<Bookmarks>(&text).unwrap(), bookmarks);
+
+        assert!(bookmarks.remove("app").is_some());
+        assert!(bookmarks.remove("app").is_none());
+    }
+}
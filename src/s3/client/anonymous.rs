@@ -0,0 +1,51 @@
+use aws_sdk_s3::types::SdkError;
+use aws_smithy_http::body::SdkBody;
+use aws_smithy_types::retry::ProvideErrorKind;
+use aws_types::Credentials;
+use http::{header::AUTHORIZATION, Request};
+use std::task::{Context, Poll};
+use tower::Service;
+
+// sdk 는 credential 없이 요청을 만들지 못하므로 이 credential 로 서명한 뒤 connector 가
+// 서명을 지운다
+pub(super) fn credentials() -> Credentials {
+    Credentials::new("anonymous", "anonymous", None, None, "Anonymous")
+}
+
+// 서명하지 않은 요청을 보내는 connector
+#[derive(Clone, Debug)]
+pub(super) struct Unsigned<C>(pub(super) C);
+
+impl<C: Service<Request<SdkBody>>> Service<Request<SdkBody>> for Unsigned<C> {
+    type Response = C::Response;
+    type Error = C::Error;
+    type Future = C::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<SdkBody>) -> Self::Future {
+        request.headers_mut().remove(AUTHORIZATION);
+        request.headers_mut().remove("x-amz-security-token");
+        self.0.call(request)
+    }
+}
+
+// 서명하지 않은 요청이 거부되었다면 bucket 이 공개되어 있지 않은 것이다
+pub(super) fn explain<E: ProvideErrorKind>(error: &SdkError<E>, bucket: &str) -> Option<String> {
+    let denied = match error {
+        SdkError::ServiceError { err, raw } => match err.code() {
+            Some(code) => code == "AccessDenied" || code == "AllAccessDisabled",
+            // HEAD 요청의 응답에는 body 가 없다
+            None => raw.http().status().as_u16() == 403,
+        },
+        _ => false,
+    };
+    denied.then(|| {
+        format!(
+            "s3://{}/ isn't public: access without credentials was denied (drop --no-sign-request to sign requests)",
+            bucket
+        )
+    })
+}
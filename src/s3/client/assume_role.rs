@@ -0,0 +1,190 @@
+use aws_sdk_sts::{model::Credentials as StsCredentials, Endpoint};
+use aws_types::{
+    credentials::{self, future, CredentialsError, ProvideCredentials, SharedCredentialsProvider},
+    region::Region,
+    Credentials,
+};
+use eyre::Result;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Mutex, OnceLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{connector, endpoint::Endpoint as S3Endpoint, S3ClientConfig};
+
+// session 이 곧 만료된다면 새로 받는다
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+// :assume-role 로 설정 파일을 바꾸지 않고 만드는 session
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssumeRole {
+    pub role_arn: String,
+    pub mfa_serial: Option<String>,
+}
+
+// config 로 연결하려면 mfa code 가 필요하다. code 를 받아 start_mfa_session 을 호출한 뒤
+// 다시 연결한다
+#[derive(Debug)]
+pub struct MfaRequired {
+    pub mfa_serial: String,
+    pub config: S3ClientConfig,
+}
+
+impl fmt::Display for MfaRequired {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "an MFA code for {} is required", self.mfa_serial)
+    }
+}
+
+impl std::error::Error for MfaRequired {}
+
+// mfa serial 마다 GetSessionToken 으로 받은 credential. 만료될 때까지 모든 client 가 함께 쓴다
+fn mfa_sessions() -> &'static Mutex<HashMap<String, Credentials>> {
+    static MFA_SESSIONS: OnceLock<Mutex<HashMap<String, Credentials>>> = OnceLock::new();
+    MFA_SESSIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+pub(super) fn mfa_session(mfa_serial: &str) -> Option<Credentials> {
+    mfa_sessions()
+        .lock()
+        .unwrap()
+        .get(mfa_serial)
+        .filter(|c| {
+            c.expiry()
+                .is_none_or(|expiry| expiry > SystemTime::now() + EXPIRY_MARGIN)
+        })
+        .cloned()
+}
+
+// endpoint 를 지정했다면 STS 도 같은 endpoint 로 요청한다 (MinIO 는 같은 주소에서 STS 를 제공한다).
+// proxy 와 tls 설정도 s3 와 같다
+pub(super) fn sts_client(
+    credentials: SharedCredentialsProvider,
+    region: Option<Region>,
+    endpoint: &S3Endpoint,
+) -> Result<aws_sdk_sts::Client> {
+    let builder = aws_sdk_sts::Config::builder()
+        .credentials_provider(credentials)
+        .region(region.or_else(|| Some(Region::new("us-east-1"))));
+    let builder = match endpoint.url.as_ref() {
+        Some(endpoint_url) => builder.endpoint_resolver(Endpoint::immutable(endpoint_url.parse()?)),
+        None => builder,
+    };
+    Ok(aws_sdk_sts::Client::from_conf_conn(
+        builder.build(),
+        connector::build(endpoint)?,
+    ))
+}
+
+// base credential 과 mfa code 로 session 을 만들고 기억한다
+pub(super) async fn get_session_token(
+    sts: &aws_sdk_sts::Client,
+    mfa_serial: &str,
+    token_code: &str,
+) -> Result<()> {
+    let output = sts
+        .get_session_token()
+        .serial_number(mfa_serial)
+        .token_code(token_code)
+        .send()
+        .await?;
+    let credentials = into_credentials(output.credentials, "MfaSession")?;
+    mfa_sessions()
+        .lock()
+        .unwrap()
+        .insert(mfa_serial.to_owned(), credentials);
+    Ok(())
+}
+
+fn into_credentials(
+    credentials: Option<StsCredentials>,
+    provider_name: &'static str,
+) -> credentials::Result {
+    let credentials =
+        credentials.ok_or_else(|| CredentialsError::unhandled("STS returned no credentials"))?;
+    let expiry = credentials
+        .expiration
+        .and_then(|e| SystemTime::try_from(e).ok());
+    match (credentials.access_key_id, credentials.secret_access_key) {
+        (Some(access_key_id), Some(secret_access_key)) => Ok(Credentials::new(
+            access_key_id,
+            secret_access_key,
+            credentials.session_token,
+            expiry,
+            provider_name,
+        )),
+        _ => Err(CredentialsError::unhandled(
+            "STS returned incomplete credentials",
+        )),
+    }
+}
+
+// 기억해둔 mfa session. 만료되었다면 code 를 다시 입력해야 한다
+#[derive(Debug)]
+pub(super) struct MfaSessionProvider {
+    mfa_serial: String,
+}
+
+impl MfaSessionProvider {
+    pub(super) fn new(mfa_serial: &str) -> Self {
+        Self {
+            mfa_serial: mfa_serial.to_owned(),
+        }
+    }
+}
+
+impl ProvideCredentials for MfaSessionProvider {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        future::ProvideCredentials::ready(mfa_session(&self.mfa_serial).ok_or_else(|| {
+            CredentialsError::provider_error(format!(
+                "the MFA session for {} has expired: open the profile again to enter a new code",
+                self.mfa_serial
+            ))
+        }))
+    }
+}
+
+// role 의 credential. LazyCachingCredentialsProvider 로 감싸 만료되기 전에 다시 assume 한다
+#[derive(Debug)]
+pub(super) struct AssumeRoleProvider {
+    sts: aws_sdk_sts::Client,
+    role_arn: String,
+    session_name: String,
+}
+
+impl AssumeRoleProvider {
+    pub(super) fn new(sts: aws_sdk_sts::Client, role_arn: &str) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self {
+            sts,
+            role_arn: role_arn.to_owned(),
+            session_name: format!("tui-s3-{}", now.as_millis()),
+        }
+    }
+}
+
+impl ProvideCredentials for AssumeRoleProvider {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        future::ProvideCredentials::new(async move {
+            let output = self
+                .sts
+                .assume_role()
+                .role_arn(&self.role_arn)
+                .role_session_name(&self.session_name)
+                .send()
+                .await
+                .map_err(CredentialsError::provider_error)?;
+            into_credentials(output.credentials, "AssumeRole")
+        })
+    }
+}
@@ -0,0 +1,294 @@
+use aws_config::{
+    ecs::EcsCredentialsProvider,
+    environment::EnvironmentVariableCredentialsProvider,
+    imds::credentials::ImdsCredentialsProvider,
+    meta::credentials::{CredentialsProviderChain, LazyCachingCredentialsProvider},
+    profile::ProfileFileCredentialsProvider,
+    provider_config::ProviderConfig,
+    web_identity_token::WebIdentityTokenCredentialsProvider,
+};
+use aws_sdk_s3::{types::SdkError, Region};
+use aws_smithy_types::retry::ProvideErrorKind;
+use aws_types::credentials::{future, ProvideCredentials, SharedCredentialsProvider};
+use std::sync::{Arc, Mutex};
+
+use eyre::Result;
+
+use super::{
+    anonymous,
+    assume_role::{self, AssumeRoleProvider, MfaRequired, MfaSessionProvider},
+    endpoint, S3ClientConfig,
+};
+use crate::s3::profile::{self, AwsProfile, ProfileKind};
+
+// credential 을 준 provider 의 이름. 아직 credential 을 구하지 않았다면 None
+#[derive(Clone, Debug, Default)]
+pub(super) struct CredentialsSource(Arc<Mutex<Option<String>>>);
+
+impl CredentialsSource {
+    pub(super) fn get(&self) -> Option<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+// credential 을 구하는데 성공하면 source 에 이름을 기록한다
+#[derive(Debug)]
+struct Recorded<P> {
+    name: String,
+    provider: P,
+    source: CredentialsSource,
+}
+
+impl<P: ProvideCredentials> ProvideCredentials for Recorded<P> {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        future::ProvideCredentials::new(async move {
+            let credentials = self.provider.provide_credentials().await?;
+            *self.source.0.lock().unwrap() = Some(self.name.clone());
+            Ok(credentials)
+        })
+    }
+}
+
+// role 을 assume 해서 credential 을 구하는 설정. mfa 를 사용하는 profile 이거나
+// :assume-role 로 만든 session 이다
+struct RoleSpec {
+    role_arn: String,
+    mfa_serial: Option<String>,
+    // role 을 assume 할 credential 의 연결 설정
+    base: S3ClientConfig,
+    name: &'static str,
+}
+
+fn role_spec(config: &S3ClientConfig, profile: Option<&AwsProfile>) -> Option<RoleSpec> {
+    if let Some(assume_role) = config.assume_role.as_ref() {
+        return Some(RoleSpec {
+            role_arn: assume_role.role_arn.clone(),
+            mfa_serial: assume_role.mfa_serial.clone(),
+            base: S3ClientConfig {
+                assume_role: None,
+                ..config.clone()
+            },
+            name: "assume-role",
+        });
+    }
+    // mfa 가 없는 role 은 sdk 의 profile provider 가 처리한다
+    let profile = profile.filter(|p| p.kind == ProfileKind::Role)?;
+    Some(RoleSpec {
+        role_arn: profile.detail.clone()?,
+        mfa_serial: Some(profile.mfa_serial.clone()?),
+        base: S3ClientConfig {
+            profile: Some(profile.source_profile.clone()?),
+            assume_role: None,
+            ..config.clone()
+        },
+        name: "role+mfa",
+    })
+}
+
+// profile 을 지정했다면 그 profile 만 사용하고, 아니라면 sdk 의 기본 순서(환경 변수, profile,
+// web identity, ecs, instance metadata)로 credential 을 찾는다
+pub(super) async fn provider(
+    config: &S3ClientConfig,
+    region: Option<Region>,
+) -> Result<(SharedCredentialsProvider, CredentialsSource)> {
+    let source = CredentialsSource::default();
+    if config.no_sign_request {
+        *source.0.lock().unwrap() = Some("anonymous".to_owned());
+        let provider = SharedCredentialsProvider::new(anonymous::credentials());
+        return Ok((provider, source));
+    }
+    let conf = ProviderConfig::default().with_region(region.clone());
+
+    let profile_name = config.profile_name();
+    let profile = profile::load()
+        .await
+        .into_iter()
+        .find(|p| p.name == profile_name);
+    if let Some(role) = role_spec(config, profile.as_ref()) {
+        return role_provider(config, role, region).await;
+    }
+
+    let profile_provider = Recorded {
+        // profile 이 credential 을 얻는 방법 (sso, role, process, keys)
+        name: profile
+            .map(|p| p.kind.to_string())
+            .unwrap_or_else(|| "profile".to_owned()),
+        provider: ProfileFileCredentialsProvider::builder()
+            .configure(&conf)
+            .profile_name(&profile_name)
+            .build(),
+        source: source.clone(),
+    };
+    let chain = if config.profile.is_some() {
+        CredentialsProviderChain::first_try("Profile", profile_provider)
+    } else {
+        CredentialsProviderChain::first_try(
+            "Environment",
+            Recorded {
+                name: "environment".to_owned(),
+                provider: EnvironmentVariableCredentialsProvider::new(),
+                source: source.clone(),
+            },
+        )
+        .or_else("Profile", profile_provider)
+        .or_else(
+            "WebIdentityToken",
+            Recorded {
+                name: "web identity".to_owned(),
+                provider: WebIdentityTokenCredentialsProvider::builder()
+                    .configure(&conf)
+                    .build(),
+                source: source.clone(),
+            },
+        )
+        .or_else(
+            "EcsContainer",
+            Recorded {
+                name: "ecs".to_owned(),
+                provider: EcsCredentialsProvider::builder().configure(&conf).build(),
+                source: source.clone(),
+            },
+        )
+        .or_else(
+            "Ec2InstanceMetadata",
+            Recorded {
+                name: "instance metadata".to_owned(),
+                provider: ImdsCredentialsProvider::builder().configure(&conf).build(),
+                source: source.clone(),
+            },
+        )
+    };
+
+    let provider = LazyCachingCredentialsProvider::builder()
+        .configure(&conf)
+        .load(chain)
+        .build();
+    Ok((SharedCredentialsProvider::new(provider), source))
+}
+
+// base 의 credential 로 role 을 assume 한다. mfa 를 사용한다면 입력받은 code 로 만든 session 이
+// 있어야 한다
+async fn role_provider(
+    config: &S3ClientConfig,
+    role: RoleSpec,
+    region: Option<Region>,
+) -> Result<(SharedCredentialsProvider, CredentialsSource)> {
+    let base = match role.mfa_serial.as_deref() {
+        Some(mfa_serial) if assume_role::mfa_session(mfa_serial).is_none() => {
+            return Err(MfaRequired {
+                mfa_serial: mfa_serial.to_owned(),
+                config: config.clone(),
+            }
+            .into())
+        }
+        Some(mfa_serial) => SharedCredentialsProvider::new(MfaSessionProvider::new(mfa_serial)),
+        None => Box::pin(provider(&role.base, region.clone())).await?.0,
+    };
+    let endpoint = endpoint::resolve(&config.endpoints, config.endpoint_url.as_deref())?;
+    let sts = assume_role::sts_client(base, region.clone(), &endpoint)?;
+
+    let source = CredentialsSource::default();
+    let provider = LazyCachingCredentialsProvider::builder()
+        .configure(&ProviderConfig::default().with_region(region))
+        .load(Recorded {
+            name: role.name.to_owned(),
+            provider: AssumeRoleProvider::new(sts, &role.role_arn),
+            source: source.clone(),
+        })
+        .build();
+    Ok((SharedCredentialsProvider::new(provider), source))
+}
+
+// config 의 role 이 사용하는 mfa 장치와 code 로 session 을 만든다
+pub(super) async fn start_mfa_session(config: &S3ClientConfig, token_code: &str) -> Result<()> {
+    let profile_name = config.profile_name();
+    let profile = profile::load()
+        .await
+        .into_iter()
+        .find(|p| p.name == profile_name);
+    let no_mfa = || eyre::eyre!("{} doesn't use MFA", profile_name);
+    let role = role_spec(config, profile.as_ref()).ok_or_else(no_mfa)?;
+    let mfa_serial = role.mfa_serial.as_deref().ok_or_else(no_mfa)?;
+    let region = config.region.clone().map(Region::new);
+    let (base, _) = Box::pin(provider(&role.base, region.clone())).await?;
+    let endpoint = endpoint::resolve(&config.endpoints, config.endpoint_url.as_deref())?;
+    let sts = assume_role::sts_client(base, region, &endpoint)?;
+    assume_role::get_session_token(&sts, mfa_serial, token_code).await
+}
+
+// credential 을 구하지 못했거나 만료되었다면 해결 방법을 알려주는 메시지
+pub(super) fn explain<E>(
+    error: &SdkError<E>,
+    profile_name: &str,
+    source: Option<&str>,
+) -> Option<String>
+where
+    E: ProvideErrorKind + std::error::Error + 'static,
+{
+    let login = format!(
+        "run `aws sso login --profile {}` or refresh the credentials",
+        profile_name
+    );
+    match error {
+        SdkError::ServiceError { err, .. } => match err.code() {
+            Some("ExpiredToken") | Some("TokenRefreshRequired") => Some(format!(
+                "the session token from {} has expired: {}",
+                source.unwrap_or("the credentials provider"),
+                login
+            )),
+            // credential 이 없으면 서명하지 않은 채로 요청한다
+            Some("AccessDenied") if source.is_none() => Some(format!(
+                "no credentials found: set AWS_ACCESS_KEY_ID, use --profile or add the {} profile to ~/.aws/credentials",
+                profile_name
+            )),
+            _ => None,
+        },
+        SdkError::ConstructionFailure(_) => {
+            let message = error.to_string();
+            let lower = message.to_lowercase();
+            // mfa session 은 code 를 다시 입력해야 한다
+            if let Some(start) = message.find("the MFA session") {
+                Some(message[start..].to_owned())
+            } else if lower.contains("expired")
+                || message.contains("UnauthorizedException")
+                || message.contains("InvalidGrantException")
+            {
+                Some(format!(
+                    "the credentials of profile {} have expired: {} ({})",
+                    profile_name, login, message
+                ))
+            } else if lower.contains("credentials") {
+                Some(format!(
+                    "can't load the credentials of profile {}: {}",
+                    profile_name, message
+                ))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_s3::error::ListBucketsError;
+
+    #[test]
+    fn test_explain_expired_sso_token() {
+        let error: SdkError<ListBucketsError> = SdkError::ConstructionFailure(
+            "failed to load credentials: UnauthorizedException: Session token not found or invalid"
+                .into(),
+        );
+        let message = explain(&error, "dev", None).unwrap();
+        assert!(message.starts_with("the credentials of profile dev have expired"));
+        assert!(message.contains("aws sso login --profile dev"));
+
+        let error: SdkError<ListBucketsError> = SdkError::ConstructionFailure("timeout".into());
+        assert_eq!(explain(&error, "dev", None), None);
+    }
+}
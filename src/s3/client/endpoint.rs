@@ -0,0 +1,77 @@
+use eyre::Result;
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+
+use crate::s3::config::EndpointConfig;
+
+// 설정 파일의 [endpoints] 와 모든 endpoint 에 적용할 명령행 옵션
+#[derive(Debug, Default, PartialEq)]
+struct Table {
+    named: BTreeMap<String, EndpointConfig>,
+    defaults: EndpointConfig,
+}
+
+// client 마다 S3ClientConfig 로 넘겨받는 endpoint 목록. 같은 목록을 여러 client 가 함께 쓴다
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Endpoints(Arc<Table>);
+
+impl Endpoints {
+    // defaults 는 자신의 ca_bundle 이나 no_verify_ssl 이 없는 endpoint 에 적용한다
+    pub fn new(named: BTreeMap<String, EndpointConfig>, defaults: EndpointConfig) -> Self {
+        Self(Arc::new(Table { named, defaults }))
+    }
+}
+
+// 요청을 보낼 주소와 연결 방법
+#[derive(Clone, Debug, Default)]
+pub(super) struct Endpoint {
+    // None 이라면 AWS 의 endpoint 를 사용한다
+    pub(super) url: Option<String>,
+    pub(super) region: Option<String>,
+    pub(super) ca_bundle: Option<PathBuf>,
+    pub(super) no_verify_ssl: bool,
+    pub(super) proxy: Option<String>,
+}
+
+// --endpoint-url 은 [endpoints] 의 이름이나 url 이다
+pub(super) fn resolve(endpoints: &Endpoints, endpoint_url: Option<&str>) -> Result<Endpoint> {
+    let endpoints = &endpoints.0;
+    let named = endpoint_url.and_then(|e| {
+        endpoints
+            .named
+            .get_key_value(e)
+            .or_else(|| endpoints.named.iter().find(|(_, c)| c.url == e))
+    });
+    if let Some((name, config)) = named {
+        if config.url.is_empty() {
+            return Err(eyre::eyre!("endpoint {} has no url", name));
+        }
+        if config.force_path_style == Some(false) {
+            return Err(eyre::eyre!(
+                "endpoint {}: virtual-hosted style isn't supported, buckets are always addressed path-style",
+                name
+            ));
+        }
+    }
+    let named = named.map(|(_, config)| config);
+    let defaults = &endpoints.defaults;
+    Ok(Endpoint {
+        url: named
+            .map(|c| c.url.clone())
+            .or_else(|| endpoint_url.map(|e| e.to_owned())),
+        region: named.and_then(|c| c.region.clone()),
+        ca_bundle: named
+            .and_then(|c| c.ca_bundle.clone())
+            .or_else(|| defaults.ca_bundle.clone())
+            .or_else(|| {
+                std::env::var_os("AWS_CA_BUNDLE")
+                    .filter(|v| !v.is_empty())
+                    .map(PathBuf::from)
+            }),
+        // endpoint 의 설정이 명령행 옵션보다 우선한다. false 라면 --no-verify-ssl 이어도 검증한다
+        no_verify_ssl: named
+            .and_then(|c| c.no_verify_ssl)
+            .or(defaults.no_verify_ssl)
+            .unwrap_or(false),
+        proxy: named.and_then(|c| c.proxy.clone()),
+    })
+}
@@ -1,127 +1,527 @@
+use async_trait::async_trait;
+use aws_smithy_types::retry::ProvideErrorKind;
 use eyre::Result;
+use std::{future::Future, time::Duration};
 
 use aws_config::meta::region::RegionProviderChain;
 use aws_sdk_s3::{
-    model::{Bucket, BucketLocationConstraint},
+    model::{Bucket, BucketLocationConstraint, Object, ObjectStorageClass},
     output::ListObjectsV2Output,
+    presigning::config::PresigningConfig,
+    types::{ByteStream, SdkError},
     Client, Endpoint, Region,
 };
+
+use super::{
+    backend::{Scheme, StorageBackend},
+    path,
+};
+
+mod anonymous;
+pub mod assume_role;
+mod connector;
+mod credentials;
+pub mod endpoint;
+pub mod region;
+mod region_cache;
+pub mod request_error;
+
+use assume_role::AssumeRole;
+use credentials::CredentialsSource;
+use endpoint::Endpoints;
+use region::{location_to_region, redirect_region, RegionalClients};
+use request_error::RequestError;
+
+// client 를 만들때 사용하는 설정
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct S3ClientConfig {
+    pub profile: Option<String>,
+    pub endpoint_url: Option<String>,
+    pub region: Option<String>,
+    // profile 의 credential 로 assume 할 role
+    pub assume_role: Option<AssumeRole>,
+    // 공개된 bucket 은 credential 없이 서명하지 않고 요청한다
+    pub no_sign_request: bool,
+    // endpoint_url 을 찾을 설정 파일의 [endpoints] 와 명령행의 tls 옵션
+    pub endpoints: Endpoints,
+}
+
+impl S3ClientConfig {
+    // profile 을 지정하지 않았다면 AWS_PROFILE, default 순서로 사용한다
+    pub fn profile_name(&self) -> String {
+        self.profile
+            .clone()
+            .or_else(|| std::env::var("AWS_PROFILE").ok().filter(|p| !p.is_empty()))
+            .unwrap_or_else(|| "default".to_owned())
+    }
+}
+
 pub struct S3Client {
-    client: Client,
+    // bucket 마다 그 bucket 의 region 으로 요청한다
+    clients: RegionalClients,
+    config: S3ClientConfig,
+    credentials_source: CredentialsSource,
+    // tls 인증서를 검증하지 않는다
+    no_verify_ssl: bool,
 }
 
+// list_buckets 가 아직 region 을 찾지 못한 bucket 의 location
+pub const LOCATION_RESOLVING: &str = "resolving…";
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct BucketWithLocation {
     pub location: BucketLocationConstraint,
     pub bucket: Bucket,
 }
 
+impl BucketWithLocation {
+    pub fn is_resolving(&self) -> bool {
+        self.location.as_str() == LOCATION_RESOLVING
+    }
+}
+
 impl S3Client {
-    pub async fn list_buckets(&self) -> Result<Vec<BucketWithLocation>> {
-        let output = self.client.list_buckets().send().await?;
-        if let Some(buckets) = output.buckets() {
-            // bucket 과 location을 함께 구한다
-            let location_bucket_list = futures::future::join_all(
-                buckets
-                    .into_iter()
-                    .map(|b| (b.name(), b.to_owned()))
-                    .filter(|(name, _)| name.is_some())
-                    .map(|(name, bucket)| {
-                        let get_location = self
-                            .client
-                            .get_bucket_location()
-                            .bucket(name.unwrap())
-                            .send();
-                        let bucket = futures_util::future::ready(bucket);
-                        futures_util::future::join(get_location, bucket)
-                    }),
+    pub async fn new(config: S3ClientConfig) -> Result<S3Client> {
+        use aws_config::profile::ProfileFileRegionProvider;
+        let profile_name = config.profile_name();
+        let endpoint = endpoint::resolve(&config.endpoints, config.endpoint_url.as_deref())?;
+        // 지정한 region, endpoint 의 region, profile 의 region, 기본 region 순서로 사용한다
+        let region = RegionProviderChain::first_try(config.region.clone().map(Region::new))
+            .or_else(endpoint.region.clone().map(Region::new))
+            .or_else(
+                ProfileFileRegionProvider::builder()
+                    .profile_name(&profile_name)
+                    .build(),
             )
+            .or_default_provider()
+            .region()
             .await;
-            Ok(location_bucket_list
+        let (provider, credentials_source) = credentials::provider(&config, region.clone()).await?;
+        let loader = aws_config::from_env()
+            .credentials_provider(provider)
+            .region(region);
+
+        let loader = if let Some(endpoint_url) = endpoint.url.as_ref() {
+            loader.endpoint_resolver(Endpoint::immutable(endpoint_url.parse()?))
+        } else {
+            loader
+        };
+
+        let clients = RegionalClients::new(
+            loader.load().await,
+            connector::build(&endpoint)?,
+            config.no_sign_request,
+        );
+
+        Ok(S3Client {
+            clients,
+            config,
+            credentials_source,
+            no_verify_ssl: endpoint.no_verify_ssl,
+        })
+    }
+
+    // mfa code 로 config 의 role 이 사용할 session 을 만든다. session 이 만료될 때까지 같은 mfa
+    // 장치를 사용하는 client 는 code 를 다시 묻지 않는다
+    pub async fn start_mfa_session(config: &S3ClientConfig, token_code: &str) -> Result<()> {
+        credentials::start_mfa_session(config, token_code).await
+    }
+
+    // region cache 의 key. 설정 파일의 endpoint 라면 그 이름이다
+    fn endpoint_name(&self) -> &str {
+        self.config.endpoint_url.as_deref().unwrap_or("aws")
+    }
+
+    // credential 문제라면 원인과 해결 방법을 알려준다. 응답의 code 와 request id 는 error log 에
+    // 남는다
+    fn explain<E>(&self, bucket: Option<&str>, error: SdkError<E>) -> eyre::Report
+    where
+        E: ProvideErrorKind + std::error::Error + Send + Sync + 'static,
+    {
+        let explanation = bucket
+            .filter(|_| self.config.no_sign_request)
+            .and_then(|bucket| anonymous::explain(&error, bucket))
+            .or_else(|| {
+                let source = self.credentials_source.get();
+                credentials::explain(&error, &self.config.profile_name(), source.as_deref())
+            });
+        RequestError::new(error, explanation).into()
+    }
+
+    // bucket 의 region 으로 요청한다. 다른 region 이라는 응답을 받으면 그 region 을 기억하고
+    // 다시 요청한다
+    async fn send<T, E, F, Fut>(&self, bucket: &str, request: F) -> Result<T>
+    where
+        F: Fn(Client) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<T, SdkError<E>>> + Send,
+        E: ProvideErrorKind + std::error::Error + Send + Sync + 'static,
+    {
+        let (client, region) = self.clients.bucket_client(bucket);
+        match request(client).await {
+            Err(e) => match redirect_region(&e) {
+                Some(redirect) if redirect != region => {
+                    self.clients.set_bucket_region(bucket, redirect.clone());
+                    request(self.clients.client(&redirect))
+                        .await
+                        .map_err(|e| self.explain(Some(bucket), e))
+                }
+                _ => Err(self.explain(Some(bucket), e)),
+            },
+            Ok(output) => {
+                self.clients.remember_bucket(bucket, &region);
+                Ok(output)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl StorageBackend for S3Client {
+    fn scheme(&self) -> Scheme {
+        Scheme::S3
+    }
+
+    fn connection(&self) -> Option<&S3ClientConfig> {
+        Some(&self.config)
+    }
+
+    fn credentials_source(&self) -> Option<String> {
+        self.credentials_source.get()
+    }
+
+    fn verifies_tls(&self) -> bool {
+        !self.no_verify_ssl
+    }
+
+    async fn list_buckets(&self) -> Result<Vec<BucketWithLocation>> {
+        // ListBuckets 는 credential 이 필요하므로 지금까지 방문한 bucket 을 보여준다
+        if self.config.no_sign_request {
+            return Ok(self
+                .clients
+                .known_buckets()
                 .into_iter()
-                .filter(|(location, _)| location.is_ok())
-                .map(|(location, bucket)| {
-                    (
-                        location.unwrap().location_constraint().unwrap().to_owned(),
-                        bucket,
-                    )
+                .map(|(bucket, region)| BucketWithLocation {
+                    location: BucketLocationConstraint::from(region.as_str()),
+                    bucket: Bucket::builder().name(bucket).build(),
                 })
-                .map(|(location, bucket)| BucketWithLocation { location, bucket })
-                .collect())
-        } else {
-            Ok(vec![])
+                .collect());
         }
+        let output = self
+            .clients
+            .default_client()
+            .list_buckets()
+            .send()
+            .await
+            .map_err(|e| self.explain(None, e))?;
+        // location 은 나중에 bucket_location 으로 조회한다. 이번 실행이나 이전 실행에서 찾은
+        // region 은 바로 보여준다
+        Ok(output
+            .buckets()
+            .unwrap_or_default()
+            .iter()
+            .filter(|bucket| bucket.name().is_some())
+            .map(|bucket| {
+                let name = bucket.name().unwrap_or_default();
+                let region = self.clients.known_region(name).or_else(|| {
+                    let region = region_cache::get(self.endpoint_name(), name)?;
+                    self.clients.set_bucket_region(name, region.clone());
+                    Some(region)
+                });
+                BucketWithLocation {
+                    location: BucketLocationConstraint::from(
+                        region.as_deref().unwrap_or(LOCATION_RESOLVING),
+                    ),
+                    bucket: bucket.to_owned(),
+                }
+            })
+            .collect())
     }
 
-    pub async fn list_objects(&self, bucket: &str, prefix: &str) -> Result<ListObjectsV2Output> {
-        let mut next_continuation_token: Option<String> = None;
-        let mut object_list = vec![];
-        let mut common_prefixes = vec![];
+    async fn bucket_location(&self, bucket: &str) -> Result<BucketLocationConstraint> {
+        let output = self
+            .clients
+            .default_client()
+            .get_bucket_location()
+            .bucket(bucket)
+            .send()
+            .await
+            .map_err(|e| self.explain(Some(bucket), e))?;
+        // us-east-1 은 location constraint 가 비어있다
+        let region = location_to_region(
+            output
+                .location_constraint()
+                .map(|l| l.as_str())
+                .unwrap_or_default(),
+        );
+        self.clients.set_bucket_region(bucket, region.clone());
+        region_cache::store(self.endpoint_name(), bucket, &region);
+        Ok(BucketLocationConstraint::from(region.as_str()))
+    }
 
+    async fn list_objects(&self, bucket: &str, prefix: &str) -> Result<ListObjectsV2Output> {
+        let mut continuation_token = None;
+        let mut contents = vec![];
+        let mut common_prefixes = vec![];
         loop {
-            let list_output = self
-                .client
-                .list_objects_v2()
-                .set_continuation_token(next_continuation_token.clone())
-                .bucket(bucket)
-                .delimiter("/")
-                .prefix(prefix)
-                .send()
+            let (page, next_continuation_token) = self
+                .list_objects_page(bucket, prefix, continuation_token)
                 .await?;
-
-            let (contents, prefixes, token) = (
-                list_output.contents,
-                list_output.common_prefixes,
-                list_output.next_continuation_token,
-            );
-
-            next_continuation_token = token;
-            if let Some(objects_vec) = contents {
-                object_list.extend(objects_vec);
-            }
-            if let Some(prefixes) = prefixes {
-                common_prefixes.extend(prefixes);
-            }
-            if next_continuation_token.is_none() {
+            contents.extend(page.contents.unwrap_or_default());
+            common_prefixes.extend(page.common_prefixes.unwrap_or_default());
+            continuation_token = next_continuation_token;
+            if continuation_token.is_none() {
                 break;
             }
         }
 
         Ok(ListObjectsV2Output::builder()
-            .set_contents(Some(object_list))
+            .set_contents(Some(contents))
             .name(bucket)
             .prefix(prefix)
             .set_common_prefixes(Some(common_prefixes))
             .build())
     }
 
-    pub async fn new(
-        profile_name: Option<&String>,
-        endpoint_url: Option<&String>,
-    ) -> Result<S3Client> {
-        use aws_config::profile::ProfileFileCredentialsProvider;
-        let provider = ProfileFileCredentialsProvider::builder()
-            .profile_name(
-                profile_name
-                    .map(|p| p.as_str())
-                    .or(Some("default"))
-                    .unwrap(),
+    async fn list_objects_page(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        continuation_token: Option<String>,
+    ) -> Result<(ListObjectsV2Output, Option<String>)> {
+        let mut list_output = self
+            .send(bucket, |client| {
+                client
+                    .list_objects_v2()
+                    .set_continuation_token(continuation_token.clone())
+                    .bucket(bucket)
+                    .delimiter("/")
+                    .prefix(prefix)
+                    .send()
+            })
+            .await?;
+        // 페이지마다 어느 목록의 것인지 알 수 있도록 한다
+        list_output.name = Some(bucket.to_owned());
+        list_output.prefix = Some(prefix.to_owned());
+        let next_continuation_token = list_output.next_continuation_token.take();
+        Ok((list_output, next_continuation_token))
+    }
+
+    // location 조회 없이 bucket 이름만 구한다
+    async fn list_bucket_names(&self) -> Result<Vec<String>> {
+        if self.config.no_sign_request {
+            return Ok(self
+                .clients
+                .known_buckets()
+                .into_iter()
+                .map(|(bucket, _)| bucket)
+                .collect());
+        }
+        let output = self
+            .clients
+            .default_client()
+            .list_buckets()
+            .send()
+            .await
+            .map_err(|e| self.explain(None, e))?;
+        Ok(output
+            .buckets()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|b| b.name().map(|n| n.to_owned()))
+            .collect())
+    }
+
+    // prefix 바로 아래의 common prefix 를 한 페이지만 조회한다
+    async fn list_common_prefixes(&self, bucket: &str, prefix: &str) -> Result<Vec<String>> {
+        let list_output = self
+            .send(bucket, |client| {
+                client
+                    .list_objects_v2()
+                    .bucket(bucket)
+                    .delimiter("/")
+                    .prefix(prefix)
+                    .send()
+            })
+            .await?;
+
+        Ok(list_output
+            .common_prefixes()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|p| p.prefix().map(|p| p.to_owned()))
+            .collect())
+    }
+
+    // delimiter 없이 prefix 아래의 모든 key 를 한 페이지씩 조회한다
+    async fn list_keys_page(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        continuation_token: Option<String>,
+    ) -> Result<(Vec<String>, Option<String>)> {
+        let list_output = self
+            .send(bucket, |client| {
+                client
+                    .list_objects_v2()
+                    .set_continuation_token(continuation_token.clone())
+                    .bucket(bucket)
+                    .prefix(prefix)
+                    .send()
+            })
+            .await?;
+
+        let keys = list_output
+            .contents()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|o| o.key().map(|k| k.to_owned()))
+            .collect();
+
+        Ok((keys, list_output.next_continuation_token))
+    }
+
+    async fn head_object(&self, bucket: &str, key: &str) -> Result<Object> {
+        let output = self
+            .send(bucket, |client| {
+                client.head_object().bucket(bucket).key(key).send()
+            })
+            .await?;
+        Ok(Object::builder()
+            .key(key)
+            .size(output.content_length())
+            .set_last_modified(output.last_modified().cloned())
+            .set_e_tag(output.e_tag().map(|e| e.to_owned()))
+            .set_storage_class(
+                output
+                    .storage_class()
+                    .map(|c| ObjectStorageClass::from(c.as_str())),
             )
-            .build();
-        let region_provider = RegionProviderChain::default_provider();
-        let config = aws_config::from_env()
-            .credentials_provider(provider)
-            .region(region_provider);
+            .build())
+    }
 
-        let config = if let Some(endpoint_url) = endpoint_url {
-            let endpoint = Endpoint::immutable(endpoint_url.parse().expect("valid URI"));
-            config.endpoint_resolver(endpoint)
-        } else {
-            config
-        };
+    async fn get_object(&self, bucket: &str, key: &str) -> Result<Vec<u8>> {
+        let output = self
+            .send(bucket, |client| {
+                client.get_object().bucket(bucket).key(key).send()
+            })
+            .await?;
+        Ok(output.body.collect().await?.into_bytes().to_vec())
+    }
 
-        let client = Client::new(&config.load().await);
+    async fn put_object(&self, bucket: &str, key: &str, body: Vec<u8>) -> Result<()> {
+        self.send(bucket, |client| {
+            client
+                .put_object()
+                .bucket(bucket)
+                .key(key)
+                .body(ByteStream::from(body.clone()))
+                .send()
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn get_object_stream(&self, bucket: &str, key: &str) -> Result<(ByteStream, i64)> {
+        let output = self
+            .send(bucket, |client| {
+                client.get_object().bucket(bucket).key(key).send()
+            })
+            .await?;
+        Ok((output.body, output.content_length))
+    }
 
-        Ok(S3Client { client })
+    // body 는 다시 보낼 수 없으므로 region 을 먼저 찾은 뒤 한 번만 보낸다
+    async fn put_object_stream(
+        &self,
+        bucket: &str,
+        key: &str,
+        body: ByteStream,
+        size: i64,
+    ) -> Result<()> {
+        // HeadBucket 이 거부되어도 기본 region 으로 보내본다
+        if self.clients.known_region(bucket).is_none() {
+            let _ = self
+                .send(bucket, |client| client.head_bucket().bucket(bucket).send())
+                .await;
+        }
+        let (client, region) = self.clients.bucket_client(bucket);
+        client
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .content_length(size)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| self.explain(Some(bucket), e))?;
+        self.clients.remember_bucket(bucket, &region);
+        Ok(())
+    }
+
+    // prefix 처럼 보이도록 크기가 0 인 "<key>/" object 를 만든다
+    async fn create_folder(&self, bucket: &str, prefix: &str) -> Result<()> {
+        self.send(bucket, |client| {
+            client.put_object().bucket(bucket).key(prefix).send()
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_object(&self, bucket: &str, key: &str) -> Result<()> {
+        self.send(bucket, |client| {
+            client.delete_object().bucket(bucket).key(key).send()
+        })
+        .await?;
+        Ok(())
+    }
+
+    // 복사는 대상 bucket 의 region 에서 요청한다
+    async fn copy_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        bucket: &str,
+        key: &str,
+    ) -> Result<()> {
+        let copy_source = format!("{}/{}", source_bucket, path::percent_encode(source_key));
+        self.send(bucket, |client| {
+            client
+                .copy_object()
+                .copy_source(copy_source.clone())
+                .bucket(bucket)
+                .key(key)
+                .send()
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn presign_get_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        expires_in: Duration,
+    ) -> Result<String> {
+        // 요청을 보내지 않으므로 목록을 조회하며 알게 된 bucket 의 region 으로 서명한다
+        let request = self
+            .clients
+            .bucket_client(bucket)
+            .0
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .presigned(PresigningConfig::expires_in(expires_in)?)
+            .await?;
+        // 공개된 object 는 서명 없는 주소로 받을 수 있다
+        if self.config.no_sign_request {
+            let uri = request.uri();
+            return Ok(format!(
+                "{}://{}{}",
+                uri.scheme_str().unwrap_or("https"),
+                uri.authority().map(|a| a.as_str()).unwrap_or_default(),
+                uri.path()
+            ));
+        }
+        Ok(request.uri().to_string())
     }
 }
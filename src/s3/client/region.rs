@@ -0,0 +1,158 @@
+use aws_sdk_s3::{types::SdkError, Client, Region};
+use aws_smithy_types::retry::ProvideErrorKind;
+use aws_types::SdkConfig;
+use std::{collections::HashMap, sync::Mutex};
+
+use super::{anonymous::Unsigned, connector::Connector};
+
+// location constraint 가 비어있다면 us-east-1 이다. EU 는 예전 이름이다
+pub fn location_to_region(location: &str) -> String {
+    match location {
+        "" => "us-east-1".to_owned(),
+        "EU" => "eu-west-1".to_owned(),
+        location => location.to_owned(),
+    }
+}
+
+// 다른 region 의 bucket 에 요청했을 때 응답이 알려주는 bucket 의 region
+pub(super) fn redirect_region<E: ProvideErrorKind>(error: &SdkError<E>) -> Option<String> {
+    let (code, raw) = match error {
+        SdkError::ServiceError { err, raw } => (err.code(), raw),
+        SdkError::ResponseError { raw, .. } => (None, raw),
+        _ => return None,
+    };
+    let response = raw.http();
+    let header = response
+        .headers()
+        .get("x-amz-bucket-region")
+        .and_then(|v| v.to_str().ok());
+    // HEAD 요청의 응답에는 body 가 없어 code 를 알 수 없으므로 상태 코드로 판단한다
+    let redirected = matches!(
+        code,
+        Some("PermanentRedirect") | Some("AuthorizationHeaderMalformed")
+    ) || (code.is_none() && matches!(response.status().as_u16(), 301 | 400));
+    if !redirected {
+        return None;
+    }
+
+    if let Some(region) = header {
+        return Some(region.to_owned());
+    }
+    // AuthorizationHeaderMalformed 는 body 의 <Region> 으로 알려준다
+    let body = std::str::from_utf8(response.body().bytes()?).ok()?;
+    let start = body.find("<Region>")? + "<Region>".len();
+    let end = start + body[start..].find("</Region>")?;
+    Some(body[start..end].to_owned()).filter(|r| !r.is_empty())
+}
+
+// region 마다 만든 client 와 bucket 의 region 을 기억한다
+pub(super) struct RegionalClients {
+    sdk_config: SdkConfig,
+    connector: Connector,
+    // 서명하지 않은 요청을 보낸다
+    anonymous: bool,
+    default_region: String,
+    clients: Mutex<HashMap<String, Client>>,
+    bucket_regions: Mutex<HashMap<String, String>>,
+}
+
+impl RegionalClients {
+    pub(super) fn new(sdk_config: SdkConfig, connector: Connector, anonymous: bool) -> Self {
+        let default_region = sdk_config
+            .region()
+            .map(|r| r.to_string())
+            .unwrap_or_else(|| "us-east-1".to_owned());
+        Self {
+            sdk_config,
+            connector,
+            anonymous,
+            default_region,
+            clients: Mutex::new(HashMap::new()),
+            bucket_regions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(super) fn default_client(&self) -> Client {
+        self.client(&self.default_region.clone())
+    }
+
+    // credential 과 endpoint 는 같고 region 만 다른 client
+    pub(super) fn client(&self, region: &str) -> Client {
+        self.clients
+            .lock()
+            .unwrap()
+            .entry(region.to_owned())
+            .or_insert_with(|| {
+                let config = aws_sdk_s3::config::Builder::from(&self.sdk_config)
+                    .region(Region::new(region.to_owned()))
+                    .build();
+                if self.anonymous {
+                    Client::from_conf_conn(config, Unsigned(self.connector.clone()))
+                } else {
+                    Client::from_conf_conn(config, self.connector.clone())
+                }
+            })
+            .clone()
+    }
+
+    // region 을 모르는 bucket 은 기본 region 으로 요청한다
+    pub(super) fn bucket_region(&self, bucket: &str) -> String {
+        self.bucket_regions
+            .lock()
+            .unwrap()
+            .get(bucket)
+            .cloned()
+            .unwrap_or_else(|| self.default_region.clone())
+    }
+
+    // 이번 실행에서 찾은 bucket 의 region
+    pub(super) fn known_region(&self, bucket: &str) -> Option<String> {
+        self.bucket_regions.lock().unwrap().get(bucket).cloned()
+    }
+
+    pub(super) fn set_bucket_region(&self, bucket: &str, region: String) {
+        self.bucket_regions
+            .lock()
+            .unwrap()
+            .insert(bucket.to_owned(), region);
+    }
+
+    // 요청에 성공한 region 을 기억한다. 이미 알고 있다면 그대로 둔다
+    pub(super) fn remember_bucket(&self, bucket: &str, region: &str) {
+        self.bucket_regions
+            .lock()
+            .unwrap()
+            .entry(bucket.to_owned())
+            .or_insert_with(|| region.to_owned());
+    }
+
+    // region 을 알고 있는 (bucket, region) 목록. 이름 순서로 정렬한다
+    pub(super) fn known_buckets(&self) -> Vec<(String, String)> {
+        let mut buckets: Vec<_> = self
+            .bucket_regions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(bucket, region)| (bucket.clone(), region.clone()))
+            .collect();
+        buckets.sort();
+        buckets
+    }
+
+    pub(super) fn bucket_client(&self, bucket: &str) -> (Client, String) {
+        let region = self.bucket_region(bucket);
+        (self.client(&region), region)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location_to_region() {
+        assert_eq!(location_to_region(""), "us-east-1");
+        assert_eq!(location_to_region("EU"), "eu-west-1");
+        assert_eq!(location_to_region("ap-northeast-2"), "ap-northeast-2");
+    }
+}
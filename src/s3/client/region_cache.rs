@@ -0,0 +1,77 @@
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+    time::Duration,
+};
+
+// 여러 bucket 의 region 을 동시에 조회하므로 바뀐 내용을 모아서 한 번에 쓴다
+const WRITE_DELAY: Duration = Duration::from_secs(1);
+
+static WRITE_PENDING: AtomicBool = AtomicBool::new(false);
+
+// endpoint 마다 GetBucketLocation 으로 찾은 bucket 의 region. 다음 실행에서는 조회하지 않고
+// 바로 보여준다
+type Regions = BTreeMap<String, BTreeMap<String, String>>;
+
+// $XDG_DATA_HOME/tui-s3 혹은 ~/.local/share/tui-s3
+fn path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("tui-s3").join("regions.toml"))
+}
+
+// 읽지 못한 파일은 빈 cache 로 다룬다. 다시 조회하면 되기 때문이다
+fn regions() -> &'static Mutex<Regions> {
+    static REGIONS: OnceLock<Mutex<Regions>> = OnceLock::new();
+    REGIONS.get_or_init(|| {
+        let regions = path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|text| toml::from_str(&text).ok())
+            .unwrap_or_default();
+        Mutex::new(regions)
+    })
+}
+
+pub(super) fn get(endpoint: &str, bucket: &str) -> Option<String> {
+    regions()
+        .lock()
+        .unwrap()
+        .get(endpoint)
+        .and_then(|buckets| buckets.get(bucket))
+        .cloned()
+}
+
+// region 이 바뀌었을 때만 파일에 쓴다. 쓰지 못해도 다음 실행에서 다시 조회할 뿐이다
+pub(super) fn store(endpoint: &str, bucket: &str, region: &str) {
+    {
+        let mut regions = regions().lock().unwrap();
+        let buckets = regions.entry(endpoint.to_owned()).or_default();
+        if buckets.get(bucket).map(|r| r.as_str()) == Some(region) {
+            return;
+        }
+        buckets.insert(bucket.to_owned(), region.to_owned());
+    }
+    if !WRITE_PENDING.swap(true, Ordering::AcqRel) {
+        tokio::spawn(async {
+            tokio::time::sleep(WRITE_DELAY).await;
+            // 여기서부터 바뀐 내용은 다음 쓰기에 포함된다
+            WRITE_PENDING.store(false, Ordering::Release);
+            let _ = tokio::task::spawn_blocking(write).await;
+        });
+    }
+}
+
+// 쓰기가 겹치면 나중에 읽은 내용이 마지막에 쓰이도록 한 번에 하나씩 쓴다
+fn write() {
+    static WRITING: Mutex<()> = Mutex::new(());
+    let _writing = WRITING.lock().unwrap();
+    let text = toml::to_string(&*regions().lock().unwrap());
+    if let (Some(path), Ok(text)) = (path(), text) {
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let _ = std::fs::write(path, text);
+    }
+}
@@ -0,0 +1,107 @@
+use aws_sdk_s3::types::SdkError;
+use aws_smithy_types::retry::ProvideErrorKind;
+use std::{error::Error, fmt};
+
+// 실패한 요청의 응답에서 찾은 정보. status line 에는 message 만, error log 에는 모두 보여준다
+#[derive(Debug)]
+pub struct RequestError {
+    pub message: String,
+    pub code: Option<String>,
+    pub status: Option<u16>,
+    pub request_id: Option<String>,
+    // x-amz-id-2
+    pub extended_request_id: Option<String>,
+    // 응답 body 의 Message
+    pub service_message: Option<String>,
+    // AccessDenied 메시지가 알려주는 거부된 action
+    pub action: Option<String>,
+    source: Box<dyn Error + Send + Sync>,
+}
+
+impl RequestError {
+    // explanation 이 있다면 status line 에 그것을 보여준다
+    pub(super) fn new<E>(error: SdkError<E>, explanation: Option<String>) -> Self
+    where
+        E: ProvideErrorKind + Error + Send + Sync + 'static,
+    {
+        let (code, raw) = match &error {
+            SdkError::ServiceError { err, raw } => (err.code().map(|c| c.to_owned()), Some(raw)),
+            SdkError::ResponseError { raw, .. } => (None, Some(raw)),
+            _ => (None, None),
+        };
+        let http = raw.map(|raw| raw.http());
+        let header = |name: &str| {
+            http.and_then(|http| http.headers().get(name))
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_owned())
+        };
+        let body = http
+            .and_then(|http| http.body().bytes())
+            .map(|body| String::from_utf8_lossy(body).into_owned())
+            .unwrap_or_default();
+        let service_message = xml_element(&body, "Message");
+        let action = service_message.as_deref().and_then(denied_action);
+
+        let message = explanation.unwrap_or_else(|| match (&code, &service_message) {
+            (Some(code), Some(message)) if message.starts_with(code.as_str()) => message.clone(),
+            (Some(code), Some(message)) => format!("{}: {}", code, message),
+            _ => error.to_string(),
+        });
+        let message = match action.as_ref() {
+            Some(action) => format!("{} (missing permission {})", message, action),
+            None => message,
+        };
+        Self {
+            message,
+            code,
+            status: http.map(|http| http.status().as_u16()),
+            request_id: header("x-amz-request-id").or_else(|| xml_element(&body, "RequestId")),
+            extended_request_id: header("x-amz-id-2"),
+            service_message,
+            action,
+            source: Box::new(error),
+        }
+    }
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for RequestError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+fn xml_element(xml: &str, name: &str) -> Option<String> {
+    let start = xml.find(&format!("<{}>", name))? + name.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", name))?;
+    Some(xml[start..end].to_owned()).filter(|v| !v.is_empty())
+}
+
+// "User: arn:... is not authorized to perform: s3:ListBucket on resource: ..."
+fn denied_action(message: &str) -> Option<String> {
+    let (_, rest) = message.split_once("not authorized to perform: ")?;
+    rest.split_whitespace().next().map(|a| a.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_denied_action() {
+        let body = "<Error><Code>AccessDenied</Code><Message>User: arn:aws:iam::123456789012:user/dev is not authorized to perform: s3:ListBucket on resource: \"arn:aws:s3:::logs\" because no identity-based policy allows the s3:ListBucket action</Message><RequestId>4442587FB7D0A2F9</RequestId></Error>";
+        let message = xml_element(body, "Message").unwrap();
+        assert_eq!(denied_action(&message).as_deref(), Some("s3:ListBucket"));
+        assert_eq!(
+            xml_element(body, "RequestId").as_deref(),
+            Some("4442587FB7D0A2F9")
+        );
+        assert_eq!(denied_action("Access Denied"), None);
+        assert_eq!(xml_element(body, "HostId"), None);
+    }
+}
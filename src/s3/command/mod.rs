@@ -0,0 +1,388 @@
+use eyre::Result;
+use std::{str::FromStr, time::Duration};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
+
+use super::path::{self, CompletionTarget};
+
+#[derive(Clone, Copy, Debug, PartialEq, EnumString, EnumIter, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum SortKey {
+    Name,
+    Size,
+    Date,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, EnumString, EnumIter, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Cd(String),
+    // ListBuckets 로 보이지 않는 bucket 도 이름으로 연다
+    Open(String),
+    Sort {
+        key: SortKey,
+        reverse: bool,
+    },
+    // None 이면 filter 를 해제한다
+    Filter(Option<String>),
+    Profile(String),
+    // 설정 파일을 바꾸지 않고 현재 credential 로 role 을 assume 한다
+    AssumeRole {
+        role_arn: String,
+        mfa_serial: Option<String>,
+    },
+    Region(String),
+    Mkdir(String),
+    // None 이면 선택된 object 를 지운다. 실수로 지우지 않도록 :rm! 로만 실행한다
+    Rm(Option<String>),
+    Cp(String),
+    Presign(Duration),
+    Export {
+        format: ExportFormat,
+        path: Option<String>,
+    },
+    // selected 라면 현재 위치 대신 선택된 항목을 저장한다
+    Bookmark {
+        name: String,
+        selected: bool,
+    },
+    // 방문 기록에서 찾을 keyword
+    Jump(Vec<String>),
+    // None 이면 bucket 목록을 연다
+    TabNew(Option<String>),
+    TabClose,
+}
+
+// (이름, 사용법)
+pub const COMMANDS: [(&str, &str); 16] = [
+    ("cd", "cd <s3://bucket/prefix/|/path/|@bookmark>"),
+    ("open", "open <bucket>"),
+    ("sort", "sort name|size|date [desc]"),
+    ("filter", "filter [text]"),
+    ("profile", "profile <name>"),
+    ("assume-role", "assume-role <role-arn> [mfa-serial]"),
+    ("region", "region <region>"),
+    ("mkdir", "mkdir <name>"),
+    ("rm!", "rm! [name]"),
+    ("cp", "cp <s3://bucket/key>"),
+    ("presign", "presign <duration, e.g. 15m, 1h, 7d>"),
+    ("export", "export csv [file]"),
+    ("bookmark", "bookmark <name> [selected]"),
+    ("z", "z <keyword>..."),
+    ("tabnew", "tabnew [s3://bucket/prefix/|@bookmark]"),
+    ("tabclose", "tabclose"),
+];
+
+fn usage(name: &str) -> String {
+    COMMANDS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, usage)| format!("usage: :{}", usage))
+        .unwrap_or_default()
+}
+
+// presigned url 이 유효할 수 있는 최대 기간
+const MAX_PRESIGN_DURATION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+// 1h, 30m, 45s, 7d 형태의 기간. 단위가 없으면 초로 해석한다
+pub fn parse_duration(str: &str) -> Result<Duration> {
+    let (number, unit) = str.split_at(str.find(|c: char| !c.is_ascii_digit()).unwrap_or(str.len()));
+    let number: u64 = number
+        .parse()
+        .map_err(|_| eyre::eyre!("invalid duration: {}", str))?;
+    let unit_seconds = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        _ => return Err(eyre::eyre!("invalid duration unit: {}", unit)),
+    };
+    let seconds = number
+        .checked_mul(unit_seconds)
+        .ok_or_else(|| eyre::eyre!("duration too long: {}", str))?;
+    Ok(Duration::from_secs(seconds))
+}
+
+fn parse_presign_duration(str: &str) -> Result<Duration> {
+    let duration = parse_duration(str)?;
+    if duration > MAX_PRESIGN_DURATION {
+        return Err(eyre::eyre!("duration too long: {} (at most 7d)", str));
+    }
+    Ok(duration)
+}
+
+pub fn parse_command(line: &str) -> Result<Command> {
+    let line = line.trim();
+    let (name, argument) = match line.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, argument.trim()),
+        None => (line, ""),
+    };
+    let arguments: Vec<_> = argument.split_whitespace().collect();
+
+    let required = |argument: &str| {
+        if argument.is_empty() {
+            Err(eyre::eyre!(usage(name)))
+        } else {
+            Ok(argument.to_owned())
+        }
+    };
+
+    let command = match name {
+        "cd" => Command::Cd(required(argument)?),
+        "open" => Command::Open(required(argument)?),
+        "sort" => match arguments[..] {
+            [key] | [key, "asc"] => Command::Sort {
+                key: SortKey::from_str(key).map_err(|_| eyre::eyre!(usage(name)))?,
+                reverse: false,
+            },
+            [key, "desc"] => Command::Sort {
+                key: SortKey::from_str(key).map_err(|_| eyre::eyre!(usage(name)))?,
+                reverse: true,
+            },
+            _ => return Err(eyre::eyre!(usage(name))),
+        },
+        "filter" => Command::Filter(Some(argument.to_owned()).filter(|a| !a.is_empty())),
+        "profile" => Command::Profile(required(argument)?),
+        "assume-role" => match arguments[..] {
+            [role_arn] => Command::AssumeRole {
+                role_arn: role_arn.to_owned(),
+                mfa_serial: None,
+            },
+            [role_arn, mfa_serial] => Command::AssumeRole {
+                role_arn: role_arn.to_owned(),
+                mfa_serial: Some(mfa_serial.to_owned()),
+            },
+            _ => return Err(eyre::eyre!(usage(name))),
+        },
+        "region" => Command::Region(required(argument)?),
+        "mkdir" => Command::Mkdir(required(argument)?.trim_end_matches('/').to_owned()),
+        "rm" => return Err(eyre::eyre!("use :rm! to remove objects")),
+        "rm!" => Command::Rm(Some(argument.to_owned()).filter(|a| !a.is_empty())),
+        "cp" => Command::Cp(required(argument)?),
+        "presign" => Command::Presign(parse_presign_duration(&required(argument)?)?),
+        "export" => match arguments[..] {
+            [format] => Command::Export {
+                format: ExportFormat::from_str(format).map_err(|_| eyre::eyre!(usage(name)))?,
+                path: None,
+            },
+            [format, path] => Command::Export {
+                format: ExportFormat::from_str(format).map_err(|_| eyre::eyre!(usage(name)))?,
+                path: Some(path.to_owned()),
+            },
+            _ => return Err(eyre::eyre!(usage(name))),
+        },
+        "bookmark" => match arguments[..] {
+            [name] => Command::Bookmark {
+                name: name.to_owned(),
+                selected: false,
+            },
+            [name, "selected"] => Command::Bookmark {
+                name: name.to_owned(),
+                selected: true,
+            },
+            _ => return Err(eyre::eyre!(usage(name))),
+        },
+        "z" => Command::Jump(
+            required(argument)?
+                .split_whitespace()
+                .map(|k| k.to_owned())
+                .collect(),
+        ),
+        "tabnew" => Command::TabNew(Some(argument.to_owned()).filter(|a| !a.is_empty())),
+        "tabclose" => Command::TabClose,
+        "" => return Err(eyre::eyre!("empty command")),
+        _ => return Err(eyre::eyre!("unknown command: {}", name)),
+    };
+    Ok(command)
+}
+
+pub enum CommandCompletion {
+    // 바로 구할 수 있는 후보
+    Candidates(Vec<String>),
+    // S3 를 조회해야 구할 수 있는 후보
+    Location(CompletionTarget),
+    Profiles { partial: String },
+    // 고정한 bucket 과 조회한 bucket 의 이름
+    Buckets { partial: String },
+    // @ 다음에 입력중인 bookmark 이름
+    Bookmarks { partial: String },
+}
+
+// 입력중인 command 의 마지막 단어를 완성할 방법을 구한다
+pub fn complete_command(line: &str) -> Option<CommandCompletion> {
+    let (name, argument) = match line.split_once(' ') {
+        Some((name, argument)) => (name, argument.trim_start()),
+        None => {
+            return Some(CommandCompletion::Candidates(
+                COMMANDS
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .filter(|name| name.starts_with(line))
+                    .map(|name| name.to_owned() + " ")
+                    .collect(),
+            ))
+        }
+    };
+
+    let candidates = |values: Vec<String>| {
+        Some(CommandCompletion::Candidates(
+            values
+                .into_iter()
+                .filter(|v| v.starts_with(argument))
+                .collect(),
+        ))
+    };
+
+    match name {
+        "cd" | "tabnew" if argument.starts_with('@') => Some(CommandCompletion::Bookmarks {
+            partial: argument[1..].to_owned(),
+        }),
+        "cd" | "cp" | "tabnew" => {
+            path::completion_target(argument).map(CommandCompletion::Location)
+        }
+        "sort" if !argument.contains(' ') => {
+            candidates(SortKey::iter().map(|k| k.to_string()).collect())
+        }
+        "export" if !argument.contains(' ') => {
+            candidates(ExportFormat::iter().map(|f| f.to_string()).collect())
+        }
+        "region" => candidates(
+            aws_sdk_s3::model::BucketLocationConstraint::values()
+                .iter()
+                .filter(|r| r.contains('-'))
+                .map(|r| r.to_string())
+                .collect(),
+        ),
+        "profile" => Some(CommandCompletion::Profiles {
+            partial: argument.to_owned(),
+        }),
+        "open" => Some(CommandCompletion::Buckets {
+            partial: argument.to_owned(),
+        }),
+        _ => None,
+    }
+}
+
+// 입력중인 command 의 마지막 단어를 candidate 로 바꾼다
+pub fn replace_last_word(line: &str, candidate: &str) -> String {
+    match line.rfind(' ') {
+        Some(i) => format!("{}{}", &line[..i + 1], candidate),
+        None => candidate.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(
+            parse_command("sort size").unwrap(),
+            Command::Sort {
+                key: SortKey::Size,
+                reverse: false
+            }
+        );
+        assert_eq!(
+            parse_command("sort date desc").unwrap(),
+            Command::Sort {
+                key: SortKey::Date,
+                reverse: true
+            }
+        );
+        assert_eq!(parse_command("filter").unwrap(), Command::Filter(None));
+        assert_eq!(
+            parse_command("filter  .log").unwrap(),
+            Command::Filter(Some(".log".to_owned()))
+        );
+        assert_eq!(
+            parse_command("mkdir logs/").unwrap(),
+            Command::Mkdir("logs".to_owned())
+        );
+        assert_eq!(
+            parse_command("presign 1h").unwrap(),
+            Command::Presign(Duration::from_secs(3600))
+        );
+        assert_eq!(
+            parse_command("export csv out.csv").unwrap(),
+            Command::Export {
+                format: ExportFormat::Csv,
+                path: Some("out.csv".to_owned())
+            }
+        );
+
+        assert_eq!(
+            parse_command("bookmark logs selected").unwrap(),
+            Command::Bookmark {
+                name: "logs".to_owned(),
+                selected: true
+            }
+        );
+
+        assert_eq!(
+            parse_command("z logs prod 2024").unwrap(),
+            Command::Jump(vec![
+                "logs".to_owned(),
+                "prod".to_owned(),
+                "2024".to_owned()
+            ])
+        );
+
+        assert_eq!(
+            parse_command("assume-role arn:aws:iam::123:role/admin arn:aws:iam::123:mfa/me")
+                .unwrap(),
+            Command::AssumeRole {
+                role_arn: "arn:aws:iam::123:role/admin".to_owned(),
+                mfa_serial: Some("arn:aws:iam::123:mfa/me".to_owned())
+            }
+        );
+
+        assert_eq!(
+            parse_command("open partner-exports").unwrap(),
+            Command::Open("partner-exports".to_owned())
+        );
+
+        assert!(parse_command("sort color").is_err());
+        assert!(parse_command("open").is_err());
+        assert!(parse_command("assume-role").is_err());
+        assert!(parse_command("presign 1y").is_err());
+        assert!(parse_command("presign 7d").is_ok());
+        // 곱하다 넘치는 기간과 7 일보다 긴 기간은 받지 않는다
+        assert_eq!(
+            parse_command("presign 999999999999999999d")
+                .unwrap_err()
+                .to_string(),
+            "duration too long: 999999999999999999d"
+        );
+        assert!(parse_command("presign 8d").is_err());
+        assert!(parse_command("presign 604801").is_err());
+        assert!(parse_command("region").is_err());
+        assert!(parse_command("rm 01.log").is_err());
+        assert_eq!(
+            parse_command("rm! 01.log").unwrap(),
+            Command::Rm(Some("01.log".to_owned()))
+        );
+        assert!(parse_command("foo").is_err());
+    }
+
+    #[test]
+    fn test_complete_command() {
+        let candidates = |line: &str| match complete_command(line) {
+            Some(CommandCompletion::Candidates(c)) => c,
+            _ => vec![],
+        };
+        assert_eq!(candidates("p"), vec!["profile ", "presign "]);
+        assert_eq!(candidates("sort s"), vec!["size"]);
+        assert_eq!(replace_last_word("sort s", "size"), "sort size");
+        assert!(matches!(
+            complete_command("cd s3://b"),
+            Some(CommandCompletion::Location(_))
+        ));
+    }
+}
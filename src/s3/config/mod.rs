@@ -0,0 +1,123 @@
+use eyre::Result;
+use serde::Deserialize;
+use std::{collections::BTreeMap, path::PathBuf};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub keymap: KeymapConfig,
+    pub theme: ThemeConfig,
+    // 이름으로 --endpoint-url 에 지정할 수 있는 s3 호환 저장소
+    pub endpoints: BTreeMap<String, EndpointConfig>,
+    // 처음 화면에서 고를 수 있는 저장소와 credential
+    pub connections: BTreeMap<String, ConnectionConfig>,
+    // ListBuckets 권한이 없어도 bucket 목록에 보여줄 bucket
+    pub pinned_buckets: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeymapConfig {
+    // <leader> 로 사용할 키
+    pub leader: String,
+    // 여러 키로 이루어진 binding 에서 다음 키를 기다리는 시간
+    pub timeout_ms: u64,
+    // 키 sequence 와 action 이름. 기본 binding 을 덮어쓴다
+    pub bindings: BTreeMap<String, String>,
+}
+
+impl Default for KeymapConfig {
+    fn default() -> Self {
+        Self {
+            leader: "<Space>".to_owned(),
+            timeout_ms: 1000,
+            bindings: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    // 기본으로 사용할 theme 이름
+    pub name: String,
+    // 요소 이름과 덮어쓸 style
+    pub styles: BTreeMap<String, StyleConfig>,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            name: "default".to_owned(),
+            styles: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StyleConfig {
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+    pub modifiers: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct EndpointConfig {
+    pub url: String,
+    // region 을 지정하지 않았을 때 사용한다
+    pub region: Option<String>,
+    // bucket 은 항상 path-style (<url>/<bucket>/<key>) 로 요청한다. false 는 지원하지 않는다
+    pub force_path_style: Option<bool>,
+    // 이 파일의 인증서만 신뢰한다
+    pub ca_bundle: Option<PathBuf>,
+    // tls 인증서를 검증하지 않는다. 없다면 --no-verify-ssl 을 따른다
+    pub no_verify_ssl: Option<bool>,
+    // 없다면 HTTPS_PROXY, HTTP_PROXY, NO_PROXY 를 따른다
+    pub proxy: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConnectionConfig {
+    // [endpoints] 의 이름이나 url. 없다면 AWS 다
+    pub endpoint: Option<String>,
+    pub region: Option<String>,
+    // credential 을 구할 profile. 없다면 기본 순서로 찾는다
+    pub profile: Option<String>,
+    // credential 없이 서명하지 않고 요청한다
+    pub no_sign_request: bool,
+    // path-style 만 지원한다
+    pub force_path_style: Option<bool>,
+    // 목록과 header 에서 이름을 보여줄 색
+    pub color: Option<String>,
+    // 이 연결의 bucket 목록에만 더할 bucket
+    pub pinned_buckets: Vec<String>,
+}
+
+impl Config {
+    // $XDG_CONFIG_HOME/tui-s3 혹은 ~/.config/tui-s3
+    pub fn dir() -> Option<PathBuf> {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+            .map(|config| config.join("tui-s3"))
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        Self::dir().map(|dir| dir.join("config.toml"))
+    }
+
+    // 설정 파일이 없다면 기본 설정을 사용한다
+    pub fn load() -> Result<Config> {
+        match Self::path() {
+            Some(path) if path.exists() => {
+                let text = std::fs::read_to_string(&path)?;
+                toml::from_str(&text).map_err(|e| eyre::eyre!("{}: {}", path.display(), e))
+            }
+            _ => Ok(Config::default()),
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+use tui::style::Color;
+
+use super::{
+    client::{endpoint::Endpoints, S3ClientConfig},
+    config::ConnectionConfig,
+    theme,
+};
+
+// 설정 파일의 [connections.<name>]. 처음 화면에서 고르면 그 연결의 bucket 목록을 보여준다
+#[derive(Clone, Debug, PartialEq)]
+pub struct Connection {
+    pub name: String,
+    pub config: S3ClientConfig,
+    // 목록과 header 에서 이름을 이 색으로 보여준다
+    pub color: Option<Color>,
+    // bucket 목록에 더할 bucket
+    pub pinned_buckets: Vec<String>,
+}
+
+impl Connection {
+    // 설정이 잘못된 연결은 건너뛰고 문제를 돌려준다. 이름 순서다
+    pub fn load(
+        connections: &BTreeMap<String, ConnectionConfig>,
+        endpoints: &Endpoints,
+        no_color: bool,
+    ) -> (Vec<Connection>, Vec<String>) {
+        let mut problems = vec![];
+        let connections = connections
+            .iter()
+            .filter_map(|(name, config)| {
+                if config.force_path_style == Some(false) {
+                    problems.push(format!(
+                        "connection {}: virtual-hosted style isn't supported",
+                        name
+                    ));
+                    return None;
+                }
+                let color = match config.color.as_deref().map(theme::parse_color) {
+                    Some(Ok(color)) if !no_color => Some(color),
+                    Some(Err(e)) => {
+                        problems.push(format!("connection {}: {}", name, e));
+                        None
+                    }
+                    _ => None,
+                };
+                Some(Connection {
+                    name: name.clone(),
+                    config: S3ClientConfig {
+                        profile: config.profile.clone(),
+                        endpoint_url: config.endpoint.clone(),
+                        region: config.region.clone(),
+                        assume_role: None,
+                        no_sign_request: config.no_sign_request,
+                        endpoints: endpoints.clone(),
+                    },
+                    color,
+                    pinned_buckets: config.pinned_buckets.clone(),
+                })
+            })
+            .collect();
+        (connections, problems)
+    }
+
+    // 같은 저장소에 같은 credential 로 연결하는지. region 은 bucket 마다 다르므로 비교하지 않는다
+    pub fn matches(&self, config: &S3ClientConfig) -> bool {
+        self.config.profile == config.profile
+            && self.config.endpoint_url == config.endpoint_url
+            && self.config.no_sign_request == config.no_sign_request
+            && config.assume_role.is_none()
+    }
+
+    // 목록에 보여줄 endpoint 와 credential
+    pub fn endpoint(&self) -> &str {
+        self.config.endpoint_url.as_deref().unwrap_or("aws")
+    }
+
+    pub fn credentials(&self) -> String {
+        if self.config.no_sign_request {
+            "anonymous".to_owned()
+        } else {
+            self.config.profile_name()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_connections() {
+        let config: crate::s3::config::Config = toml::from_str(
+            r##"
+            [connections.r2]
+            endpoint = "https://account.r2.cloudflarestorage.com"
+            region = "auto"
+            profile = "r2"
+            color = "#f38020"
+
+            [connections.aws]
+            color = "purple"
+
+            [connections.minio]
+            endpoint = "minio"
+            force_path_style = false
+            "##,
+        )
+        .unwrap();
+        let (connections, problems) =
+            Connection::load(&config.connections, &Endpoints::default(), false);
+
+        let names: Vec<_> = connections.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["aws", "r2"]);
+        assert_eq!(connections[0].endpoint(), "aws");
+        assert_eq!(connections[0].color, None);
+        assert_eq!(connections[1].color, Some(Color::Rgb(0xf3, 0x80, 0x20)));
+        assert_eq!(connections[1].config.region.as_deref(), Some("auto"));
+        assert_eq!(problems.len(), 2);
+
+        // region 이 달라도 같은 연결이다
+        assert!(connections[1].matches(&S3ClientConfig {
+            region: None,
+            ..connections[1].config.clone()
+        }));
+        assert!(!connections[0].matches(&connections[1].config));
+    }
+}
@@ -0,0 +1,159 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use eyre::Result;
+
+use super::*;
+use crate::s3::bookmark::Bookmark;
+
+impl Controller {
+    pub(super) fn open_bookmark_list(&mut self) {
+        self.bookmark_list = Some(BookmarkListViewModel::new(
+            self.bookmarks.bookmarks().clone(),
+        ));
+        self.input_mode = InputMode::BookmarkList;
+    }
+
+    fn close_bookmark_list(&mut self) {
+        self.bookmark_list = None;
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub(super) async fn handle_event_in_bookmark_list_mode(
+        &mut self,
+        key: KeyEvent,
+    ) -> EventAction {
+        let bookmark_list = match self.bookmark_list.as_mut() {
+            Some(bookmark_list) => bookmark_list,
+            None => return EventAction::NoNeedReDraw,
+        };
+
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _)
+            | (KeyCode::Char('q'), _)
+            | (KeyCode::Char('c'), KeyModifiers::CONTROL) => self.close_bookmark_list(),
+            (KeyCode::Down, _) | (KeyCode::Char('j'), _) => bookmark_list.next(),
+            (KeyCode::Up, _) | (KeyCode::Char('k'), _) => bookmark_list.previous(),
+            (KeyCode::Enter, _) => {
+                let selected = bookmark_list.selected().cloned();
+                self.close_bookmark_list();
+                if let Some(bookmark) = selected {
+                    if let Err(e) = self.open_bookmark(bookmark).await {
+                        self.report_error(e);
+                    }
+                }
+            }
+            (KeyCode::Char('d'), _) => {
+                if let Some(name) = bookmark_list.selected().map(|b| b.name.clone()) {
+                    self.bookmarks.remove(&name);
+                    bookmark_list.update(self.bookmarks.bookmarks().clone());
+                    self.set_status(match self.bookmarks.save() {
+                        Ok(_) => StatusMessage::Info(format!("removed bookmark {}", name)),
+                        Err(e) => {
+                            StatusMessage::Error(ErrorLogEntry::new(format!("bookmarks: {}", e)))
+                        }
+                    });
+                }
+            }
+            _ => return EventAction::NoNeedReDraw,
+        }
+        EventAction::NeedReDraw
+    }
+
+    // 현재 위치 혹은 선택된 항목을 현재 profile, endpoint 와 함께 저장한다
+    pub(super) async fn add_bookmark(&mut self, name: String, selected: bool) -> Result<()> {
+        let (bucket, prefix) = self.current_prefix()?;
+        let key = if selected {
+            match self.tab.vm.selected() {
+                Some(S3Item::CommonPrefix(p)) => p.prefix().unwrap_or_default().to_owned(),
+                Some(S3Item::Object(o)) => o.key().unwrap_or_default().to_owned(),
+                _ => return Err(eyre::eyre!("select a prefix or an object first")),
+            }
+        } else {
+            prefix
+        };
+
+        let config = self.s3_connection()?;
+        let bookmark = Bookmark {
+            name,
+            bucket,
+            key,
+            profile: config.profile,
+            endpoint_url: config.endpoint_url,
+            no_sign_request: config.no_sign_request,
+        };
+        let message = format!("bookmarked {} as {}", bookmark.s3_uri(), bookmark.name);
+        self.bookmarks.insert(bookmark);
+        self.bookmarks.save()?;
+        self.status = Some(StatusMessage::Info(message));
+        Ok(())
+    }
+
+    pub(super) fn find_bookmark(&self, name: &str) -> Result<Bookmark> {
+        self.bookmarks
+            .get(name)
+            .cloned()
+            .ok_or_else(|| eyre::eyre!("unknown bookmark: {}", name))
+    }
+
+    // bookmark 의 profile, endpoint 로 바꾼 뒤 bookmark 위치로 이동한다
+    pub(super) async fn open_bookmark(&mut self, bookmark: Bookmark) -> Result<()> {
+        self.use_connection(
+            bookmark.profile.clone(),
+            bookmark.endpoint_url.clone(),
+            bookmark.no_sign_request,
+        )
+        .await?;
+
+        if bookmark.is_prefix() {
+            self.go_to_location(bookmark.bucket, bookmark.key).await;
+        } else {
+            self.jump_to_key(bookmark.bucket, bookmark.key).await;
+        }
+        Ok(())
+    }
+}
+
+impl Opt {
+    // 경로마다 tab 의 연결 설정과 위치를 구한다. @name 은 bookmark 로 바꾸고, 명시한 profile,
+    // endpoint 가 bookmark 보다 우선한다
+    pub(super) fn resolve_locations(
+        &self,
+        bookmarks: &Bookmarks,
+        endpoints: &Endpoints,
+    ) -> Result<Vec<(S3ClientConfig, Option<String>)>> {
+        let config = S3ClientConfig {
+            profile: self.profile.clone(),
+            endpoint_url: self.endpoint_url.clone(),
+            region: None,
+            assume_role: None,
+            no_sign_request: self.no_sign_request,
+            endpoints: endpoints.clone(),
+        };
+        if self.s3_paths.is_empty() {
+            return Ok(vec![(config, None)]);
+        }
+
+        self.s3_paths
+            .iter()
+            .map(|s3_path| match s3_path.strip_prefix('@') {
+                Some(name) => {
+                    let bookmark = bookmarks
+                        .get(name)
+                        .ok_or_else(|| eyre::eyre!("unknown bookmark: {}", name))?;
+                    let config = S3ClientConfig {
+                        profile: self.profile.clone().or_else(|| bookmark.profile.clone()),
+                        endpoint_url: self
+                            .endpoint_url
+                            .clone()
+                            .or_else(|| bookmark.endpoint_url.clone()),
+                        region: None,
+                        assume_role: None,
+                        no_sign_request: self.no_sign_request || bookmark.no_sign_request,
+                        endpoints: endpoints.clone(),
+                    };
+                    Ok((config, Some(bookmark.s3_uri())))
+                }
+                None => Ok((config.clone(), Some(s3_path.clone()))),
+            })
+            .collect()
+    }
+}
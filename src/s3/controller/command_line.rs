@@ -0,0 +1,493 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use eyre::Result;
+use std::{io::Write, sync::Arc};
+use tokio::sync::mpsc::Sender;
+
+use super::*;
+use crate::s3::{
+    client::assume_role::AssumeRole,
+    command::{self, Command, CommandCompletion, ExportFormat},
+    path::{self, CompletionTarget},
+    profile,
+};
+
+// 저장소 작업의 결과를 status 로 알리고, 성공했다면 목록을 새로 고친다
+async fn send_result(
+    ev_tx: Sender<ClientOutput>,
+    result: Result<String>,
+    refresh: Option<(usize, String, String)>,
+) {
+    let status = match result {
+        Ok(message) => StatusMessage::Info(message),
+        Err(e) => StatusMessage::error(&e),
+    };
+    let succeeded = matches!(status, StatusMessage::Info(_));
+    let _ = ev_tx.send(ClientOutput::Status(status)).await;
+
+    if let (true, Some((tab_id, bucket, prefix))) = (succeeded, refresh) {
+        let _ = ev_tx
+            .send(ClientOutput::Refresh {
+                tab_id,
+                bucket,
+                prefix,
+            })
+            .await;
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+impl Controller {
+    pub(super) fn open_command_line(&mut self) {
+        self.input_line = ":".to_owned();
+        self.history_index = None;
+        self.input_mode = InputMode::Command;
+    }
+
+    pub(super) async fn handle_event_in_command_mode(&mut self, key: KeyEvent) -> EventAction {
+        match key.code {
+            KeyCode::Backspace => {
+                self.clear_completions();
+                if self.input_line.len() > 1 {
+                    self.input_line.pop();
+                } else {
+                    self.input_mode = InputMode::Normal;
+                    self.input_line.clear();
+                }
+            }
+            KeyCode::Char(c) => {
+                self.clear_completions();
+                self.input_line.push(c);
+            }
+            KeyCode::Tab => self.complete_command_line().await,
+            KeyCode::Up => self.previous_history(),
+            KeyCode::Down => self.next_history(),
+            KeyCode::Enter => {
+                self.clear_completions();
+                self.input_mode = InputMode::Normal;
+                let line = std::mem::take(&mut self.input_line);
+                self.execute_command_line(&line[1..]).await;
+            }
+            KeyCode::Esc => {
+                self.clear_completions();
+                self.input_line.clear();
+                self.input_mode = InputMode::Normal;
+            }
+            _ => return EventAction::NoNeedReDraw,
+        }
+        EventAction::NeedReDraw
+    }
+
+    fn previous_history(&mut self) {
+        if self.command_history.is_empty() {
+            return;
+        }
+        let i = match self.history_index {
+            Some(i) => i.saturating_sub(1),
+            None => self.command_history.len() - 1,
+        };
+        self.history_index = Some(i);
+        self.input_line = format!(":{}", self.command_history[i]);
+    }
+
+    fn next_history(&mut self) {
+        match self.history_index {
+            Some(i) if i + 1 < self.command_history.len() => {
+                self.history_index = Some(i + 1);
+                self.input_line = format!(":{}", self.command_history[i + 1]);
+            }
+            Some(_) => {
+                self.history_index = None;
+                self.input_line = ":".to_owned();
+            }
+            None => {}
+        }
+    }
+
+    pub(super) fn clear_completions(&mut self) {
+        self.completions.clear();
+        self.completion_index = None;
+    }
+
+    pub(super) fn apply_completions(&mut self, candidates: Vec<String>) {
+        let line = &self.input_line[1..];
+        match candidates.len() {
+            0 => {}
+            1 => self.input_line = format!(":{}", command::replace_last_word(line, &candidates[0])),
+            _ => {
+                let common = path::longest_common_prefix(&candidates);
+                self.input_line = format!(":{}", command::replace_last_word(line, &common));
+                self.completions = candidates;
+            }
+        }
+    }
+
+    async fn complete_command_line(&mut self) {
+        // 이미 후보가 있다면 후보를 차례대로 선택한다
+        if !self.completions.is_empty() {
+            let i = self
+                .completion_index
+                .map(|i| (i + 1) % self.completions.len())
+                .unwrap_or(0);
+            self.completion_index = Some(i);
+            self.input_line = format!(
+                ":{}",
+                command::replace_last_word(&self.input_line[1..], &self.completions[i])
+            );
+            return;
+        }
+
+        let completion = match command::complete_command(&self.input_line[1..]) {
+            Some(CommandCompletion::Candidates(candidates)) => {
+                self.apply_completions(candidates);
+                return;
+            }
+            Some(CommandCompletion::Bookmarks { partial }) => {
+                let candidates = self
+                    .bookmarks
+                    .bookmarks()
+                    .iter()
+                    .filter(|b| b.name.starts_with(&partial))
+                    .map(|b| format!("@{}", b.name))
+                    .collect();
+                self.apply_completions(candidates);
+                return;
+            }
+            // s3 위치는 s3 tab 에서만 완성한다
+            Some(CommandCompletion::Location(_)) if self.tab.backend.scheme() != Scheme::S3 => {
+                return
+            }
+            Some(completion) => completion,
+            None => return,
+        };
+
+        let input_line = self.input_line.clone();
+        let backend_copy = self.tab.backend.clone();
+        let pinned_buckets = self.tab.pinned_buckets.clone();
+        let ev_tx_copy = self.ev_tx.clone();
+
+        tokio::spawn(async move {
+            let candidates = match completion {
+                CommandCompletion::Candidates(candidates) => candidates,
+                CommandCompletion::Bookmarks { .. } => vec![],
+                CommandCompletion::Location(CompletionTarget::Buckets { partial }) => backend_copy
+                    .list_bucket_names()
+                    .await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|name| name.starts_with(&partial))
+                    .map(|name| format!("s3://{}/", name))
+                    .collect(),
+                CommandCompletion::Location(CompletionTarget::Prefixes {
+                    bucket,
+                    prefix,
+                    partial,
+                }) => backend_copy
+                    .list_common_prefixes(&bucket, &prefix)
+                    .await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|p| p.starts_with(&partial))
+                    .map(|p| format!("s3://{}/{}", bucket, p))
+                    .collect(),
+                // ListBuckets 가 거부되었다면 고정한 bucket 만 완성한다
+                CommandCompletion::Buckets { partial } => {
+                    let mut names = pinned_buckets;
+                    for name in backend_copy.list_bucket_names().await.unwrap_or_default() {
+                        if !names.contains(&name) {
+                            names.push(name);
+                        }
+                    }
+                    names
+                        .into_iter()
+                        .filter(|name| name.starts_with(&partial))
+                        .collect()
+                }
+                CommandCompletion::Profiles { partial } => profile::load()
+                    .await
+                    .into_iter()
+                    .map(|p| p.name)
+                    .filter(|name| name.starts_with(&partial))
+                    .collect(),
+            };
+            ev_tx_copy
+                .send(ClientOutput::Completions {
+                    input_line,
+                    candidates,
+                })
+                .await
+                .expect("ev_tx_copy send error");
+        });
+    }
+
+    pub(super) async fn execute_command_line(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        if self.command_history.last().map(|l| l.as_str()) != Some(line) {
+            self.command_history.push(line.to_owned());
+        }
+
+        let result = match command::parse_command(line) {
+            Ok(command) => self.execute_command(command).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            self.report_error(e);
+        }
+    }
+
+    // 선택된 object 의 (bucket, key)
+    fn selected_object(&self) -> Result<(String, String)> {
+        match (self.tab.vm.bucket_and_prefix(), self.tab.vm.selected()) {
+            (Some((bucket, _)), Some(S3Item::Object(o))) => {
+                Ok((bucket, o.key().unwrap_or_default().to_owned()))
+            }
+            _ => Err(eyre::eyre!("select an object first")),
+        }
+    }
+
+    pub(super) fn current_prefix(&self) -> Result<(String, String)> {
+        self.tab
+            .vm
+            .bucket_and_prefix()
+            .ok_or_else(|| eyre::eyre!("select a bucket first"))
+    }
+
+    async fn execute_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Cd(location) => self.go_to(&location).await?,
+            Command::Open(bucket) => self.open_bucket(&bucket).await?,
+            Command::Sort { key, reverse } => self.tab.vm.set_sort_order(Some((key, reverse))),
+            Command::Filter(filter) => self.tab.vm.set_filter(filter),
+            Command::Profile(profile) => self.switch_profile(profile).await?,
+            Command::AssumeRole {
+                role_arn,
+                mfa_serial,
+            } => {
+                let config = S3ClientConfig {
+                    assume_role: Some(AssumeRole {
+                        role_arn,
+                        mfa_serial,
+                    }),
+                    ..self.connection_or_default()
+                };
+                self.replace_client(config).await?;
+                self.go_to_bucket_list().await;
+            }
+            Command::Region(region) => {
+                let config = S3ClientConfig {
+                    region: Some(region),
+                    ..self.s3_connection()?
+                };
+                self.replace_client(config).await?;
+                self.refresh().await;
+            }
+            Command::Mkdir(name) => {
+                let (bucket, prefix) = self.current_prefix()?;
+                let folder = format!("{}{}/", prefix, name);
+                let (tab_id, scheme) = (self.tab.id, self.tab.vm.scheme());
+                let (backend_copy, ev_tx_copy) = (self.tab.backend.clone(), self.ev_tx.clone());
+                tokio::spawn(async move {
+                    let result = backend_copy
+                        .create_folder(&bucket, &folder)
+                        .await
+                        .map(|_| format!("created {}", scheme.uri(&bucket, &folder)));
+                    let refresh = Some((tab_id, bucket, prefix));
+                    send_result(ev_tx_copy, result, refresh).await;
+                });
+            }
+            Command::Rm(name) => {
+                let (bucket, prefix) = self.current_prefix()?;
+                let key = match name {
+                    Some(name) => format!("{}{}", prefix, name),
+                    None => self.selected_object()?.1,
+                };
+                let (tab_id, scheme) = (self.tab.id, self.tab.vm.scheme());
+                let (backend_copy, ev_tx_copy) = (self.tab.backend.clone(), self.ev_tx.clone());
+                tokio::spawn(async move {
+                    let result = backend_copy
+                        .delete_object(&bucket, &key)
+                        .await
+                        .map(|_| format!("removed {}", scheme.uri(&bucket, &key)));
+                    let refresh = Some((tab_id, bucket, prefix));
+                    send_result(ev_tx_copy, result, refresh).await;
+                });
+            }
+            Command::Cp(destination) => {
+                let (source_bucket, source_key) = self.selected_object()?;
+                let (bucket, mut key) = path::parse_s3_location(&destination)?;
+                if key.is_empty() || key.ends_with('/') {
+                    key += source_key.rsplit('/').next().unwrap_or_default();
+                }
+                let (tab_id, scheme) = (self.tab.id, self.tab.vm.scheme());
+                let refresh = self.tab.vm.bucket_and_prefix().map(|(b, p)| (tab_id, b, p));
+                let (backend_copy, ev_tx_copy) = (self.tab.backend.clone(), self.ev_tx.clone());
+                tokio::spawn(async move {
+                    let result = backend_copy
+                        .copy_object(&source_bucket, &source_key, &bucket, &key)
+                        .await
+                        .map(|_| format!("copied to {}", scheme.uri(&bucket, &key)));
+                    send_result(ev_tx_copy, result, refresh).await;
+                });
+            }
+            Command::Presign(expires_in) => {
+                let (bucket, key) = self.selected_object()?;
+                let url = self
+                    .tab
+                    .backend
+                    .presign_get_object(&bucket, &key, expires_in)
+                    .await?;
+                self.copy_to_clipboard(url.clone()).await?;
+                self.status = Some(StatusMessage::Info(format!("copied {}", url)));
+            }
+            Command::Bookmark { name, selected } => self.add_bookmark(name, selected).await?,
+            Command::Jump(keywords) => self.jump(keywords).await?,
+            Command::TabNew(location) => self.new_tab(location).await?,
+            Command::TabClose => self.close_tab()?,
+            Command::Export { format, path } => {
+                let path = path.unwrap_or_else(|| self.default_export_path(format));
+                let rows = self.export(format, &path)?;
+                self.status = Some(StatusMessage::Info(format!(
+                    "exported {} rows to {}",
+                    rows, path
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // profile, endpoint 나 서명 여부가 다르다면 client 를 바꾼다
+    pub(super) async fn use_connection(
+        &mut self,
+        profile: Option<String>,
+        endpoint_url: Option<String>,
+        no_sign_request: bool,
+    ) -> Result<()> {
+        let same = self.tab.backend.connection().is_some_and(|c| {
+            c.profile == profile
+                && c.endpoint_url == endpoint_url
+                && c.no_sign_request == no_sign_request
+        });
+        if !same {
+            self.replace_client(S3ClientConfig {
+                profile,
+                endpoint_url,
+                region: None,
+                assume_role: None,
+                no_sign_request,
+                endpoints: self.default_connection.endpoints.clone(),
+            })
+            .await?;
+        }
+        Ok(())
+    }
+
+    // 현재 tab 의 s3 연결 설정
+    pub(super) fn s3_connection(&self) -> Result<S3ClientConfig> {
+        self.tab
+            .backend
+            .connection()
+            .cloned()
+            .ok_or_else(|| eyre::eyre!("not an s3 location"))
+    }
+
+    // 다른 tab 과 같은 client 를 사용하고 있을 수 있으므로 현재 tab 의 client 만 바꾼다
+    pub(super) async fn replace_client(&mut self, config: S3ClientConfig) -> Result<()> {
+        let client = S3Client::new(config).await?;
+        self.tab.set_backend(Arc::new(client));
+        self.label_connection();
+        Ok(())
+    }
+
+    // s3 위치, 로컬 경로 혹은 @bookmark 로 이동한다
+    pub(super) async fn go_to(&mut self, location: &str) -> Result<()> {
+        if let Some(name) = location.strip_prefix('@') {
+            return self.open_bookmark(self.find_bookmark(name)?).await;
+        }
+        if let Some((bucket, key)) = local::parse_location(location) {
+            if self.tab.backend.scheme() != Scheme::File {
+                self.tab.set_backend(Arc::new(LocalBackend));
+                self.label_connection();
+            }
+            if key.is_empty() || key.ends_with('/') {
+                self.go_to_location(bucket, key).await;
+            } else {
+                self.jump_to_key(bucket, key).await;
+            }
+            return Ok(());
+        }
+
+        let (bucket, prefix) = parse_s3_path(location)?;
+        if self.tab.backend.scheme() != Scheme::S3 {
+            self.replace_client(self.default_connection.clone()).await?;
+        }
+        self.go_to_location(bucket, prefix).await;
+        Ok(())
+    }
+
+    fn default_export_path(&self, format: ExportFormat) -> String {
+        let name = match self.tab.vm.bucket_and_prefix() {
+            Some((bucket, prefix)) => format!("{}/{}", bucket, prefix)
+                .trim_end_matches('/')
+                .replace('/', "_"),
+            None => "buckets".to_owned(),
+        };
+        format!("{}.{}", name, format)
+    }
+
+    // 현재 목록을 파일로 내보내고 내보낸 행의 수를 돌려준다
+    fn export(&self, format: ExportFormat, path: &str) -> Result<usize> {
+        let ExportFormat::Csv = format;
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        writeln!(file, "type,key,size,last_modified,storage_class")?;
+
+        let prefix = self
+            .tab
+            .vm
+            .bucket_and_prefix()
+            .map(|b| b.1)
+            .unwrap_or_default();
+        let mut rows = 0;
+        for item in self.tab.vm.items() {
+            let (item_type, key, storage_class) = match &item {
+                S3Item::Pop => continue,
+                S3Item::Connection(_) => ("connection", item.name(), String::default()),
+                S3Item::Bucket(_) => ("bucket", item.name(), String::default()),
+                S3Item::CommonPrefix(_) => {
+                    ("prefix", prefix.clone() + &item.name(), String::default())
+                }
+                S3Item::Object(o) => (
+                    "object",
+                    o.key().unwrap_or_default().to_owned(),
+                    o.storage_class()
+                        .map(|s| s.as_str().to_owned())
+                        .unwrap_or_default(),
+                ),
+            };
+            let last_modified = item
+                .last_modified()
+                .and_then(|d| d.fmt(aws_smithy_types::date_time::Format::DateTime).ok())
+                .unwrap_or_default();
+            writeln!(
+                file,
+                "{},{},{},{},{}",
+                item_type,
+                csv_field(&key),
+                item.size(),
+                last_modified,
+                storage_class
+            )?;
+            rows += 1;
+        }
+        file.flush()?;
+        Ok(rows)
+    }
+}
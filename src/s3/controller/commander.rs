@@ -0,0 +1,256 @@
+use eyre::Result;
+use std::sync::Arc;
+use tokio::sync::mpsc::Sender;
+
+use super::*;
+
+// 두 저장소가 같은 곳을 가리키는지. 로컬 디스크는 하나뿐이다
+fn is_same_storage(a: &Arc<dyn StorageBackend>, b: &Arc<dyn StorageBackend>) -> bool {
+    Arc::ptr_eq(a, b)
+        || (a.scheme() == Scheme::File && b.scheme() == Scheme::File)
+        || (a.connection().is_some() && a.connection() == b.connection())
+}
+
+// 한 pane 에서 선택한 항목을 다른 pane 의 위치로 복사 혹은 이동한다
+struct Transfer {
+    source: Arc<dyn StorageBackend>,
+    source_bucket: String,
+    // 선택한 항목의 상위 prefix. 대상 key 는 이 prefix 를 뺀 나머지를 붙여 만든다
+    source_prefix: String,
+    // 선택한 object 의 key 혹은 prefix
+    selected: String,
+    destination: Arc<dyn StorageBackend>,
+    bucket: String,
+    prefix: String,
+    remove_source: bool,
+}
+
+impl Transfer {
+    // prefix 를 선택했다면 그 아래의 모든 key 를 구한다
+    async fn source_keys(&self) -> Result<Vec<String>> {
+        if !self.selected.ends_with('/') {
+            return Ok(vec![self.selected.clone()]);
+        }
+        let mut keys = vec![];
+        let mut continuation_token = None;
+        loop {
+            let (page, next_continuation_token) = self
+                .source
+                .list_keys_page(&self.source_bucket, &self.selected, continuation_token)
+                .await?;
+            keys.extend(page);
+            if next_continuation_token.is_none() {
+                break;
+            }
+            continuation_token = next_continuation_token;
+        }
+        Ok(keys)
+    }
+
+    // 진행 상황을 status 로 알리고 옮긴 object 의 수를 돌려준다
+    async fn run(&self, ev_tx: &Sender<ClientOutput>) -> Result<usize> {
+        let verb = if self.remove_source {
+            "moving"
+        } else {
+            "copying"
+        };
+        let keys = self.source_keys().await?;
+        let same_storage = is_same_storage(&self.source, &self.destination);
+
+        for (i, source_key) in keys.iter().enumerate() {
+            let key = format!("{}{}", self.prefix, &source_key[self.source_prefix.len()..]);
+            let _ = ev_tx
+                .send(ClientOutput::Status(StatusMessage::Info(format!(
+                    "{} {}/{} {}",
+                    verb,
+                    i + 1,
+                    keys.len(),
+                    source_key
+                ))))
+                .await;
+
+            if same_storage {
+                self.source
+                    .copy_object(&self.source_bucket, source_key, &self.bucket, &key)
+                    .await?;
+            } else if key.ends_with('/') {
+                self.destination.create_folder(&self.bucket, &key).await?;
+            } else {
+                let (body, size) = self
+                    .source
+                    .get_object_stream(&self.source_bucket, source_key)
+                    .await?;
+                self.destination
+                    .put_object_stream(&self.bucket, &key, body, size)
+                    .await?;
+            }
+        }
+
+        if self.remove_source {
+            // 디렉토리는 안의 항목을 지운 뒤에 지운다
+            for source_key in keys.iter().rev() {
+                self.source
+                    .delete_object(&self.source_bucket, source_key)
+                    .await?;
+            }
+            // 선택한 디렉토리가 목록에 없다면 따로 지운다
+            if self.selected.ends_with('/') && !keys.contains(&self.selected) {
+                self.source
+                    .delete_object(&self.source_bucket, &self.selected)
+                    .await?;
+            }
+        }
+        Ok(keys.len())
+    }
+}
+
+impl Controller {
+    // 현재 tab 과 다음 tab 을 나란히 보여준다. tab 이 하나뿐이라면 현재 디렉토리를 보여주는
+    // 로컬 tab 을 만든다
+    pub(super) async fn toggle_commander(&mut self) -> Result<()> {
+        if self.commander.take().is_some() {
+            return Ok(());
+        }
+
+        if self.other_tabs.is_empty() {
+            let (bucket, prefix) = local::current_dir_location()?;
+            let mut tab = Tab::new(self.next_tab_id(), Arc::new(LocalBackend));
+            tab.vm.set_sort_order(self.tab.vm.sort_order());
+            let index = self.tab_index;
+            self.insert_tab(tab);
+            self.go_to_location(bucket, prefix).await;
+            self.switch_tab(index);
+        }
+
+        let next = (self.tab_index + 1) % (self.other_tabs.len() + 1);
+        let i = if next < self.tab_index {
+            next
+        } else {
+            next - 1
+        };
+        self.commander = self.other_tabs.get(i).map(|t| t.id);
+        Ok(())
+    }
+
+    pub(super) fn switch_pane(&mut self) {
+        if let Some(index) = self.commander.and_then(|id| self.tab_position(id)) {
+            self.switch_tab(index);
+        }
+    }
+
+    pub(super) fn transfer_to_other_pane(&mut self, remove_source: bool) -> Result<()> {
+        let other = self
+            .commander
+            .and_then(|id| self.other_tabs.iter().find(|t| t.id == id))
+            .ok_or_else(|| eyre::eyre!("open the commander first"))?;
+        let (bucket, prefix) = other
+            .vm
+            .bucket_and_prefix()
+            .ok_or_else(|| eyre::eyre!("open a directory in the other pane first"))?;
+        let (source_bucket, source_prefix) = self.current_prefix()?;
+        let selected = match self.tab.vm.selected() {
+            Some(S3Item::CommonPrefix(p)) => p.prefix().unwrap_or_default().to_owned(),
+            Some(S3Item::Object(o)) => o.key().unwrap_or_default().to_owned(),
+            _ => return Err(eyre::eyre!("select a prefix or an object first")),
+        };
+
+        if is_same_storage(&self.tab.backend, &other.backend) && source_bucket == bucket {
+            if prefix == source_prefix {
+                return Err(eyre::eyre!("the other pane shows the same location"));
+            }
+            if selected.ends_with('/') && prefix.starts_with(&selected) {
+                return Err(eyre::eyre!("can't copy {} into itself", selected));
+            }
+        }
+
+        let destination_uri = other.vm.scheme().uri(&bucket, &prefix);
+        let other_id = other.id;
+        let transfer = Transfer {
+            source: self.tab.backend.clone(),
+            source_bucket,
+            source_prefix,
+            selected,
+            destination: other.backend.clone(),
+            bucket,
+            prefix,
+            remove_source,
+        };
+        let (tab_id, ev_tx_copy) = (self.tab.id, self.ev_tx.clone());
+
+        // 옮기는 동안에도 계속 탐색할 수 있도록 background 에서 옮긴다
+        tokio::spawn(async move {
+            let status = match transfer.run(&ev_tx_copy).await {
+                Ok(count) => StatusMessage::Info(format!(
+                    "{} {} {} to {}",
+                    if remove_source { "moved" } else { "copied" },
+                    count,
+                    if count == 1 { "object" } else { "objects" },
+                    destination_uri
+                )),
+                Err(e) => StatusMessage::error(&e),
+            };
+            let _ = ev_tx_copy.send(ClientOutput::Status(status)).await;
+
+            // 일부만 옮겨졌을 수 있으므로 실패해도 목록을 새로 고친다
+            let _ = ev_tx_copy
+                .send(ClientOutput::Refresh {
+                    tab_id: other_id,
+                    bucket: transfer.bucket.clone(),
+                    prefix: transfer.prefix.clone(),
+                })
+                .await;
+            if remove_source {
+                let _ = ev_tx_copy
+                    .send(ClientOutput::Refresh {
+                        tab_id,
+                        bucket: transfer.source_bucket.clone(),
+                        prefix: transfer.source_prefix.clone(),
+                    })
+                    .await;
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc::channel;
+
+    #[tokio::test]
+    async fn test_move_directory() {
+        let dir = std::env::temp_dir().join(format!("tui-s3-commander-{}", std::process::id()));
+        let (bucket, prefix) = local::parse_location(&format!("{}/", dir.display())).unwrap();
+        let backend: Arc<dyn StorageBackend> = Arc::new(LocalBackend);
+        for key in ["src/logs/a.log", "src/logs/2024/b.log"] {
+            backend
+                .put_object(&bucket, &format!("{}{}", prefix, key), b"log".to_vec())
+                .await
+                .unwrap();
+        }
+
+        let transfer = Transfer {
+            source: backend.clone(),
+            source_bucket: bucket.clone(),
+            source_prefix: format!("{}src/", prefix),
+            selected: format!("{}src/logs/", prefix),
+            destination: backend.clone(),
+            bucket: bucket.clone(),
+            prefix: format!("{}dst/", prefix),
+            remove_source: true,
+        };
+        backend
+            .create_folder(&bucket, &transfer.prefix)
+            .await
+            .unwrap();
+        let (ev_tx, _ev_rx) = channel(100);
+        assert_eq!(transfer.run(&ev_tx).await.unwrap(), 3);
+
+        // 안의 항목뿐 아니라 디렉토리도 옮겨진다
+        assert!(!dir.join("src/logs").exists());
+        assert!(dir.join("src").exists());
+        assert!(dir.join("dst/logs/2024/b.log").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,60 @@
+use eyre::Result;
+
+use super::*;
+
+// tab 의 저장소가 설정 파일의 연결이라면 header 에 그 이름을 보여주고 그 연결에 고정한 bucket 을
+// bucket 목록에 더한다
+pub(super) fn label_connection(tab: &mut Tab, connections: &[Connection], pinned: &[String]) {
+    let connection = tab
+        .backend
+        .connection()
+        .and_then(|config| connections.iter().find(|c| c.matches(config)))
+        .cloned();
+    let s3 = tab.backend.scheme() == Scheme::S3;
+    tab.vm.set_has_connections(!connections.is_empty() && s3);
+    tab.pinned_buckets = if s3 {
+        pinned::pinned_buckets(pinned, connection.as_ref())
+    } else {
+        vec![]
+    };
+    tab.vm.set_connection(connection);
+}
+
+impl Tab {
+    // 연결 목록에서 시작하는 tab. 연결을 고르기 전까지는 config 로 만든 client 를 사용한다
+    pub(super) async fn open_connections(
+        id: usize,
+        config: S3ClientConfig,
+        connections: Vec<Connection>,
+    ) -> Result<Self> {
+        let mut tab = Tab::new(id, Arc::new(S3Client::new(config).await?));
+        tab.vm.push(S3Output::Connections(connections));
+        Ok(tab)
+    }
+}
+
+impl Controller {
+    pub(super) fn label_connection(&mut self) {
+        label_connection(&mut self.tab, &self.connections, &self.pinned_buckets);
+    }
+
+    // bucket 목록의 .. 은 연결 목록이다
+    pub(super) fn show_connections(&mut self) {
+        self.tab.cancel_request();
+        if let Some(before) = self.tab.vm.history_entry() {
+            self.tab.history.record(before);
+        }
+        self.tab.vm.list_stack.clear();
+        self.tab
+            .vm
+            .push(S3Output::Connections(self.connections.clone()));
+        self.tab.vm.select_connection();
+    }
+
+    // 현재 tab 의 client 를 연결의 설정으로 바꾸고 그 bucket 목록을 보여준다
+    pub(super) async fn open_connection(&mut self, connection: Connection) -> Result<()> {
+        self.replace_client(connection.config).await?;
+        self.request_bucket_list().await;
+        Ok(())
+    }
+}
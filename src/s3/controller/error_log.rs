@@ -0,0 +1,58 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use super::*;
+
+// error log 에 남길 최대 error 수
+const MAX_ERROR_LOG: usize = 100;
+
+impl Controller {
+    // error 는 다음 키 입력에 status line 에서 사라지므로 error log 에 남긴다
+    pub(super) fn set_status(&mut self, status: StatusMessage) {
+        if let StatusMessage::Error(entry) = &status {
+            if self.error_log.len() == MAX_ERROR_LOG {
+                self.error_log.remove(0);
+            }
+            self.error_log.push(entry.clone());
+        }
+        self.status = Some(status);
+    }
+
+    pub(super) fn open_error_log(&mut self) {
+        self.error_log_view = Some(ErrorLogViewModel::new(&self.error_log));
+        self.input_mode = InputMode::ErrorLog;
+    }
+
+    fn close_error_log(&mut self) {
+        self.error_log_view = None;
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub(super) async fn handle_event_in_error_log_mode(&mut self, key: KeyEvent) -> EventAction {
+        let error_log_view = match self.error_log_view.as_mut() {
+            Some(error_log_view) => error_log_view,
+            None => return EventAction::NoNeedReDraw,
+        };
+
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _)
+            | (KeyCode::Char('q'), _)
+            | (KeyCode::Char('c'), KeyModifiers::CONTROL) => self.close_error_log(),
+            (KeyCode::Down, _) | (KeyCode::Char('j'), _) => error_log_view.next(),
+            (KeyCode::Up, _) | (KeyCode::Char('k'), _) => error_log_view.previous(),
+            // 복사한 뒤 닫고 결과를 status 로 보여준다
+            (KeyCode::Char('y'), _) => {
+                let text = error_log_view.selected().map(|e| e.text());
+                self.close_error_log();
+                if let Some(text) = text {
+                    self.status = Some(match self.copy_to_clipboard(text).await {
+                        Ok(_) => StatusMessage::Info("copied the error details".to_owned()),
+                        // 복사하지 못한 것은 error log 에 남기지 않는다
+                        Err(e) => StatusMessage::Error(ErrorLogEntry::new(e.to_string())),
+                    });
+                }
+            }
+            _ => return EventAction::NoNeedReDraw,
+        }
+        EventAction::NeedReDraw
+    }
+}
@@ -0,0 +1,158 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use eyre::Result;
+
+use super::*;
+use crate::s3::frecency::{self, Visit};
+
+// 방문 기록을 받은 순서대로 파일에 쓴다
+pub(super) fn spawn_frecency_writer(
+    ev_tx: Sender<ClientOutput>,
+) -> (Sender<String>, JoinHandle<()>) {
+    let (tx, mut rx) = channel::<String>(1);
+    let writer = tokio::spawn(async move {
+        while let Some(contents) = rx.recv().await {
+            let written = tokio::task::spawn_blocking(move || FrecencyDb::write(&contents)).await;
+            if let Ok(Err(e)) = written {
+                let entry = ErrorLogEntry::new(format!("frecency: {}", e));
+                let _ = ev_tx
+                    .send(ClientOutput::Status(StatusMessage::Error(entry)))
+                    .await;
+            }
+        }
+    });
+    (tx, writer)
+}
+
+impl Controller {
+    pub(super) fn open_frecency_jump(&mut self) {
+        let visits = self.frecency.query(&[], frecency::now());
+        self.frecency_jump = Some(FrecencyJumpViewModel::new(visits));
+        self.input_mode = InputMode::FrecencyJump;
+    }
+
+    fn close_frecency_jump(&mut self) {
+        self.frecency_jump = None;
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub(super) async fn handle_event_in_frecency_jump_mode(
+        &mut self,
+        key: KeyEvent,
+    ) -> EventAction {
+        let frecency_jump = match self.frecency_jump.as_mut() {
+            Some(frecency_jump) => frecency_jump,
+            None => return EventAction::NoNeedReDraw,
+        };
+
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                self.close_frecency_jump();
+            }
+            (KeyCode::Enter, _) => {
+                let selected = frecency_jump.selected().cloned();
+                self.close_frecency_jump();
+                if let Some(visit) = selected {
+                    if let Err(e) = self.open_visit(visit).await {
+                        self.report_error(e);
+                    }
+                }
+            }
+            (KeyCode::Down, _) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => {
+                frecency_jump.next();
+            }
+            (KeyCode::Up, _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                frecency_jump.previous();
+            }
+            (KeyCode::Backspace, _) => frecency_jump.pop_query(),
+            (KeyCode::Char(c), KeyModifiers::NONE) | (KeyCode::Char(c), KeyModifiers::SHIFT) => {
+                frecency_jump.push_query(c);
+            }
+            _ => return EventAction::NoNeedReDraw,
+        }
+        EventAction::NeedReDraw
+    }
+
+    // keyword 와 일치하는 가장 점수가 높은 prefix 로 이동한다
+    pub(super) async fn jump(&mut self, keywords: Vec<String>) -> Result<()> {
+        let visit = self
+            .frecency
+            .query(&keywords, frecency::now())
+            .into_iter()
+            .next()
+            .ok_or_else(|| eyre::eyre!("no visited prefix matches {}", keywords.join(" ")))?;
+        self.open_visit(visit).await
+    }
+
+    async fn open_visit(&mut self, visit: Visit) -> Result<()> {
+        // 방문 기록에는 서명 여부가 없으므로 현재 tab 을 따른다
+        let no_sign_request = self
+            .tab
+            .backend
+            .connection()
+            .is_some_and(|c| c.no_sign_request);
+        self.use_connection(visit.profile, visit.endpoint_url, no_sign_request)
+            .await?;
+        self.go_to_location(visit.bucket, visit.prefix).await;
+        Ok(())
+    }
+
+    // 현재 위치를 방문 기록에 남긴다
+    pub(super) async fn record_visit(&mut self) {
+        let (bucket, prefix) = match self.tab.vm.bucket_and_prefix() {
+            Some(bucket_and_prefix) => bucket_and_prefix,
+            None => return,
+        };
+        // 로컬 디스크는 기록하지 않는다
+        let config = match self.tab.backend.connection() {
+            Some(config) => config.clone(),
+            None => return,
+        };
+        self.frecency.visit(Visit {
+            bucket,
+            prefix,
+            profile: config.profile,
+            endpoint_url: config.endpoint_url,
+            rank: 0.0,
+            last_accessed: frecency::now(),
+        });
+        self.frecency_changed = true;
+    }
+
+    // 방문할 때마다 쓰지 않고 입력이 없을 때 모아서 쓴다. 이전 쓰기가 끝나지 않았다면 다음에 쓴다
+    pub(super) fn save_frecency_in_background(&mut self) {
+        if !self.frecency_changed {
+            return;
+        }
+        let (writer, _) = match self.frecency_writer.as_ref() {
+            Some(frecency_writer) => frecency_writer,
+            None => return,
+        };
+        match self.frecency.contents() {
+            Ok(contents) => {
+                if writer.try_send(contents).is_ok() {
+                    self.frecency_changed = false;
+                }
+            }
+            Err(e) => {
+                self.frecency_changed = false;
+                self.set_status(StatusMessage::Error(ErrorLogEntry::new(format!(
+                    "frecency: {}",
+                    e
+                ))));
+            }
+        }
+    }
+
+    // 끝내기 전에 쓰고 있던 방문 기록과 남은 방문 기록을 쓴다
+    pub(super) async fn save_frecency(&mut self) {
+        if let Some((writer, handle)) = self.frecency_writer.take() {
+            if self.frecency_changed {
+                if let Ok(contents) = self.frecency.contents() {
+                    let _ = writer.send(contents).await;
+                }
+            }
+            drop(writer);
+            let _ = handle.await;
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use eyre::Result;
+
+use super::*;
+
+impl Controller {
+    // mfa code 가 필요하다면 code 를 입력받고, 아니라면 status 에 보여준다
+    pub(super) fn report_error(&mut self, e: eyre::Report) {
+        match e.downcast::<MfaRequired>() {
+            Ok(required) => self.open_mfa_prompt(required, self.tab.id, None),
+            Err(e) => self.set_status(StatusMessage::error(&e)),
+        }
+    }
+
+    // 입력중인 prompt 가 있다면 그 다음에 묻는다. 이미 code 를 기다리는 tab 은 다시 묻지 않는다
+    pub(super) fn open_mfa_prompt(
+        &mut self,
+        required: MfaRequired,
+        tab_id: usize,
+        location: Option<String>,
+    ) {
+        if let Some(mfa_prompt) = self.mfa_prompt.as_ref() {
+            let waiting = mfa_prompt.tab_id == tab_id
+                || self.mfa_queue.iter().any(|(_, id, _)| *id == tab_id);
+            if !waiting {
+                self.mfa_queue.push_back((required, tab_id, location));
+            }
+            return;
+        }
+        self.mfa_prompt = Some(MfaPromptViewModel::new(
+            required.mfa_serial,
+            tab_id,
+            required.config,
+            location,
+        ));
+        self.input_mode = InputMode::MfaPrompt;
+    }
+
+    fn close_mfa_prompt(&mut self) -> Option<MfaPromptViewModel> {
+        self.input_mode = InputMode::Normal;
+        self.mfa_prompt.take()
+    }
+
+    // 다음으로 code 를 기다리는 tab 의 prompt 를 연다. 그 사이에 닫힌 tab 은 건너뛴다
+    fn open_next_mfa_prompt(&mut self) {
+        while let Some((required, tab_id, location)) = self.mfa_queue.pop_front() {
+            if self.tab_position(tab_id).is_some() {
+                self.open_mfa_prompt(required, tab_id, location);
+                return;
+            }
+        }
+    }
+
+    pub(super) async fn handle_event_in_mfa_prompt_mode(&mut self, key: KeyEvent) -> EventAction {
+        let mfa_prompt = match self.mfa_prompt.as_mut() {
+            Some(mfa_prompt) => mfa_prompt,
+            None => return EventAction::NoNeedReDraw,
+        };
+
+        match (key.code, key.modifiers) {
+            // 취소한 tab 은 연결하지 않은 채로 두고 다음에 이동할 때 다시 묻는다
+            (KeyCode::Esc, _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                self.close_mfa_prompt();
+                self.open_next_mfa_prompt();
+            }
+            (KeyCode::Char(c), _) => mfa_prompt.push(c),
+            (KeyCode::Backspace, _) => mfa_prompt.pop(),
+            (KeyCode::Enter, _) => {
+                if let Some(mfa_prompt) = self.close_mfa_prompt() {
+                    if let Err(e) = self.submit_mfa_code(mfa_prompt).await {
+                        self.report_error(e);
+                    }
+                }
+                self.open_next_mfa_prompt();
+            }
+            _ => return EventAction::NoNeedReDraw,
+        }
+        EventAction::NeedReDraw
+    }
+
+    // session 을 만든 뒤 code 를 요청한 tab 과 같은 mfa device 를 기다리던 tab 을 다시 연결한다
+    async fn submit_mfa_code(&mut self, mfa_prompt: MfaPromptViewModel) -> Result<()> {
+        S3Client::start_mfa_session(&mfa_prompt.config, mfa_prompt.code()).await?;
+        let (same, others) = std::mem::take(&mut self.mfa_queue)
+            .into_iter()
+            .partition(|(required, _, _)| required.mfa_serial == mfa_prompt.mfa_serial);
+        self.mfa_queue = others;
+
+        if let Some(index) = self.tab_position(mfa_prompt.tab_id) {
+            self.switch_tab(index);
+        }
+        let current = self.tab_index;
+        for (required, tab_id, location) in same {
+            if let Some(index) = self.tab_position(tab_id) {
+                self.switch_tab(index);
+                if let Err(e) = self.connect(required.config, location).await {
+                    self.report_error(e);
+                }
+            }
+        }
+        self.switch_tab(current);
+        self.connect(mfa_prompt.config, mfa_prompt.location).await
+    }
+
+    async fn connect(&mut self, config: S3ClientConfig, location: Option<String>) -> Result<()> {
+        self.replace_client(config).await?;
+        match location {
+            Some(location) => self.go_to(&location).await?,
+            None => self.go_to_bucket_list().await,
+        }
+        Ok(())
+    }
+}
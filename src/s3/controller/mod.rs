@@ -1,253 +1,811 @@
 use async_trait::async_trait;
 use eyre::Result;
-use std::sync::Arc;
+use std::{collections::VecDeque, path::PathBuf, sync::Arc};
 use tui::{
+    backend::Backend,
+    layout::Rect,
     layout::{Constraint, Direction, Layout},
-    style::{Color, Style},
-    text::{Span, Text},
-    widgets::{Block, Borders, Paragraph},
+    style::Style,
+    text::{Span, Spans, Text},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Tabs},
+    Terminal,
 };
 
 use crossterm::event::{Event as TerminalEvent, KeyCode, KeyEvent, KeyModifiers};
+use std::time::Instant;
 
-use crate::{App, CrosstermTerminal, EventAction, FrontendEvent};
+use crate::{App, EventAction, FrontendEvent};
+
+mod bookmark;
+mod command_line;
+mod commander;
+mod connection;
+mod error_log;
+mod frecency_jump;
+mod mfa;
+mod pinned;
+mod profile;
+mod tab;
+
+use crate::s3::connection::Connection;
+use tab::Tab;
 
 use super::{
-    client::S3Client,
-    view_model::{S3ItemsViewModel, S3Output},
+    backend::{
+        local::{self, LocalBackend},
+        unconnected::UnconnectedBackend,
+        Scheme, StorageBackend,
+    },
+    bookmark::Bookmarks,
+    client::{
+        assume_role::MfaRequired, endpoint::Endpoints, BucketWithLocation, S3Client, S3ClientConfig,
+    },
+    config::{Config, EndpointConfig},
+    frecency::FrecencyDb,
+    keymap::{Action, Keymap, KeymapResult},
+    path,
+    theme::Theme,
+    view_model::{
+        bookmark_list::BookmarkListViewModel,
+        error_log::{ErrorLogEntry, ErrorLogViewModel},
+        frecency_jump::FrecencyJumpViewModel,
+        fuzzy_finder::FuzzyFinderViewModel,
+        history::{HistoryEntry, NavigationHistory},
+        mfa_prompt::MfaPromptViewModel,
+        popup_rect,
+        profile_list::ProfileListViewModel,
+        S3ItemsViewModel, S3Output,
+    },
     S3Item, S3ItemType,
 };
+use aws_sdk_s3::{model::BucketLocationConstraint, output::ListObjectsV2Output};
 use copypasta_ext::{prelude::*, x11_fork::ClipboardContext};
+use futures::StreamExt;
 use structopt::StructOpt;
-use tokio::sync::{
-    mpsc::{channel, Receiver, Sender},
-    Mutex,
+use tokio::{
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Mutex,
+    },
+    task::JoinHandle,
 };
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "tui-s3", about = "tui for s3")]
 pub struct Opt {
-    /// Where to write the output: to `stdout` or `file`
+    /// Locations (s3://, https://, arn:, a local path or @bookmark) to open, each in its own tab
     #[structopt(parse(from_str))]
-    s3_path: Option<String>,
+    s3_paths: Vec<String>,
 
     #[structopt(parse(from_str), long = "profile")]
     profile: Option<String>,
 
     #[structopt(parse(from_str), long = "endpoint-url", short = "e")]
     endpoint_url: Option<String>,
+
+    /// Don't sign requests, for public buckets. Lists only the visited buckets
+    #[structopt(long = "no-sign-request")]
+    no_sign_request: bool,
+
+    /// Trust only the certificates in this PEM file (overrides AWS_CA_BUNDLE)
+    #[structopt(parse(from_os_str), long = "ca-bundle")]
+    ca_bundle: Option<PathBuf>,
+
+    /// Don't verify TLS certificates
+    #[structopt(long = "no-verify-ssl")]
+    no_verify_ssl: bool,
 }
 
-impl Opt {
-    // s3_path uri 를 String을 bucket, prefix 로 빼낸다
-    fn parse_s3_path(&self) -> Result<Option<(String, String)>> {
-        if let Some(s3_path) = &self.s3_path {
-            if let Some(str) = s3_path.strip_prefix("s3://") {
-                if let Some(i) = str.find("/") {
-                    let bucket = str[..i].to_owned();
-                    let prefix = str
-                        .strip_prefix(&(bucket.clone() + "/"))
-                        .unwrap()
-                        .to_owned();
-
-                    // key must be removed
-                    let prefix = if !prefix.ends_with("/") {
-                        if let Some(i) = prefix.rfind("/") {
-                            prefix[..i + 1].to_owned()
-                        } else {
-                            String::default()
-                        }
-                    } else {
-                        prefix
-                    };
+// 동시에 보낼 GetBucketLocation 요청 수
+const MAX_LOCATION_REQUESTS: usize = 8;
 
-                    Ok(Some((bucket, prefix)))
-                } else {
-                    Ok(Some((str.to_owned(), String::default())))
-                }
-            } else {
-                Err(eyre::eyre!("s3_path must start with s3://"))
+fn resolving_buckets(buckets: &[BucketWithLocation]) -> Vec<String> {
+    buckets
+        .iter()
+        .filter(|b| b.is_resolving())
+        .filter_map(|b| b.bucket.name().map(|n| n.to_owned()))
+        .collect()
+}
+
+// bucket 의 location 을 찾는 대로 보낸다. 찾지 못한 bucket 은 목록에 unknown 으로 보여준다
+async fn resolve_locations(
+    backend: Arc<dyn StorageBackend>,
+    tab_id: usize,
+    buckets: Vec<String>,
+    ev_tx: Sender<ClientOutput>,
+) {
+    let mut locations = futures::stream::iter(buckets)
+        .map(|bucket| {
+            let backend = backend.clone();
+            async move {
+                let location = backend
+                    .bucket_location(&bucket)
+                    .await
+                    .unwrap_or_else(|_| BucketLocationConstraint::from(""));
+                (bucket, location)
             }
-        } else {
-            Ok(None)
+        })
+        .buffer_unordered(MAX_LOCATION_REQUESTS);
+    while let Some((bucket, location)) = locations.next().await {
+        let event = ClientOutput::BucketLocation {
+            tab_id,
+            bucket,
+            location,
+        };
+        // 앱이 끝났다면 더 조회하지 않는다
+        if ev_tx.send(event).await.is_err() {
+            break;
         }
     }
 }
 
+// tab 의 목록을 한 페이지씩 조회해 보낸다. continuation_token 이 있다면 첫 페이지는 이미 받은 것이다
+fn spawn_object_list(
+    tab: &mut Tab,
+    ev_tx: Sender<ClientOutput>,
+    bucket: String,
+    prefix: String,
+    mut continuation_token: Option<String>,
+) {
+    let backend = tab.backend.clone();
+    let tab_id = tab.id;
+    let mut pages = usize::from(continuation_token.is_some());
+    tab.spawn_request(pages, move |request_id| async move {
+        loop {
+            let event = match backend
+                .list_objects_page(&bucket, &prefix, continuation_token.take())
+                .await
+            {
+                Ok((page, next_continuation_token)) => {
+                    pages += 1;
+                    continuation_token = next_continuation_token;
+                    ClientOutput::ObjectsPage {
+                        tab_id,
+                        request_id,
+                        page: Box::new(page),
+                        pages,
+                        done: continuation_token.is_none(),
+                    }
+                }
+                // credential 이 없거나 만료된 경우에도 이유를 보여준다. 이미 받은 페이지는 그대로 둔다
+                Err(e) => ClientOutput::RequestFailed {
+                    tab_id,
+                    request_id,
+                    status: StatusMessage::error(&e),
+                },
+            };
+            let done = !matches!(event, ClientOutput::ObjectsPage { done: false, .. });
+            // 앱이 끝났다면 더 조회하지 않는다
+            if ev_tx.send(event).await.is_err() || done {
+                break;
+            }
+        }
+    });
+}
+
+// s3_path uri 를 String을 bucket, prefix 로 빼낸다
+fn parse_s3_path(s3_path: &str) -> Result<(String, String)> {
+    path::parse_s3_prefix(s3_path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::s3::backend::memory::MemoryBackend;
+    use std::time::Duration;
 
-    #[test]
-    fn test_parse_s3_path() {
-        let make_opt = |s: &str| Opt {
-            s3_path: Some(s.to_owned()),
-            profile: None,
-            endpoint_url: None,
+    // 요청한 조회 결과가 모두 반영될 때까지 기다린다
+    async fn settle(controller: &mut Controller) {
+        while let Ok(Some(output)) =
+            tokio::time::timeout(Duration::from_millis(100), controller.ev_rx.recv()).await
+        {
+            controller.handle_event(Event::ClientEvent(output)).await;
+        }
+    }
+
+    async fn press(controller: &mut Controller, code: KeyCode) {
+        let key = KeyEvent::new(code, KeyModifiers::NONE);
+        let event = FrontendEvent::TerminalEvent(TerminalEvent::Key(key));
+        controller.handle_event(Event::KeyEvent(event)).await;
+        settle(controller).await;
+    }
+
+    fn names(controller: &Controller) -> Vec<String> {
+        controller.tab.vm.items().iter().map(|i| i.name()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_controller_with_memory_backend() {
+        let backend = Arc::new(MemoryBackend::new());
+        backend.insert("logs", "2024/01.log", "a");
+        backend.insert("logs", "2024/02.log", "b");
+        backend.insert("logs", "readme.txt", "readme");
+        backend.insert("photos", "cat.jpg", "cat");
+
+        let mut controller = Controller::with_backend(backend.clone()).await.unwrap();
+        assert_eq!(names(&controller), vec!["logs", "photos"]);
+
+        press(&mut controller, KeyCode::Enter).await;
+        assert_eq!(names(&controller), vec!["", "2024/", "readme.txt"]);
+
+        press(&mut controller, KeyCode::Char('j')).await;
+        press(&mut controller, KeyCode::Enter).await;
+        assert_eq!(controller.tab.title(), "logs/2024/");
+        assert_eq!(names(&controller), vec!["", "01.log", "02.log"]);
+
+        controller.execute_command_line("rm! 01.log").await;
+        settle(&mut controller).await;
+        assert_eq!(names(&controller), vec!["", "02.log"]);
+
+        // .. 으로 돌아가면 이전에 선택한 prefix 가 선택되어 있다
+        press(&mut controller, KeyCode::Char('k')).await;
+        press(&mut controller, KeyCode::Enter).await;
+        assert_eq!(controller.tab.vm.selected_s3_uri(), "s3://logs/2024/");
+
+        // 다른 tab 을 다른 pane 으로 열어 선택한 object 를 복사한다
+        controller.execute_command_line("tabnew s3://photos/").await;
+        settle(&mut controller).await;
+        controller.previous_tab();
+        press(&mut controller, KeyCode::Char('|')).await;
+        press(&mut controller, KeyCode::Char('j')).await;
+        press(&mut controller, KeyCode::F(5)).await;
+        assert_eq!(
+            backend.get_object("photos", "readme.txt").await.unwrap(),
+            b"readme"
+        );
+        assert_eq!(
+            controller.status.as_ref().map(|s| format!("{:?}", s)),
+            Some("Info(\"copied 1 object to s3://photos/\")".to_owned())
+        );
+        let other = controller
+            .other_tabs
+            .iter()
+            .find(|t| Some(t.id) == controller.commander);
+        let other_names: Vec<_> = other.unwrap().vm.items().iter().map(|i| i.name()).collect();
+        assert_eq!(other_names, vec!["", "cat.jpg", "readme.txt"]);
+    }
+
+    #[tokio::test]
+    async fn test_unconnected_tab() {
+        let config = S3ClientConfig::default();
+        let tab = Tab::unconnected(0, config, Some("s3://logs/2024/"), "expired".to_owned());
+        assert_eq!(tab.title(), "logs/2024/");
+        assert!(!tab.backend.is_connected());
+        let e = tab.backend.list_buckets().await.unwrap_err();
+        assert_eq!(e.to_string(), "not connected: expired");
+    }
+
+    #[tokio::test]
+    async fn test_mfa_prompt_queue() {
+        let backend = Arc::new(MemoryBackend::new());
+        let mut controller = Controller::with_backend(backend).await.unwrap();
+        controller.execute_command_line("tabnew").await;
+        let required = |mfa_serial: &str| MfaRequired {
+            mfa_serial: mfa_serial.to_owned(),
+            config: S3ClientConfig::default(),
         };
 
+        // tab 마다 한 번씩 차례로 묻는다
+        controller.open_mfa_prompt(required("first"), 0, None);
+        controller.open_mfa_prompt(required("first"), 0, None);
+        controller.open_mfa_prompt(required("second"), 1, None);
+        assert_eq!(controller.mfa_queue.len(), 1);
+        let prompt_tab = |controller: &Controller| controller.mfa_prompt.as_ref().map(|p| p.tab_id);
+        assert_eq!(prompt_tab(&controller), Some(0));
+
+        press(&mut controller, KeyCode::Esc).await;
+        assert_eq!(prompt_tab(&controller), Some(1));
+        press(&mut controller, KeyCode::Esc).await;
+        assert_eq!(prompt_tab(&controller), None);
+        assert!(matches!(controller.input_mode, InputMode::Normal));
+    }
+
+    #[test]
+    fn test_parse_s3_path() {
         assert_eq!(
-            make_opt("s3://bucket/p1/p2/").parse_s3_path().unwrap(),
-            Some(("bucket".to_owned(), "p1/p2/".to_owned()))
+            parse_s3_path("s3://bucket/p1/p2/").unwrap(),
+            ("bucket".to_owned(), "p1/p2/".to_owned())
         );
         assert_eq!(
-            make_opt("s3://bucket/p1/p2/k").parse_s3_path().unwrap(),
-            Some(("bucket".to_owned(), "p1/p2/".to_owned()))
+            parse_s3_path("s3://bucket/p1/p2/k").unwrap(),
+            ("bucket".to_owned(), "p1/p2/".to_owned())
         );
         assert_eq!(
-            make_opt("s3://bucket").parse_s3_path().unwrap(),
-            Some(("bucket".to_owned(), "".to_owned()))
+            parse_s3_path("s3://bucket").unwrap(),
+            ("bucket".to_owned(), "".to_owned())
         );
         assert_eq!(
-            make_opt("s3://bucket/").parse_s3_path().unwrap(),
-            Some(("bucket".to_owned(), "".to_owned()))
+            parse_s3_path("s3://bucket/").unwrap(),
+            ("bucket".to_owned(), "".to_owned())
         );
         assert_eq!(
-            make_opt("s3://bucket/key").parse_s3_path().unwrap(),
-            Some(("bucket".to_owned(), "".to_owned()))
+            parse_s3_path("s3://bucket/key").unwrap(),
+            ("bucket".to_owned(), "".to_owned())
         );
     }
 }
 
+#[derive(Debug)]
+pub enum ClientOutput {
+    // tab_id 번 tab 의 request_id 번 조회가 받은 bucket 목록
+    S3Output {
+        tab_id: usize,
+        request_id: usize,
+        output: S3Output,
+    },
+    // tab_id 번 tab 의 request_id 번 조회가 받은 pages 번째 페이지. 첫 페이지가 목록을 바꾼다
+    ObjectsPage {
+        tab_id: usize,
+        request_id: usize,
+        page: Box<ListObjectsV2Output>,
+        pages: usize,
+        done: bool,
+    },
+    // tab_id 번 tab 의 request_id 번 조회가 실패한 이유. 지난 조회의 실패라면 보여주지 않는다
+    RequestFailed {
+        tab_id: usize,
+        request_id: usize,
+        status: StatusMessage,
+    },
+    // 저장소를 바꾼 뒤 tab_id 번 tab 이 아직 bucket, prefix 를 보여주고 있다면 목록을 새로 조회한다
+    Refresh {
+        tab_id: usize,
+        bucket: String,
+        prefix: String,
+    },
+    // tab_id 번 tab 의 bucket 목록에서 조회중이던 bucket 의 location
+    BucketLocation {
+        tab_id: usize,
+        bucket: String,
+        location: BucketLocationConstraint,
+    },
+    // fuzzy finder 를 위해 조회한 key 목록
+    Keys {
+        walk_id: usize,
+        keys: Vec<String>,
+        done: bool,
+    },
+    // fuzzy finder 를 위한 조회가 실패했다. 그때까지 찾은 key 로 끝낸다
+    KeysFailed {
+        walk_id: usize,
+        error: eyre::Report,
+    },
+    // command line 에서 tab 을 눌렀을 때 구한 완성 후보
+    Completions {
+        input_line: String,
+        candidates: Vec<String>,
+    },
+    Status(StatusMessage),
+}
+
+#[derive(Debug)]
+pub enum StatusMessage {
+    Info(String),
+    // error log 에도 남는다
+    Error(ErrorLogEntry),
+}
+
+impl StatusMessage {
+    pub fn error(e: &eyre::Report) -> Self {
+        StatusMessage::Error(ErrorLogEntry::from_report(e))
+    }
+}
+
 #[derive(Debug)]
 pub enum Event {
-    ClientEvent(S3Output),
+    ClientEvent(ClientOutput),
     KeyEvent(FrontendEvent),
+    // 여러 키로 이루어진 binding 의 다음 키를 기다리다 시간이 지났다
+    KeyTimeout,
 }
 
 #[derive(PartialEq)]
 enum InputMode {
     Normal,
     Search,
+    Command,
+    FuzzyFinder,
+    BookmarkList,
+    FrecencyJump,
+    ProfileList,
+    MfaPrompt,
+    ErrorLog,
 }
 
 pub struct Controller {
     // 컨트롤  대상
-    vm: S3ItemsViewModel,
-    client: Arc<Mutex<S3Client>>,
+    tab: Tab,
+    // 현재 tab 을 제외한 tab. 현재 tab 은 전체 tab 중 tab_index 번째다
+    other_tabs: Vec<Tab>,
+    tab_index: usize,
     // UI를 다시 그릴것을 요청하기 위한 sender
-    ev_tx: Sender<S3Output>,
-    ev_rx: Receiver<S3Output>,
-    key_events: Vec<KeyEvent>,
-    clipboard_context: Arc<Mutex<ClipboardContext>>,
+    ev_tx: Sender<ClientOutput>,
+    ev_rx: Receiver<ClientOutput>,
+    keymap: Keymap,
+    theme: Theme,
+    // X 가 없는 환경에서는 None 이다
+    clipboard_context: Option<Arc<Mutex<ClipboardContext>>>,
     input_mode: InputMode,
-    search_input: String,
+    // search(/) 와 command(:) 입력
+    input_line: String,
+    completions: Vec<String>,
+    completion_index: Option<usize>,
+    command_history: Vec<String>,
+    history_index: Option<usize>,
+    // 다음 키 입력 전까지 보여줄 메시지
+    status: Option<StatusMessage>,
+    fuzzy_finder: Option<FuzzyFinderViewModel>,
+    fuzzy_walk: Option<(usize, JoinHandle<()>)>,
+    bookmarks: Bookmarks,
+    bookmark_list: Option<BookmarkListViewModel>,
+    frecency: FrecencyDb,
+    // 아직 파일에 쓰지 않은 방문 기록이 있다
+    frecency_changed: bool,
+    frecency_writer: Option<(Sender<String>, JoinHandle<()>)>,
+    frecency_jump: Option<FrecencyJumpViewModel>,
+    profile_list: Option<ProfileListViewModel>,
+    mfa_prompt: Option<MfaPromptViewModel>,
+    // 입력중인 prompt 다음에 mfa code 를 물을 (요청, tab id, 이동할 위치)
+    mfa_queue: VecDeque<(MfaRequired, usize, Option<String>)>,
+    // status 로 보여준 error. 오래된 것부터 쌓인다
+    error_log: Vec<ErrorLogEntry>,
+    error_log_view: Option<ErrorLogViewModel>,
+    // commander 에서 현재 tab 과 나란히 보여줄 tab 의 id
+    commander: Option<usize>,
+    // s3 가 아닌 tab 에서 s3 위치로 이동할 때 사용할 연결 설정
+    default_connection: S3ClientConfig,
+    // 설정 파일의 연결. 있다면 bucket 목록 위에 연결 목록을 보여준다
+    connections: Vec<Connection>,
+    // 설정 파일에서 모든 연결의 bucket 목록에 더할 bucket
+    pinned_buckets: Vec<String>,
 }
 impl Controller {
     pub async fn new(opt: Opt) -> Result<Self> {
-        let (ev_tx, ev_rx) = channel(100);
-        let mut controller = Self {
-            vm: S3ItemsViewModel::new(),
-            // TODO: 에러 처리
-            client: Arc::new(Mutex::new(
-                S3Client::new(opt.profile.as_ref(), opt.endpoint_url.as_ref()).await?,
-            )),
-            ev_tx,
-            ev_rx,
-            key_events: Default::default(),
-            clipboard_context: Arc::new(Mutex::new(ClipboardContext::new().unwrap())),
-            input_mode: InputMode::Normal,
-            search_input: String::default(),
+        // 설정에 문제가 있다면 기본 설정으로 시작하고 status 로 알린다
+        let (config, mut config_problems) = match Config::load() {
+            Ok(config) => (config, vec![]),
+            Err(e) => (Config::default(), vec![e.to_string()]),
+        };
+        let bookmarks = Bookmarks::load().unwrap_or_else(|e| {
+            config_problems.push(format!("bookmarks: {}", e));
+            Bookmarks::default()
+        });
+        let frecency = FrecencyDb::load().unwrap_or_else(|e| {
+            config_problems.push(format!("frecency: {}", e));
+            FrecencyDb::default()
+        });
+        // 설정 파일의 endpoint 에 ca_bundle 이나 no_verify_ssl 이 없다면 명령행 옵션을 따른다
+        let endpoints = Endpoints::new(
+            config.endpoints.clone(),
+            EndpointConfig {
+                ca_bundle: opt.ca_bundle.clone(),
+                no_verify_ssl: Some(opt.no_verify_ssl),
+                ..EndpointConfig::default()
+            },
+        );
+        let default_connection = S3ClientConfig {
+            profile: opt.profile.clone(),
+            endpoint_url: opt.endpoint_url.clone(),
+            region: None,
+            assume_role: None,
+            no_sign_request: opt.no_sign_request,
+            endpoints: endpoints.clone(),
         };
 
-        controller.init(opt).await?;
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let (connections, problems) = Connection::load(&config.connections, &endpoints, no_color);
+        config_problems.extend(problems);
+        let config_pinned = config.pinned_buckets.clone();
+        // 연결이 있고 경로나 연결 설정을 지정하지 않았다면 연결 목록에서 시작한다
+        let start_at_connections = !connections.is_empty()
+            && opt.s3_paths.is_empty()
+            && opt.profile.is_none()
+            && opt.endpoint_url.is_none()
+            && !opt.no_sign_request;
+
+        // 경로마다 tab 을 동시에 연다. 열지 못한 tab 은 이유를 status 에 보여주고, mfa code 가
+        // 필요하다면 code 를 입력받을 때까지 빈 tab 을 보여준다
+        let locations = opt.resolve_locations(&bookmarks, &endpoints)?;
+        let opened = futures::future::join_all(locations.iter().enumerate().map(
+            |(id, (config, s3_path))| {
+                let (connections, config_pinned) = (&connections, &config_pinned);
+                async move {
+                    if start_at_connections {
+                        Tab::open_connections(id, config.clone(), connections.clone()).await
+                    } else {
+                        let connection = connections.iter().find(|c| c.matches(config));
+                        let pinned = pinned::pinned_buckets(config_pinned, connection);
+                        Tab::open(id, config.clone(), s3_path.as_deref(), pinned).await
+                    }
+                }
+            },
+        ))
+        .await;
+
+        let mut tabs = vec![];
+        let mut mfa_required = vec![];
+        let mut open_errors = vec![];
+        for (tab, (config, s3_path)) in opened.into_iter().zip(locations) {
+            let id = tabs.len();
+            match tab.map_err(|e| e.downcast::<MfaRequired>()) {
+                Ok(tab) => tabs.push(tab),
+                Err(Ok(required)) => {
+                    let reason = required.to_string();
+                    tabs.push(Tab::unconnected(id, config, s3_path.as_deref(), reason));
+                    mfa_required.push((required, id, s3_path));
+                }
+                Err(Err(e)) => {
+                    open_errors.push(format!(
+                        "{}: {}",
+                        s3_path.as_deref().unwrap_or("buckets"),
+                        e
+                    ));
+                    tabs.push(Tab::unconnected(
+                        id,
+                        config,
+                        s3_path.as_deref(),
+                        e.to_string(),
+                    ));
+                }
+            }
+        }
+
+        let (keymap, keymap_problems) = Keymap::new(&config.keymap);
+        let (theme, theme_problems) = Theme::new(&config.theme, no_color);
+        config_problems.extend(keymap_problems);
+        config_problems.extend(theme_problems);
+        let mut controller = Self::with_tabs(tabs, keymap, theme, bookmarks, frecency);
+        controller.frecency_writer = Some(frecency_jump::spawn_frecency_writer(
+            controller.ev_tx.clone(),
+        ));
+        controller.default_connection = default_connection;
+        for tab in std::iter::once(&mut controller.tab).chain(controller.other_tabs.iter_mut()) {
+            connection::label_connection(tab, &connections, &config_pinned);
+            open_errors.extend(tab.open_error.take());
+        }
+        controller.connections = connections;
+        controller.pinned_buckets = config_pinned;
+        if !config_problems.is_empty() {
+            open_errors.insert(0, format!("config: {}", config_problems.join(", ")));
+        }
+        // ListBuckets 가 거부되어도 고정한 bucket 목록으로 시작한다
+        if !open_errors.is_empty() {
+            controller.set_status(StatusMessage::Error(ErrorLogEntry::new(
+                open_errors.join(", "),
+            )));
+        }
+
+        for (required, tab_id, location) in mfa_required {
+            controller.open_mfa_prompt(required, tab_id, location);
+        }
+
+        controller.resolve_tab_locations();
+        controller.continue_tab_listings();
+        controller.record_visit().await;
 
         Ok(controller)
     }
 
-    pub async fn init(&mut self, opt: Opt) -> Result<()> {
-        let output = if let Some((bucket, prefix)) = opt.parse_s3_path()? {
-            S3Output::Objects(
-                self.client
-                    .lock()
-                    .await
-                    .list_objects(&bucket, &prefix)
-                    .await?,
-            )
-        } else {
-            S3Output::Buckets(self.client.lock().await.list_buckets().await?)
-        };
-        self.vm.push(output);
-        Ok(())
+    // 설정 파일, bookmark, 방문 기록 없이 backend 의 bucket 목록에서 시작한다. 테스트와
+    // AWS 가 없는 환경에서 사용한다
+    pub async fn with_backend(backend: Arc<dyn StorageBackend>) -> Result<Self> {
+        let tab = Tab::open_backend(0, backend, None, vec![]).await?;
+        let config = Config::default();
+        Ok(Self::with_tabs(
+            vec![tab],
+            Keymap::new(&config.keymap).0,
+            Theme::new(&config.theme, false).0,
+            Bookmarks::default(),
+            FrecencyDb::default(),
+        ))
     }
 
-    async fn handle_event_in_nomal_mode(
-        &mut self,
-        key: KeyEvent,
-        last_key_event: Option<KeyEvent>,
-    ) -> EventAction {
-        match (key.code, key.modifiers) {
-            (KeyCode::Char('q'), KeyModifiers::NONE) => EventAction::Exit,
-            (KeyCode::Char('g'), KeyModifiers::NONE) => {
-                if let Some(key) = last_key_event.as_ref() {
-                    if key.code == KeyCode::Char('g') && key.modifiers == KeyModifiers::NONE {
-                        self.vm.first();
-                        // gg pressed
-                        EventAction::NeedReDraw
-                    } else {
-                        EventAction::NoNeedReDraw
+    fn with_tabs(
+        mut tabs: Vec<Tab>,
+        keymap: Keymap,
+        theme: Theme,
+        bookmarks: Bookmarks,
+        frecency: FrecencyDb,
+    ) -> Self {
+        let (ev_tx, ev_rx) = channel(100);
+        let tab = tabs.remove(0);
+
+        Self {
+            tab,
+            other_tabs: tabs,
+            tab_index: 0,
+            ev_tx,
+            ev_rx,
+            keymap,
+            theme,
+            clipboard_context: ClipboardContext::new()
+                .ok()
+                .map(|context| Arc::new(Mutex::new(context))),
+            input_mode: InputMode::Normal,
+            input_line: String::default(),
+            completions: vec![],
+            completion_index: None,
+            command_history: vec![],
+            history_index: None,
+            status: None,
+            fuzzy_finder: None,
+            fuzzy_walk: None,
+            bookmarks,
+            bookmark_list: None,
+            frecency,
+            frecency_changed: false,
+            frecency_writer: None,
+            frecency_jump: None,
+            profile_list: None,
+            mfa_prompt: None,
+            mfa_queue: VecDeque::new(),
+            error_log: vec![],
+            error_log_view: None,
+            commander: None,
+            default_connection: S3ClientConfig::default(),
+            connections: vec![],
+            pinned_buckets: vec![],
+        }
+    }
+
+    pub(super) async fn copy_to_clipboard(&self, contents: String) -> Result<()> {
+        self.clipboard_context
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("clipboard is not available"))?
+            .lock()
+            .await
+            .set_contents(contents)
+            .map_err(|e| eyre::eyre!("clipboard error: {}", e))
+    }
+
+    async fn handle_event_in_nomal_mode(&mut self, key: KeyEvent) -> EventAction {
+        let mut result = self.keymap.feed(key, Instant::now());
+        let mut event_action = EventAction::NoNeedReDraw;
+        loop {
+            match result {
+                KeymapResult::Interrupted(action, next) => {
+                    event_action = self.perform_action(action).await;
+                    // 끝내거나 다른 입력 모드로 바뀌었다면 남은 키는 버린다
+                    if matches!(event_action, EventAction::Exit)
+                        || self.input_mode != InputMode::Normal
+                    {
+                        return event_action;
                     }
+                    result = *next;
+                }
+                KeymapResult::Action(action) => return self.perform_action(action).await,
+                KeymapResult::Pending | KeymapResult::Unbound => return event_action,
+            }
+        }
+    }
+
+    async fn perform_action(&mut self, action: Action) -> EventAction {
+        match action {
+            Action::Quit => {
+                self.save_frecency().await;
+                return EventAction::Exit;
+            }
+            Action::Up => self.tab.vm.previous(),
+            Action::Down => self.tab.vm.next(),
+            Action::First => self.tab.vm.first(),
+            Action::Last => self.tab.vm.last(),
+            Action::Enter => self.enter().await,
+            Action::Refresh => {
+                self.refresh().await;
+                return EventAction::NoNeedReDraw;
+            }
+            Action::CopyUri => {
+                if let Err(e) = self.copy_to_clipboard(self.tab.vm.selected_s3_uri()).await {
+                    self.report_error(e);
                 } else {
-                    EventAction::NoNeedReDraw
+                    return EventAction::NoNeedReDraw;
                 }
             }
-            (KeyCode::Char('y'), KeyModifiers::NONE) => {
-                self.clipboard_context
-                    .lock()
-                    .await
-                    .set_contents(self.vm.selected_s3_uri())
-                    .unwrap();
-                EventAction::NoNeedReDraw
+            Action::Search => {
+                self.input_line = "/".to_owned();
+                self.tab.search.clear();
+                self.input_mode = InputMode::Search;
             }
-            (KeyCode::Char('G'), KeyModifiers::SHIFT) => {
-                self.vm.last();
-                EventAction::NeedReDraw
+            Action::SearchNext => self.search_next(),
+            Action::CommandLine => self.open_command_line(),
+            Action::FuzzyFinder => self.open_fuzzy_finder(),
+            Action::Bookmarks => self.open_bookmark_list(),
+            Action::FrecencyJump => self.open_frecency_jump(),
+            Action::Profiles => {
+                if let Err(e) = self.open_profile_list().await {
+                    self.report_error(e);
+                }
             }
-            (KeyCode::Char('r'), KeyModifiers::CONTROL) => {
-                self.refresh().await;
-                EventAction::NoNeedReDraw
+            Action::OpenBucket => self.open_bucket_prompt(),
+            Action::ErrorLog => self.open_error_log(),
+            Action::Cancel => {
+                if !self.tab.cancel_request() {
+                    return EventAction::NoNeedReDraw;
+                }
+                self.set_status(StatusMessage::Info("cancelled".to_owned()));
             }
-            (KeyCode::Down, KeyModifiers::NONE) | (KeyCode::Char('j'), KeyModifiers::NONE) => {
-                self.vm.next();
-                EventAction::NeedReDraw
+            Action::NewTab => {
+                if let Err(e) = self.new_tab(None).await {
+                    self.report_error(e);
+                }
             }
-            (KeyCode::Up, KeyModifiers::NONE) | (KeyCode::Char('k'), KeyModifiers::NONE) => {
-                self.vm.previous();
-                EventAction::NeedReDraw
+            Action::DuplicateTab => self.duplicate_tab().await,
+            Action::CloseTab => {
+                if let Err(e) = self.close_tab() {
+                    self.report_error(e);
+                }
             }
-            (KeyCode::Enter, KeyModifiers::NONE) => {
-                self.enter().await;
-                EventAction::NeedReDraw
+            Action::NextTab => self.next_tab(),
+            Action::PreviousTab => self.previous_tab(),
+            Action::Back => self.go_back().await,
+            Action::Forward => self.go_forward().await,
+            Action::Commander => {
+                if let Err(e) = self.toggle_commander().await {
+                    self.report_error(e);
+                }
             }
-            (KeyCode::Char('n'), KeyModifiers::NONE) => {
-                self.search_next();
-                EventAction::NeedReDraw
+            Action::SwitchPane => self.switch_pane(),
+            Action::CopyToPane | Action::MoveToPane => {
+                if let Err(e) = self.transfer_to_other_pane(action == Action::MoveToPane) {
+                    self.report_error(e);
+                }
             }
-            (KeyCode::Char('/'), KeyModifiers::NONE) => {
-                self.search_input = "/".to_owned();
-                self.input_mode = InputMode::Search;
-                EventAction::NeedReDraw
+            Action::AddBookmark => {
+                self.open_command_line();
+                self.input_line.push_str("bookmark ");
             }
-            (KeyCode::Char('c'), KeyModifiers::CONTROL) => EventAction::Exit,
-            _ => EventAction::NoNeedReDraw,
+            Action::Noop => return EventAction::NoNeedReDraw,
         }
+        EventAction::NeedReDraw
     }
 
-    async fn handle_event_in_edit_mode(
-        &mut self,
-        key: KeyEvent,
-        _last_key_event: Option<KeyEvent>,
-    ) -> EventAction {
+    async fn handle_event_in_fuzzy_finder_mode(&mut self, key: KeyEvent) -> EventAction {
+        let fuzzy_finder = match self.fuzzy_finder.as_mut() {
+            Some(fuzzy_finder) => fuzzy_finder,
+            None => return EventAction::NoNeedReDraw,
+        };
+
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                self.close_fuzzy_finder();
+            }
+            (KeyCode::Enter, _) => {
+                let selected = fuzzy_finder
+                    .selected_key()
+                    .map(|key| (fuzzy_finder.bucket().to_owned(), key.to_owned()));
+                self.close_fuzzy_finder();
+                if let Some((bucket, key)) = selected {
+                    self.jump_to_key(bucket, key).await;
+                }
+            }
+            (KeyCode::Down, _) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => {
+                fuzzy_finder.next();
+            }
+            (KeyCode::Up, _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                fuzzy_finder.previous();
+            }
+            (KeyCode::Backspace, _) => fuzzy_finder.pop_query(),
+            (KeyCode::Char(c), KeyModifiers::NONE) | (KeyCode::Char(c), KeyModifiers::SHIFT) => {
+                fuzzy_finder.push_query(c);
+            }
+            _ => return EventAction::NoNeedReDraw,
+        }
+        EventAction::NeedReDraw
+    }
+
+    async fn handle_event_in_edit_mode(&mut self, key: KeyEvent) -> EventAction {
         match key.code {
             KeyCode::Backspace => {
-                if self.search_input.len() > 1 {
-                    self.search_input.pop();
+                if self.input_line.len() > 1 {
+                    self.input_line.pop();
                 }
+                self.tab.search = self.input_line[1..].to_owned();
                 self.search_next();
                 EventAction::NeedReDraw
             }
             KeyCode::Char(c) => {
-                self.search_input.push(c);
+                self.input_line.push(c);
+                self.tab.search = self.input_line[1..].to_owned();
                 self.search_next();
                 EventAction::NeedReDraw
             }
@@ -261,23 +819,174 @@ impl Controller {
 
     async fn handle_event(&mut self, event: Event) -> EventAction {
         match event {
-            Event::ClientEvent(s3output) => {
-                self.vm.update(s3output);
+            Event::KeyTimeout => match self.keymap.timeout() {
+                Some(action) => self.perform_action(action).await,
+                None => EventAction::NoNeedReDraw,
+            },
+            Event::ClientEvent(ClientOutput::S3Output {
+                tab_id,
+                request_id,
+                output,
+            }) => {
+                let is_current = tab_id == self.tab.id;
+                // 이미 닫힌 tab 이나 취소한 조회의 결과는 무시한다
+                let tab = match self.tab_mut(tab_id) {
+                    Some(tab) => tab,
+                    None => return EventAction::NoNeedReDraw,
+                };
+                if !tab.accepts(request_id) {
+                    return EventAction::NoNeedReDraw;
+                }
+                if tab.apply_output(output) && is_current {
+                    self.record_visit().await;
+                }
                 EventAction::NeedReDraw
             }
+            Event::ClientEvent(ClientOutput::ObjectsPage {
+                tab_id,
+                request_id,
+                page,
+                pages,
+                done,
+            }) => {
+                let is_current = tab_id == self.tab.id;
+                let moved = match self.tab_mut(tab_id) {
+                    Some(tab) => tab.apply_page(request_id, page, pages, done),
+                    None => None,
+                };
+                match moved {
+                    Some(moved) => {
+                        if moved && is_current {
+                            self.record_visit().await;
+                        }
+                        EventAction::NeedReDraw
+                    }
+                    // 이미 닫힌 tab 이나 취소한 조회의 페이지는 무시한다
+                    None => EventAction::NoNeedReDraw,
+                }
+            }
+            Event::ClientEvent(ClientOutput::RequestFailed {
+                tab_id,
+                request_id,
+                status,
+            }) => {
+                if self
+                    .tab_mut(tab_id)
+                    .is_some_and(|t| t.fail_request(request_id))
+                {
+                    self.set_status(status);
+                    EventAction::NeedReDraw
+                } else {
+                    EventAction::NoNeedReDraw
+                }
+            }
+            Event::ClientEvent(ClientOutput::Refresh {
+                tab_id,
+                bucket,
+                prefix,
+            }) => {
+                let ev_tx = self.ev_tx.clone();
+                match self.tab_mut(tab_id) {
+                    // 진행중인 조회는 바뀌기 전의 목록일 수 있으므로 새 조회로 대신한다
+                    Some(tab)
+                        if tab.vm.bucket_and_prefix() == Some((bucket.clone(), prefix.clone())) =>
+                    {
+                        spawn_object_list(tab, ev_tx, bucket, prefix, None);
+                        EventAction::NeedReDraw
+                    }
+                    // 이미 닫혔거나 다른 곳으로 이동한 tab 은 새로 고치지 않는다
+                    _ => EventAction::NoNeedReDraw,
+                }
+            }
+            Event::ClientEvent(ClientOutput::BucketLocation {
+                tab_id,
+                bucket,
+                location,
+            }) => {
+                let updated = self
+                    .tab_mut(tab_id)
+                    .is_some_and(|t| t.vm.set_bucket_location(&bucket, &location));
+                if updated {
+                    EventAction::NeedReDraw
+                } else {
+                    EventAction::NoNeedReDraw
+                }
+            }
+            Event::ClientEvent(ClientOutput::Completions {
+                input_line,
+                candidates,
+            }) => {
+                // 완성 후보를 구하는 동안 입력이 바뀌었다면 무시한다
+                if self.input_mode != InputMode::Command || input_line != self.input_line {
+                    return EventAction::NoNeedReDraw;
+                }
+                self.apply_completions(candidates);
+                EventAction::NeedReDraw
+            }
+            Event::ClientEvent(ClientOutput::Status(status)) => {
+                self.set_status(status);
+                EventAction::NeedReDraw
+            }
+            Event::ClientEvent(ClientOutput::Keys {
+                walk_id,
+                keys,
+                done,
+            }) => {
+                match (self.fuzzy_walk.as_ref(), self.fuzzy_finder.as_mut()) {
+                    (Some((id, _)), Some(fuzzy_finder)) if *id == walk_id => {
+                        fuzzy_finder.extend_keys(keys, done);
+                        EventAction::NeedReDraw
+                    }
+                    // 이미 닫힌 fuzzy finder 의 결과는 무시한다
+                    _ => EventAction::NoNeedReDraw,
+                }
+            }
+            Event::ClientEvent(ClientOutput::KeysFailed { walk_id, error }) => {
+                match (self.fuzzy_walk.as_ref(), self.fuzzy_finder.as_mut()) {
+                    (Some((id, _)), Some(fuzzy_finder)) if *id == walk_id => {
+                        fuzzy_finder.fail(error.to_string());
+                        // 요청의 자세한 내용은 error log 에서 볼 수 있다
+                        self.set_status(StatusMessage::error(&error));
+                        EventAction::NeedReDraw
+                    }
+                    _ => EventAction::NoNeedReDraw,
+                }
+            }
             Event::KeyEvent(key_event) => match key_event {
+                // 조회중인 tab 이 있다면 spinner 를 돌린다
                 FrontendEvent::Tick => {
-                    self.key_events.clear();
-                    EventAction::NoNeedReDraw
+                    self.save_frecency_in_background();
+                    let mut loading = false;
+                    for tab in std::iter::once(&mut self.tab).chain(self.other_tabs.iter_mut()) {
+                        loading |= tab.vm.tick_loading();
+                    }
+                    if loading {
+                        EventAction::NeedReDraw
+                    } else {
+                        EventAction::NoNeedReDraw
+                    }
                 }
                 FrontendEvent::TerminalEvent(terminal_event) => match terminal_event {
                     TerminalEvent::Key(key) => {
-                        let last_key_event = self.key_events.last().map(|e| e.to_owned());
-                        self.key_events.push(key);
-                        if self.input_mode == InputMode::Normal {
-                            self.handle_event_in_nomal_mode(key, last_key_event).await
-                        } else {
-                            self.handle_event_in_edit_mode(key, last_key_event).await
+                        self.status = None;
+                        match self.input_mode {
+                            InputMode::Normal => self.handle_event_in_nomal_mode(key).await,
+                            InputMode::Search => self.handle_event_in_edit_mode(key).await,
+                            InputMode::Command => self.handle_event_in_command_mode(key).await,
+                            InputMode::FuzzyFinder => {
+                                self.handle_event_in_fuzzy_finder_mode(key).await
+                            }
+                            InputMode::BookmarkList => {
+                                self.handle_event_in_bookmark_list_mode(key).await
+                            }
+                            InputMode::FrecencyJump => {
+                                self.handle_event_in_frecency_jump_mode(key).await
+                            }
+                            InputMode::ProfileList => {
+                                self.handle_event_in_profile_list_mode(key).await
+                            }
+                            InputMode::MfaPrompt => self.handle_event_in_mfa_prompt_mode(key).await,
+                            InputMode::ErrorLog => self.handle_event_in_error_log_mode(key).await,
                         }
                     }
                     TerminalEvent::Resize(_, _) => EventAction::NeedReDraw,
@@ -288,66 +997,269 @@ impl Controller {
     }
 
     fn search_next(&mut self) {
-        if self.search_input.starts_with("/") {
-            self.vm.search_next(&self.search_input[1..]);
+        if !self.tab.search.is_empty() {
+            self.tab.vm.search_next(&self.tab.search);
+        }
+    }
+
+    // 현재 위치의 바로 아래라면 list_stack 에 쌓고, 아니라면 list_stack 을 새로 만들어
+    // .. 이 항상 상위 prefix 를 가리키도록 한다
+    async fn go_to_location(&mut self, bucket: String, prefix: String) {
+        let target = Some((bucket.clone(), prefix.clone()));
+        let current = self.tab.vm.bucket_and_prefix();
+        let is_child = match current.as_ref() {
+            Some((current_bucket, current_prefix)) => {
+                *current_bucket == bucket
+                    && !prefix.is_empty()
+                    && path::parent_prefix(prefix.trim_end_matches('/')) == *current_prefix
+            }
+            None => prefix.is_empty(),
+        };
+
+        self.tab.pending_replace = if is_child || current == target {
+            None
+        } else {
+            Some(target)
+        };
+        self.request_object_list(bucket, prefix).await;
+    }
+
+    async fn go_to_bucket_list(&mut self) {
+        self.tab.pending_replace = Some(None);
+        self.request_bucket_list().await;
+    }
+
+    async fn go_to_history_entry(&mut self, entry: HistoryEntry) {
+        let location = entry.location.clone();
+        self.tab.pending_history = Some(entry);
+        match location {
+            Some((bucket, prefix)) => self.go_to_location(bucket, prefix).await,
+            None => self.go_to_bucket_list().await,
         }
     }
 
-    async fn request_bucket_list(&self) {
-        let client_copy = self.client.clone();
+    async fn go_back(&mut self) {
+        if let Some(entry) = self
+            .tab
+            .vm
+            .history_entry()
+            .and_then(|current| self.tab.history.back(current))
+        {
+            self.go_to_history_entry(entry).await;
+        }
+    }
+
+    async fn go_forward(&mut self) {
+        if let Some(entry) = self
+            .tab
+            .vm
+            .history_entry()
+            .and_then(|current| self.tab.history.forward(current))
+        {
+            self.go_to_history_entry(entry).await;
+        }
+    }
+
+    fn open_fuzzy_finder(&mut self) {
+        let (bucket, prefix) = match self.tab.vm.bucket_and_prefix() {
+            Some(bucket_and_prefix) => bucket_and_prefix,
+            // bucket 목록에서는 검색할 key 가 없다
+            None => return,
+        };
+
+        let walk_id = self.fuzzy_walk.as_ref().map(|(id, _)| id + 1).unwrap_or(0);
+        let backend_copy = self.tab.backend.clone();
         let ev_tx_copy = self.ev_tx.clone();
-        tokio::spawn(async move {
-            if let Ok(output) = client_copy.lock().await.list_buckets().await {
-                ev_tx_copy
-                    .send(S3Output::Buckets(output))
+        let (walk_bucket, walk_prefix) = (bucket.clone(), prefix.clone());
+
+        // 조회한 페이지마다 결과를 보낸다
+        let walk = tokio::spawn(async move {
+            let mut continuation_token = None;
+            loop {
+                let page = backend_copy
+                    .list_keys_page(&walk_bucket, &walk_prefix, continuation_token)
+                    .await;
+                let (keys, next_continuation_token) = match page {
+                    Ok(page) => page,
+                    Err(error) => {
+                        let _ = ev_tx_copy
+                            .send(ClientOutput::KeysFailed { walk_id, error })
+                            .await;
+                        break;
+                    }
+                };
+                let done = next_continuation_token.is_none();
+                if ev_tx_copy
+                    .send(ClientOutput::Keys {
+                        walk_id,
+                        keys,
+                        done,
+                    })
                     .await
-                    .expect("ev_tx_copy send error");
-            } else {
-                // TODO: error 처리
+                    .is_err()
+                    || done
+                {
+                    break;
+                }
+                continuation_token = next_continuation_token;
             }
         });
+
+        self.close_fuzzy_finder();
+        self.fuzzy_walk = Some((walk_id, walk));
+        self.fuzzy_finder = Some(FuzzyFinderViewModel::new(bucket, prefix));
+        self.input_mode = InputMode::FuzzyFinder;
     }
 
-    async fn request_object_list(&self, bucket: String, prefix: String) {
-        let client_copy = self.client.clone();
-        let ev_tx_copy = self.ev_tx.clone();
+    fn close_fuzzy_finder(&mut self) {
+        if let Some((_, walk)) = self.fuzzy_walk.as_ref() {
+            walk.abort();
+        }
+        self.fuzzy_finder = None;
+        self.input_mode = InputMode::Normal;
+    }
 
-        tokio::spawn(async move {
-            if let Ok(output) = client_copy
-                .lock()
-                .await
-                .list_objects(&bucket, &prefix)
-                .await
-            {
-                ev_tx_copy
-                    .send(S3Output::Objects(output))
-                    .await
-                    .expect("ev_tx_copy send error");
-            } else {
-                // TODO: error 처리
+    // key 의 상위 prefix 로 이동한 뒤 key 를 선택한다
+    async fn jump_to_key(&mut self, bucket: String, key: String) {
+        let prefix = path::parent_prefix(&key);
+        self.tab.pending_selection = Some((bucket.clone(), key));
+        self.go_to_location(bucket, prefix).await;
+    }
+
+    async fn request_bucket_list(&mut self) {
+        if !self.reconnect(None).await {
+            return;
+        }
+        let backend_copy = self.tab.backend.clone();
+        let ev_tx_copy = self.ev_tx.clone();
+        let tab_id = self.tab.id;
+        let pinned_buckets = self.tab.pinned_buckets.clone();
+        self.tab.spawn_request(0, move |request_id| async move {
+            let (buckets, error) = pinned::list_buckets(&*backend_copy, &pinned_buckets).await;
+            // credential 이 없거나 만료된 경우에도 이유를 보여준다
+            if let Some(e) = error.as_ref().filter(|_| pinned_buckets.is_empty()) {
+                let event = ClientOutput::RequestFailed {
+                    tab_id,
+                    request_id,
+                    status: StatusMessage::error(e),
+                };
+                ev_tx_copy.send(event).await.expect("ev_tx_copy send error");
+                return;
+            }
+            // 조회하지 못했더라도 고정한 bucket 은 보여준다
+            let resolving = resolving_buckets(&buckets);
+            let event = ClientOutput::S3Output {
+                tab_id,
+                request_id,
+                output: S3Output::Buckets(buckets),
+            };
+            ev_tx_copy.send(event).await.expect("ev_tx_copy send error");
+            if let Some(e) = error {
+                let event = ClientOutput::Status(StatusMessage::error(&e));
+                ev_tx_copy.send(event).await.expect("ev_tx_copy send error");
             }
+            // 목록을 보여준 뒤에도 location 조회는 계속한다
+            tokio::spawn(resolve_locations(
+                backend_copy,
+                tab_id,
+                resolving,
+                ev_tx_copy,
+            ));
         });
     }
 
+    // 처음 연 tab 이 첫 페이지만 조회했다면 나머지 페이지를 조회한다
+    fn continue_tab_listings(&mut self) {
+        let ev_tx = self.ev_tx.clone();
+        for tab in std::iter::once(&mut self.tab).chain(self.other_tabs.iter_mut()) {
+            if let Some((bucket, prefix, continuation_token)) = tab.next_page.take() {
+                spawn_object_list(tab, ev_tx.clone(), bucket, prefix, Some(continuation_token));
+            }
+        }
+    }
+
+    // 처음 연 tab 의 bucket 목록에서 location 을 조회한다
+    fn resolve_tab_locations(&self) {
+        for tab in std::iter::once(&self.tab).chain(self.other_tabs.iter()) {
+            let buckets = tab.vm.resolving_buckets();
+            if !buckets.is_empty() {
+                tokio::spawn(resolve_locations(
+                    tab.backend.clone(),
+                    tab.id,
+                    buckets,
+                    self.ev_tx.clone(),
+                ));
+            }
+        }
+    }
+
+    // 연결하지 못한 tab 이라면 다시 연결한다. 연결하지 못했다면 이유를 보여주고 false.
+    // mfa code 가 필요하다면 code 를 받은 뒤 location 으로 이동한다
+    async fn reconnect(&mut self, location: Option<String>) -> bool {
+        let config = match self.tab.backend.connection() {
+            Some(config) if !self.tab.backend.is_connected() => config.clone(),
+            _ => return true,
+        };
+        match self.replace_client(config).await {
+            Ok(()) => true,
+            Err(e) => {
+                match e.downcast::<MfaRequired>() {
+                    Ok(required) => self.open_mfa_prompt(required, self.tab.id, location),
+                    Err(e) => self.set_status(StatusMessage::error(&e)),
+                }
+                false
+            }
+        }
+    }
+
+    async fn request_object_list(&mut self, bucket: String, prefix: String) {
+        let location = Scheme::S3.uri(&bucket, &prefix);
+        if !self.reconnect(Some(location)).await {
+            return;
+        }
+        spawn_object_list(&mut self.tab, self.ev_tx.clone(), bucket, prefix, None);
+    }
+
     async fn refresh(&mut self) {
-        if let Some((bucket, prefix)) = self.vm.bucket_and_prefix() {
-            self.request_object_list(bucket, prefix).await;
+        match self.tab.vm.bucket_and_prefix() {
+            Some((bucket, prefix)) => self.request_object_list(bucket, prefix).await,
+            // 연결하지 못한 tab 은 bucket 목록을 다시 조회한다
+            None if !self.tab.backend.is_connected() => self.go_to_bucket_list().await,
+            None => {}
         }
     }
 
     async fn enter(&mut self) {
-        let item = self.vm.selected();
+        let item = self.tab.vm.selected();
 
+        if let Some(S3Item::Connection(connection)) = item {
+            if let Err(e) = self.open_connection(connection.clone()).await {
+                self.report_error(e);
+            }
+            return;
+        }
         if let Some(s3_item_type) = item.as_ref().map(|i| i.get_type()) {
+            // bucket 목록의 .. 은 연결 목록이다
+            if s3_item_type == S3ItemType::Pop && self.tab.vm.bucket_and_prefix().is_none() {
+                self.show_connections();
+                return;
+            }
             if s3_item_type == S3ItemType::Pop {
-                if let Some(i) = self.vm.pop() {
-                    if self.vm.list_stack.len() == 0 {
+                // 조회중이던 하위 목록이 나중에 도착해 쌓이지 않도록 한다
+                self.tab.cancel_request();
+                if let Some(before) = self.tab.vm.history_entry() {
+                    self.tab.history.record(before);
+                }
+                if let Some(i) = self.tab.vm.pop() {
+                    if !self.tab.vm.list_stack.is_empty() {
+                        self.record_visit().await;
+                    } else {
                         if let Some((bucket, prefix)) = i.output().bucket_and_prefix() {
                             if prefix.is_empty() {
                                 self.request_bucket_list().await;
                                 return;
                             }
-                            let mut components: Vec<_> = prefix.split("/").collect();
+                            let mut components: Vec<_> = prefix.split('/').collect();
                             components.pop();
                             components.pop();
                             let mut prefix = components.join("/");
@@ -372,7 +1284,7 @@ impl Controller {
                 "".to_owned(),
             )),
             Some(S3Item::CommonPrefix(d)) => Some((
-                self.vm.bucket_and_prefix().map(|b| b.0).unwrap(),
+                self.tab.vm.bucket_and_prefix().map(|b| b.0).unwrap(),
                 d.prefix().map(|d| d.to_owned()).unwrap(),
             )),
             _ => None,
@@ -384,39 +1296,230 @@ impl Controller {
     }
 }
 
+impl Controller {
+    fn make_completions_view(&self) -> (List<'static>, ListState) {
+        let items: Vec<_> = self
+            .completions
+            .iter()
+            .map(|c| ListItem::new(c.clone()))
+            .collect();
+        let mut state = ListState::default();
+        state.select(self.completion_index);
+
+        let list = List::new(items)
+            .block(Block::default().title("completions").borders(Borders::ALL))
+            .highlight_style(self.theme.highlight);
+        (list, state)
+    }
+}
+
 #[async_trait]
 impl App for Controller {
-    fn draw(&mut self, terminal: &mut CrosstermTerminal) -> Result<()> {
-        let widget_and_state = self.vm.make_item_list_view();
+    fn draw<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        let widget_and_state = self.tab.vm.make_item_list_view(&self.theme);
+        // commander 라면 다른 pane 의 (위치, 목록)
+        let mut other_pane = self.commander.and_then(|id| {
+            let index = self.tab_position(id)?;
+            let other = self.other_tabs.iter().find(|t| t.id == id)?;
+            let (list, state) = other.vm.make_item_list_view(&self.theme)?;
+            Some((
+                id,
+                index,
+                list.highlight_style(self.theme.inactive_highlight),
+                state,
+            ))
+        });
         if let Some((s3_items_view, mut state)) = widget_and_state {
             terminal.draw(|f| {
-                let rect = f.size();
+                let mut rect = f.size();
+                // tab 이 여러 개라면 맨 위에 tab bar 를 보여준다
+                if !self.other_tabs.is_empty() && rect.height > 0 {
+                    let titles = self.tab_titles().into_iter().map(Spans::from).collect();
+                    let tab_bar = Tabs::new(titles)
+                        .select(self.tab_index)
+                        .style(self.theme.header)
+                        .highlight_style(self.theme.highlight);
+                    f.render_widget(tab_bar, Rect::new(rect.x, rect.y, rect.width, 1));
+                    rect = Rect::new(rect.x, rect.y + 1, rect.width, rect.height - 1);
+                }
                 let chunks = Layout::default()
                     .direction(Direction::Vertical)
                     .constraints(
                         [
                             Constraint::Length(1),
-                            Constraint::Length(rect.height - 3),
+                            // 아주 작은 터미널에서도 빼기가 넘치지 않도록 한다
+                            Constraint::Length(rect.height.saturating_sub(3)),
                             Constraint::Min(1),
                             Constraint::Min(1),
                         ]
                         .as_ref(),
                     )
-                    .split(f.size());
+                    .split(rect);
 
-                f.render_widget(self.vm.make_currenent_common_prefix_view(), chunks[0]);
-                f.render_stateful_widget(s3_items_view, chunks[1], &mut state);
-                f.render_widget(self.vm.make_selected_s3_item_view(), chunks[2]);
+                // commander 라면 tab 순서대로 좌우에 나눠 보여준다
+                let mut panes = [chunks[0], chunks[1], chunks[2]];
+                if let Some((id, index, other_list, other_state)) = other_pane.as_mut() {
+                    let other = self.other_tabs.iter().find(|t| t.id == *id);
+                    for (i, area) in panes.iter_mut().enumerate() {
+                        let halves = Layout::default()
+                            .direction(Direction::Horizontal)
+                            .constraints(
+                                [Constraint::Percentage(50), Constraint::Percentage(50)].as_ref(),
+                            )
+                            .split(*area);
+                        let (current, other_area) = if self.tab_index < *index {
+                            (halves[0], halves[1])
+                        } else {
+                            (halves[1], halves[0])
+                        };
+                        *area = current;
+                        match (i, other) {
+                            (0, Some(other)) => f.render_widget(
+                                other.vm.make_currenent_common_prefix_view(&self.theme),
+                                other_area,
+                            ),
+                            (1, _) => f.render_stateful_widget(
+                                other_list.clone(),
+                                other_area,
+                                other_state,
+                            ),
+                            (2, Some(other)) => f.render_widget(
+                                other.vm.make_selected_s3_item_view(&self.theme),
+                                other_area,
+                            ),
+                            _ => {}
+                        }
+                    }
+                }
+
+                f.render_widget(
+                    self.tab.vm.make_currenent_common_prefix_view(&self.theme),
+                    panes[0],
+                );
+                f.render_stateful_widget(s3_items_view, panes[1], &mut state);
+                f.render_widget(
+                    self.tab.vm.make_selected_s3_item_view(&self.theme),
+                    panes[2],
+                );
 
-                // search input view
-                let search_input_view =
-                    Text::from(Span::styled(&self.search_input, Style::default()));
+                // search input view, 입력중이 아니라면 status 를 보여준다
+                let search_input_view = match (&self.input_mode, &self.status) {
+                    (InputMode::Normal, Some(StatusMessage::Info(message))) => {
+                        Text::from(Span::styled(message, self.theme.info))
+                    }
+                    (InputMode::Normal, Some(StatusMessage::Error(entry))) => {
+                        Text::from(Span::styled(&entry.message, self.theme.error))
+                    }
+                    _ => Text::from(Span::styled(&self.input_line, Style::default())),
+                };
 
                 let paragraph = Paragraph::new(search_input_view).style(Style::default());
                 f.render_widget(paragraph, chunks[3]);
+
+                if !self.completions.is_empty() {
+                    let (completions_view, mut completions_state) = self.make_completions_view();
+                    let height = (self.completions.len() as u16 + 2).min(chunks[1].height);
+                    let area = Rect::new(
+                        chunks[1].x,
+                        chunks[1].y + chunks[1].height - height,
+                        chunks[1].width,
+                        height,
+                    );
+                    f.render_widget(Clear, area);
+                    f.render_stateful_widget(completions_view, area, &mut completions_state);
+                }
+
+                if let Some(fuzzy_finder) = self.fuzzy_finder.as_mut() {
+                    let area = popup_rect(rect, 80, 70);
+                    let popup_chunks = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+                        .split(area);
+                    let (matched_list_view, mut matched_state) =
+                        fuzzy_finder.make_matched_list_view(&self.theme);
+
+                    f.render_widget(Clear, area);
+                    f.render_widget(fuzzy_finder.make_query_view(&self.theme), popup_chunks[0]);
+                    f.render_stateful_widget(
+                        matched_list_view,
+                        popup_chunks[1],
+                        &mut matched_state,
+                    );
+                    fuzzy_finder.reset_state(matched_state);
+                }
+
+                if let Some(frecency_jump) = self.frecency_jump.as_mut() {
+                    let area = popup_rect(rect, 80, 70);
+                    let popup_chunks = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+                        .split(area);
+                    let (matched_list_view, mut matched_state) =
+                        frecency_jump.make_matched_list_view(&self.theme);
+
+                    f.render_widget(Clear, area);
+                    f.render_widget(frecency_jump.make_query_view(&self.theme), popup_chunks[0]);
+                    f.render_stateful_widget(
+                        matched_list_view,
+                        popup_chunks[1],
+                        &mut matched_state,
+                    );
+                    frecency_jump.reset_state(matched_state);
+                }
+
+                if let Some(bookmark_list) = self.bookmark_list.as_mut() {
+                    let area = popup_rect(rect, 80, 50);
+                    let (bookmark_list_view, mut bookmark_list_state) =
+                        bookmark_list.make_list_view(&self.theme);
+                    f.render_widget(Clear, area);
+                    f.render_stateful_widget(bookmark_list_view, area, &mut bookmark_list_state);
+                    bookmark_list.reset_state(bookmark_list_state);
+                }
+
+                if let Some(profile_list) = self.profile_list.as_mut() {
+                    let area = popup_rect(rect, 80, 50);
+                    let (profile_list_view, mut profile_list_state) =
+                        profile_list.make_list_view(&self.theme);
+                    f.render_widget(Clear, area);
+                    f.render_stateful_widget(profile_list_view, area, &mut profile_list_state);
+                    profile_list.reset_state(profile_list_state);
+                }
+
+                if let Some(error_log_view) = self.error_log_view.as_mut() {
+                    let area = popup_rect(rect, 80, 70);
+                    let popup_chunks = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Percentage(50), Constraint::Min(3)].as_ref())
+                        .split(area);
+                    let (error_list_view, mut error_list_state) =
+                        error_log_view.make_list_view(&self.theme);
+                    f.render_widget(Clear, area);
+                    f.render_stateful_widget(
+                        error_list_view,
+                        popup_chunks[0],
+                        &mut error_list_state,
+                    );
+                    error_log_view.reset_state(error_list_state);
+                    f.render_widget(
+                        error_log_view.make_details_view(&self.theme),
+                        popup_chunks[1],
+                    );
+                }
+
+                if let Some(mfa_prompt) = self.mfa_prompt.as_ref() {
+                    let area = popup_rect(rect, 80, 50);
+                    let area = Rect::new(area.x, area.y, area.width, area.height.min(3));
+                    f.render_widget(Clear, area);
+                    f.render_widget(mfa_prompt.make_view(&self.theme), area);
+                }
             })?;
 
-            self.vm.reset_state(state);
+            self.tab.vm.reset_state(state);
+            if let Some((id, _, _, other_state)) = other_pane {
+                if let Some(other) = self.other_tabs.iter_mut().find(|t| t.id == id) {
+                    other.vm.reset_state(other_state);
+                }
+            }
         }
         Ok(())
     }
@@ -425,10 +1528,14 @@ impl App for Controller {
         &mut self,
         frontenv_event_rx: &mut Receiver<FrontendEvent>,
     ) -> EventAction {
+        let deadline = self.keymap.deadline();
         let event = tokio::select! {
             // Key Code 이벤트 처리
             Some(frontend_event) = frontenv_event_rx.recv() => Event::KeyEvent(frontend_event),
-            Some(s3_output) = self.ev_rx.recv() => Event::ClientEvent(s3_output)
+            Some(client_output) = self.ev_rx.recv() => Event::ClientEvent(client_output),
+            _ = tokio::time::sleep_until(
+                deadline.map(tokio::time::Instant::from_std).unwrap_or_else(tokio::time::Instant::now)
+            ), if deadline.is_some() => Event::KeyTimeout
         };
 
         self.handle_event(event).await
@@ -0,0 +1,103 @@
+use aws_sdk_s3::model::{Bucket, BucketLocationConstraint};
+use eyre::Result;
+
+use super::*;
+
+// 목록에 없는 고정한 bucket 의 location. region 은 처음 요청할 때 찾는다
+const PINNED: &str = "pinned";
+
+// 모든 연결에 고정한 bucket 과 그 연결에만 고정한 bucket
+pub(super) fn pinned_buckets(pinned: &[String], connection: Option<&Connection>) -> Vec<String> {
+    let mut buckets = pinned.to_vec();
+    for bucket in connection.into_iter().flat_map(|c| c.pinned_buckets.iter()) {
+        if !buckets.contains(bucket) {
+            buckets.push(bucket.clone());
+        }
+    }
+    buckets
+}
+
+// 조회한 bucket 목록에 없는 고정한 bucket 을 더한다
+fn merge_pinned(
+    mut buckets: Vec<BucketWithLocation>,
+    pinned: &[String],
+) -> Vec<BucketWithLocation> {
+    for name in pinned {
+        if !buckets
+            .iter()
+            .any(|b| b.bucket.name() == Some(name.as_str()))
+        {
+            buckets.push(BucketWithLocation {
+                location: BucketLocationConstraint::from(PINNED),
+                bucket: Bucket::builder().name(name).build(),
+            });
+        }
+    }
+    buckets
+}
+
+// bucket 목록에 고정한 bucket 을 더한다. ListBuckets 권한이 없어 목록을 조회하지 못했다면
+// 고정한 bucket 만 보여주고 이유를 함께 돌려준다
+pub(super) async fn list_buckets(
+    backend: &dyn StorageBackend,
+    pinned: &[String],
+) -> (Vec<BucketWithLocation>, Option<eyre::Report>) {
+    match backend.list_buckets().await {
+        Ok(buckets) => (merge_pinned(buckets, pinned), None),
+        Err(e) => (merge_pinned(vec![], pinned), Some(e)),
+    }
+}
+
+impl Controller {
+    // 이름으로 bucket 을 연다. ListBuckets 로 보이지 않는 bucket 도 권한이 있다면 열 수 있다
+    pub(super) fn open_bucket_prompt(&mut self) {
+        self.open_command_line();
+        self.input_line = ":open ".to_owned();
+    }
+
+    pub(super) async fn open_bucket(&mut self, bucket: &str) -> Result<()> {
+        let bucket = bucket
+            .trim_start_matches("s3://")
+            .trim_end_matches('/')
+            .to_owned();
+        if bucket.is_empty() || bucket.contains('/') {
+            return Err(eyre::eyre!("invalid bucket name: {}", bucket));
+        }
+        if self.tab.backend.scheme() != Scheme::S3 {
+            self.replace_client(self.default_connection.clone()).await?;
+        }
+        self.go_to_location(bucket, String::default()).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::s3::backend::memory::MemoryBackend;
+
+    #[tokio::test]
+    async fn test_pinned_buckets() {
+        let backend = MemoryBackend::new();
+        backend.insert("logs", "readme.txt", "readme");
+        let pinned = vec!["logs".to_owned(), "partner-exports".to_owned()];
+
+        let (buckets, error) = list_buckets(&backend, &pinned).await;
+        let names: Vec<_> = buckets.iter().filter_map(|b| b.bucket.name()).collect();
+        assert_eq!(names, vec!["logs", "partner-exports"]);
+        assert_eq!(buckets[0].location.as_str(), "memory");
+        assert_eq!(buckets[1].location.as_str(), PINNED);
+        assert!(error.is_none());
+
+        let connection = Connection {
+            name: "partner".to_owned(),
+            config: S3ClientConfig::default(),
+            color: None,
+            pinned_buckets: vec!["partner-exports".to_owned(), "partner-logs".to_owned()],
+        };
+        assert_eq!(
+            pinned_buckets(&pinned, Some(&connection)),
+            vec!["logs", "partner-exports", "partner-logs"]
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use eyre::Result;
+
+use super::*;
+use crate::s3::profile;
+
+impl Controller {
+    // s3 가 아닌 tab 에서는 기본 연결 설정의 profile 을 현재 profile 로 본다
+    pub(super) fn connection_or_default(&self) -> S3ClientConfig {
+        self.tab
+            .backend
+            .connection()
+            .cloned()
+            .unwrap_or_else(|| self.default_connection.clone())
+    }
+
+    pub(super) async fn open_profile_list(&mut self) -> Result<()> {
+        let profiles = profile::load().await;
+        if profiles.is_empty() {
+            return Err(eyre::eyre!(
+                "no profiles in ~/.aws/config or ~/.aws/credentials"
+            ));
+        }
+        let active = self.connection_or_default().profile_name();
+        self.profile_list = Some(ProfileListViewModel::new(profiles, active));
+        self.input_mode = InputMode::ProfileList;
+        Ok(())
+    }
+
+    fn close_profile_list(&mut self) {
+        self.profile_list = None;
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub(super) async fn handle_event_in_profile_list_mode(&mut self, key: KeyEvent) -> EventAction {
+        let profile_list = match self.profile_list.as_mut() {
+            Some(profile_list) => profile_list,
+            None => return EventAction::NoNeedReDraw,
+        };
+
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _)
+            | (KeyCode::Char('q'), _)
+            | (KeyCode::Char('c'), KeyModifiers::CONTROL) => self.close_profile_list(),
+            (KeyCode::Down, _) | (KeyCode::Char('j'), _) => profile_list.next(),
+            (KeyCode::Up, _) | (KeyCode::Char('k'), _) => profile_list.previous(),
+            (KeyCode::Enter, _) => {
+                let selected = profile_list.selected().map(|p| p.name.clone());
+                self.close_profile_list();
+                if let Some(name) = selected {
+                    if let Err(e) = self.switch_profile(name).await {
+                        self.report_error(e);
+                    }
+                }
+            }
+            _ => return EventAction::NoNeedReDraw,
+        }
+        EventAction::NeedReDraw
+    }
+
+    // 현재 tab 의 client 를 profile 로 다시 만들고 bucket 목록으로 돌아간다. region 은
+    // profile 의 region 을 따른다
+    pub(super) async fn switch_profile(&mut self, profile: String) -> Result<()> {
+        let config = S3ClientConfig {
+            profile: Some(profile),
+            region: None,
+            assume_role: None,
+            no_sign_request: false,
+            ..self.connection_or_default()
+        };
+        self.replace_client(config).await?;
+        self.go_to_bucket_list().await;
+        Ok(())
+    }
+}
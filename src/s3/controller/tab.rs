@@ -0,0 +1,400 @@
+use eyre::Result;
+use std::future::Future;
+
+use super::*;
+
+// 각 tab 이 따로 가지는 위치, 검색, history 와 저장소
+pub(super) struct Tab {
+    pub(super) id: usize,
+    pub(super) vm: S3ItemsViewModel,
+    pub(super) backend: Arc<dyn StorageBackend>,
+    // 마지막으로 검색한 문자열
+    pub(super) search: String,
+    pub(super) history: NavigationHistory,
+    // 조회가 끝나면 선택할 (bucket, key)
+    pub(super) pending_selection: Option<(String, String)>,
+    // 조회가 끝나면 list_stack 을 새로 만들 위치. Some(None) 은 bucket 목록이다
+    pub(super) pending_replace: Option<Option<(String, String)>>,
+    // back/forward 로 이동중인 history. 조회가 끝나면 선택 상태를 되돌린다
+    pub(super) pending_history: Option<HistoryEntry>,
+    // bucket 목록에 더할 bucket. s3 tab 에만 있다
+    pub(super) pinned_buckets: Vec<String>,
+    // 열 때 bucket 목록을 조회하지 못한 이유. status 로 보여준 뒤 비운다
+    pub(super) open_error: Option<String>,
+    // 진행중인 목록 조회와 그 id. 다른 id 의 결과는 이미 떠난 위치의 것이므로 버린다
+    request: Option<(usize, JoinHandle<()>)>,
+    last_request_id: usize,
+    // 열 때 첫 페이지만 조회한 목록의 (bucket, prefix, continuation token)
+    pub(super) next_page: Option<(String, String, String)>,
+}
+
+impl Tab {
+    pub(super) fn new(id: usize, backend: Arc<dyn StorageBackend>) -> Self {
+        let mut tab = Self {
+            id,
+            vm: S3ItemsViewModel::new(),
+            backend: backend.clone(),
+            search: String::default(),
+            history: NavigationHistory::default(),
+            pending_selection: None,
+            pending_replace: None,
+            pending_history: None,
+            pinned_buckets: vec![],
+            open_error: None,
+            request: None,
+            last_request_id: 0,
+            next_page: None,
+        };
+        tab.set_backend(backend);
+        tab
+    }
+
+    // s3_path 를 조회한 tab 을 만든다. s3_path 가 없다면 bucket 목록을 조회한다.
+    // file:// 혹은 / 로 시작하는 경로는 로컬 디스크를 조회한다
+    pub(super) async fn open(
+        id: usize,
+        config: S3ClientConfig,
+        s3_path: Option<&str>,
+        pinned_buckets: Vec<String>,
+    ) -> Result<Self> {
+        let (backend, location): (Arc<dyn StorageBackend>, _) =
+            match s3_path.and_then(local::parse_location) {
+                Some(location) => (Arc::new(LocalBackend), Some(location)),
+                None => (
+                    Arc::new(S3Client::new(config).await?),
+                    s3_path.map(path::parse_s3_location).transpose()?,
+                ),
+            };
+        Self::open_backend(id, backend, location, pinned_buckets).await
+    }
+
+    // (bucket, key) 를 조회한 tab 을 만든다. 목록은 첫 페이지만 조회한다.
+    // 위치가 없다면 bucket 목록을 조회하고, 조회하지 못했다면 고정한 bucket 만 보여준다
+    pub(super) async fn open_backend(
+        id: usize,
+        backend: Arc<dyn StorageBackend>,
+        location: Option<(String, String)>,
+        pinned_buckets: Vec<String>,
+    ) -> Result<Self> {
+        let (mut open_error, mut next_page) = (None, None);
+        let output = match location.as_ref() {
+            Some((bucket, key)) => {
+                let prefix = path::parent_prefix(key);
+                let (page, continuation_token) =
+                    backend.list_objects_page(bucket, &prefix, None).await?;
+                next_page = continuation_token.map(|token| (bucket.clone(), prefix, token));
+                S3Output::Objects(Box::new(page))
+            }
+            None => {
+                let (buckets, error) = pinned::list_buckets(&*backend, &pinned_buckets).await;
+                open_error = error.map(|e| e.to_string());
+                S3Output::Buckets(buckets)
+            }
+        };
+
+        let mut tab = Tab::new(id, backend);
+        tab.pinned_buckets = pinned_buckets;
+        tab.open_error = open_error;
+        tab.next_page = next_page;
+        tab.vm.push(output);
+        // object 를 가리키는 경로라면 그 object 를 선택한다. 다음 페이지에 있다면 받은 뒤에 선택한다
+        if let Some((bucket, key)) = location {
+            if !tab.vm.select_key(&key) {
+                tab.pending_selection = Some((bucket, key));
+            }
+        }
+        Ok(tab)
+    }
+
+    // 열지 못한 경로의 빈 tab. s3 라면 연결 설정을 가지고 있다가 이동할 때 다시 연결한다
+    pub(super) fn unconnected(
+        id: usize,
+        config: S3ClientConfig,
+        s3_path: Option<&str>,
+        reason: String,
+    ) -> Self {
+        let (backend, location): (Arc<dyn StorageBackend>, _) =
+            match s3_path.and_then(local::parse_location) {
+                Some(location) => (Arc::new(LocalBackend), Some(location)),
+                None => (
+                    Arc::new(UnconnectedBackend::new(config, reason)),
+                    s3_path.and_then(|s3_path| path::parse_s3_location(s3_path).ok()),
+                ),
+            };
+        let mut tab = Tab::new(id, backend);
+        // 새로 고치면 열려던 위치를 다시 조회한다
+        tab.vm.push(match location {
+            Some((bucket, key)) => S3Output::Objects(Box::new(
+                ListObjectsV2Output::builder()
+                    .name(bucket)
+                    .prefix(path::parent_prefix(&key))
+                    .build(),
+            )),
+            None => S3Output::Buckets(vec![]),
+        });
+        tab
+    }
+
+    // 저장소가 바뀌면 uri 의 scheme 과 header 의 profile 도 바뀐다
+    pub(super) fn set_backend(&mut self, backend: Arc<dyn StorageBackend>) {
+        self.vm.set_scheme(backend.scheme());
+        self.vm
+            .set_profile(backend.connection().map(|c| c.profile_name()));
+        self.vm.set_credentials_source(backend.credentials_source());
+        self.vm.set_tls_unverified(!backend.verifies_tls());
+        self.vm.set_connection(None);
+        self.backend = backend;
+    }
+
+    pub(super) fn title(&self) -> String {
+        match self.vm.bucket_and_prefix() {
+            Some((bucket, prefix)) => self.vm.scheme().path(&bucket, &prefix),
+            None if self.vm.showing_connections() => "connections".to_owned(),
+            None => "buckets".to_owned(),
+        }
+    }
+
+    // 진행중인 조회를 취소하고 새 id 로 조회를 시작한다. pages 는 이미 받아 보여주고 있는
+    // 페이지의 수다
+    pub(super) fn spawn_request<F>(&mut self, pages: usize, request: impl FnOnce(usize) -> F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.abort_request();
+        self.last_request_id += 1;
+        let handle = tokio::spawn(request(self.last_request_id));
+        self.request = Some((self.last_request_id, handle));
+        self.vm.set_loading(Some(pages));
+    }
+
+    fn abort_request(&mut self) {
+        if let Some((_, handle)) = self.request.take() {
+            handle.abort();
+        }
+        self.vm.set_loading(None);
+    }
+
+    // 진행중인 조회를 취소하고 조회가 끝나면 하려던 일도 잊는다. 취소할 조회가 없다면 false
+    pub(super) fn cancel_request(&mut self) -> bool {
+        if self.request.is_none() {
+            return false;
+        }
+        self.abort_request();
+        self.pending_selection = None;
+        self.pending_replace = None;
+        self.pending_history = None;
+        true
+    }
+
+    fn is_current_request(&self, request_id: usize) -> bool {
+        self.request.as_ref().map(|(id, _)| *id) == Some(request_id)
+    }
+
+    // 조회한 pages 번째 페이지를 반영한다. 첫 페이지는 목록을 바꾸고 다음 페이지는 그 목록에 더한다.
+    // 지난 조회의 페이지라면 None, 아니라면 위치가 바뀌었는지 돌려준다
+    pub(super) fn apply_page(
+        &mut self,
+        request_id: usize,
+        page: Box<ListObjectsV2Output>,
+        pages: usize,
+        done: bool,
+    ) -> Option<bool> {
+        if !self.is_current_request(request_id) {
+            return None;
+        }
+        if done {
+            self.request = None;
+            self.vm.set_loading(None);
+        } else {
+            self.vm.set_loading(Some(pages));
+        }
+        if pages == 1 {
+            return Some(self.apply_output(S3Output::Objects(page)));
+        }
+        self.vm.append_page(*page);
+        self.select_pending_key();
+        Some(false)
+    }
+
+    // 실패한 조회가 진행중인 조회라면 취소한 것처럼 다룬다. 지난 조회의 실패라면 false
+    pub(super) fn fail_request(&mut self, request_id: usize) -> bool {
+        self.is_current_request(request_id) && self.cancel_request()
+    }
+
+    // 진행중인 조회의 결과만 받는다
+    pub(super) fn accepts(&mut self, request_id: usize) -> bool {
+        if !self.is_current_request(request_id) {
+            return false;
+        }
+        self.request = None;
+        self.vm.set_loading(None);
+        true
+    }
+
+    // 조회 결과를 반영하고 위치가 바뀌었는지 돌려준다
+    pub(super) fn apply_output(&mut self, s3_output: S3Output) -> bool {
+        let before = self.vm.history_entry();
+        let moved = before.as_ref().map(|e| &e.location) != Some(&s3_output.bucket_and_prefix());
+        self.replace_pending_location(&s3_output);
+        // 첫 요청을 보내야 어떤 provider 가 credential 을 주었는지 알 수 있다
+        self.vm
+            .set_credentials_source(self.backend.credentials_source());
+        self.vm.update(s3_output);
+        self.select_pending_key();
+        self.record_navigation(before);
+        moved
+    }
+
+    fn replace_pending_location(&mut self, s3_output: &S3Output) {
+        if self.pending_replace.as_ref() == Some(&s3_output.bucket_and_prefix()) {
+            self.vm.list_stack.clear();
+            self.pending_replace = None;
+        }
+    }
+
+    fn select_pending_key(&mut self) {
+        if let Some((bucket, key)) = self.pending_selection.as_ref() {
+            if self.vm.bucket_and_prefix().map(|b| b.0).as_ref() == Some(bucket)
+                && self.vm.select_key(key)
+            {
+                self.pending_selection = None;
+            }
+        }
+    }
+
+    // 위치가 바뀌었다면 이전 위치를 history 에 기록한다
+    fn record_navigation(&mut self, before: Option<HistoryEntry>) {
+        let after = self.vm.bucket_and_prefix();
+        if self.pending_history.as_ref().map(|e| &e.location) == Some(&after) {
+            // back/forward 로 이동했다면 기록하지 않고 선택 상태를 되돌린다
+            if let Some(entry) = self.pending_history.take() {
+                self.vm.restore_state(entry.state);
+            }
+            return;
+        }
+        if let Some(before) = before {
+            if before.location != after {
+                self.history.record(before);
+            }
+        }
+    }
+}
+
+impl Controller {
+    pub(super) fn tab_mut(&mut self, id: usize) -> Option<&mut Tab> {
+        std::iter::once(&mut self.tab)
+            .chain(self.other_tabs.iter_mut())
+            .find(|t| t.id == id)
+    }
+
+    pub(super) fn next_tab_id(&self) -> usize {
+        self.other_tabs
+            .iter()
+            .map(|t| t.id)
+            .chain(std::iter::once(self.tab.id))
+            .max()
+            .unwrap_or(0)
+            + 1
+    }
+
+    // 현재 tab 을 순서대로 끼워 넣은 전체 tab 의 제목
+    pub(super) fn tab_titles(&self) -> Vec<String> {
+        let mut titles: Vec<_> = self.other_tabs.iter().map(|t| t.title()).collect();
+        titles.insert(self.tab_index, self.tab.title());
+        titles
+            .into_iter()
+            .enumerate()
+            .map(|(i, title)| format!("{}: {}", i + 1, title))
+            .collect()
+    }
+
+    // 전체 tab 중 id 인 tab 의 순서
+    pub(super) fn tab_position(&self, id: usize) -> Option<usize> {
+        if id == self.tab.id {
+            return Some(self.tab_index);
+        }
+        let i = self.other_tabs.iter().position(|t| t.id == id)?;
+        Some(if i < self.tab_index { i } else { i + 1 })
+    }
+
+    // 전체 tab 중 index 번째 tab 으로 바꾼다. 다른 pane 의 tab 으로 바꾸면 pane 을 서로 바꾼다
+    pub(super) fn switch_tab(&mut self, index: usize) {
+        if index == self.tab_index || index > self.other_tabs.len() {
+            return;
+        }
+        let previous_id = self.tab.id;
+        let tab = self.other_tabs.remove(if index < self.tab_index {
+            index
+        } else {
+            index - 1
+        });
+        let previous = std::mem::replace(&mut self.tab, tab);
+        self.other_tabs.insert(
+            if index < self.tab_index {
+                self.tab_index - 1
+            } else {
+                self.tab_index
+            },
+            previous,
+        );
+        self.tab_index = index;
+        if self.commander == Some(self.tab.id) {
+            self.commander = Some(previous_id);
+        }
+    }
+
+    pub(super) fn next_tab(&mut self) {
+        let count = self.other_tabs.len() + 1;
+        self.switch_tab((self.tab_index + 1) % count);
+    }
+
+    pub(super) fn previous_tab(&mut self) {
+        let count = self.other_tabs.len() + 1;
+        self.switch_tab((self.tab_index + count - 1) % count);
+    }
+
+    // 현재 tab 다음에 tab 을 만들어 그 tab 으로 바꾼다
+    pub(super) fn insert_tab(&mut self, tab: Tab) {
+        self.other_tabs.insert(self.tab_index, tab);
+        self.switch_tab(self.tab_index + 1);
+    }
+
+    // 같은 연결을 사용하는 tab 을 만들고 location 혹은 bucket 목록으로 이동한다
+    pub(super) async fn new_tab(&mut self, location: Option<String>) -> Result<()> {
+        let mut tab = Tab::new(self.next_tab_id(), self.tab.backend.clone());
+        tab.vm.set_sort_order(self.tab.vm.sort_order());
+        self.insert_tab(tab);
+        self.label_connection();
+        match location {
+            Some(location) => self.go_to(&location).await?,
+            None => self.go_to_bucket_list().await,
+        }
+        Ok(())
+    }
+
+    pub(super) async fn duplicate_tab(&mut self) {
+        let location = self.tab.vm.bucket_and_prefix();
+        let mut tab = Tab::new(self.next_tab_id(), self.tab.backend.clone());
+        tab.vm.set_sort_order(self.tab.vm.sort_order());
+        tab.search = self.tab.search.clone();
+        self.insert_tab(tab);
+        self.label_connection();
+        match location {
+            Some((bucket, prefix)) => self.go_to_location(bucket, prefix).await,
+            None => self.go_to_bucket_list().await,
+        }
+    }
+
+    pub(super) fn close_tab(&mut self) -> Result<()> {
+        if self.other_tabs.is_empty() {
+            return Err(eyre::eyre!("can't close the last tab"));
+        }
+        // 다음 tab 을, 마지막 tab 이었다면 이전 tab 을 보여준다
+        let index = self.tab_index.min(self.other_tabs.len() - 1);
+        self.tab = self.other_tabs.remove(index);
+        self.tab_index = index;
+        // 현재 tab 은 항상 pane 중 하나이므로 commander 를 끈다
+        self.commander = None;
+        Ok(())
+    }
+}
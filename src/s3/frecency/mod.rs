@@ -0,0 +1,194 @@
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+// rank 의 합이 이 값을 넘으면 전체 rank 를 줄이고 오래된 기록을 지운다
+const MAX_TOTAL_RANK: f64 = 10000.0;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Visit {
+    pub bucket: String,
+    pub prefix: String,
+    pub profile: Option<String>,
+    pub endpoint_url: Option<String>,
+    pub rank: f64,
+    // unix time (초)
+    pub last_accessed: u64,
+}
+
+impl Visit {
+    pub fn s3_uri(&self) -> String {
+        format!("s3://{}/{}", self.bucket, self.prefix)
+    }
+
+    // 최근에 방문했을수록 점수를 높인다 (zoxide 와 같은 방식)
+    pub fn score(&self, now: u64) -> f64 {
+        let elapsed = now.saturating_sub(self.last_accessed);
+        if elapsed < HOUR {
+            self.rank * 4.0
+        } else if elapsed < DAY {
+            self.rank * 2.0
+        } else if elapsed < WEEK {
+            self.rank / 2.0
+        } else {
+            self.rank / 4.0
+        }
+    }
+
+    // 모든 keyword 가 순서대로 나타나고, 마지막 keyword 는 마지막 component 에 나타나야 한다
+    pub fn matches(&self, keywords: &[String]) -> bool {
+        let path = format!("{}/{}", self.bucket, self.prefix).to_lowercase();
+        let path = path.trim_end_matches('/');
+        let last_component = path.rfind('/').map(|i| i + 1).unwrap_or(0);
+
+        let mut position = 0;
+        for (i, keyword) in keywords.iter().enumerate() {
+            let keyword = keyword.to_lowercase();
+            match path[position..].find(&keyword) {
+                Some(found) => position += found + keyword.len(),
+                None => return false,
+            }
+            if i + 1 == keywords.len() && position <= last_component {
+                return path[last_component..].contains(&keyword);
+            }
+        }
+        true
+    }
+
+    fn is_same_location(&self, other: &Visit) -> bool {
+        self.bucket == other.bucket
+            && self.prefix == other.prefix
+            && self.profile == other.profile
+            && self.endpoint_url == other.endpoint_url
+    }
+}
+
+pub fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+// 방문한 prefix 의 기록
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FrecencyDb {
+    #[serde(default, rename = "visit")]
+    visits: Vec<Visit>,
+}
+
+impl FrecencyDb {
+    // $XDG_DATA_HOME/tui-s3 혹은 ~/.local/share/tui-s3
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("tui-s3").join("frecency.toml"))
+    }
+
+    pub fn load() -> Result<FrecencyDb> {
+        match Self::path() {
+            Some(path) if path.exists() => {
+                let text = std::fs::read_to_string(&path)?;
+                toml::from_str(&text).map_err(|e| eyre::eyre!("{}: {}", path.display(), e))
+            }
+            _ => Ok(FrecencyDb::default()),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        Self::write(&self.contents()?)
+    }
+
+    // 파일에 쓸 내용. UI 를 막지 않도록 쓰기는 따로 할 수 있다
+    pub fn contents(&self) -> Result<String> {
+        Ok(toml::to_string(self)?)
+    }
+
+    pub fn write(contents: &str) -> Result<()> {
+        let path = Self::path().ok_or_else(|| eyre::eyre!("can't find the data directory"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, contents)?;
+        Ok(())
+    }
+
+    pub fn visit(&mut self, mut visit: Visit) {
+        match self.visits.iter_mut().find(|v| v.is_same_location(&visit)) {
+            Some(v) => {
+                v.rank += 1.0;
+                v.last_accessed = visit.last_accessed;
+            }
+            None => {
+                visit.rank = 1.0;
+                self.visits.push(visit);
+            }
+        }
+
+        if self.visits.iter().map(|v| v.rank).sum::<f64>() > MAX_TOTAL_RANK {
+            for v in self.visits.iter_mut() {
+                v.rank *= 0.9;
+            }
+            self.visits.retain(|v| v.rank >= 1.0);
+        }
+    }
+
+    // keyword 와 일치하는 기록을 점수 순서로 구한다
+    pub fn query(&self, keywords: &[String], now: u64) -> Vec<Visit> {
+        let mut visits: Vec<_> = self
+            .visits
+            .iter()
+            .filter(|v| v.matches(keywords))
+            .cloned()
+            .collect();
+        visits.sort_by(|a, b| b.score(now).total_cmp(&a.score(now)));
+        visits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frecency() {
+        let visit = |bucket: &str, prefix: &str, last_accessed: u64| Visit {
+            bucket: bucket.to_owned(),
+            prefix: prefix.to_owned(),
+            profile: None,
+            endpoint_url: None,
+            rank: 0.0,
+            last_accessed,
+        };
+        let keywords =
+            |str: &str| -> Vec<String> { str.split_whitespace().map(|k| k.to_owned()).collect() };
+
+        let deep = visit("logs", "prod/2024/", 0);
+        assert!(deep.matches(&keywords("logs prod 2024")));
+        assert!(deep.matches(&keywords("Prod 24")));
+        assert!(deep.matches(&keywords("")));
+        // keyword 는 순서대로 나타나야 한다
+        assert!(!deep.matches(&keywords("prod logs")));
+        // 마지막 keyword 는 마지막 component 에 있어야 한다
+        assert!(!deep.matches(&keywords("prod")));
+
+        let now = 10 * WEEK;
+        let mut db = FrecencyDb::default();
+        db.visit(visit("prod-logs", "app/2024/", now - 2 * WEEK));
+        db.visit(visit("prod-logs", "app/2024/", now - 2 * WEEK));
+        db.visit(visit("prod-logs", "web/2024/", now - 10));
+
+        let uris: Vec<_> = db
+            .query(&keywords("2024"), now)
+            .iter()
+            .map(|v| v.s3_uri())
+            .collect();
+        assert_eq!(
+            uris,
+            vec!["s3://prod-logs/web/2024/", "s3://prod-logs/app/2024/"]
+        );
+        assert_eq!(db.query(&keywords("app 2024"), now)[0].rank, 2.0);
+    }
+}
@@ -0,0 +1,485 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use eyre::Result;
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+use strum_macros::{Display, EnumString};
+
+use super::config::KeymapConfig;
+
+#[derive(Clone, Copy, Debug, PartialEq, EnumString, Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Action {
+    Quit,
+    Up,
+    Down,
+    First,
+    Last,
+    Enter,
+    Refresh,
+    CopyUri,
+    Search,
+    SearchNext,
+    CommandLine,
+    FuzzyFinder,
+    Bookmarks,
+    AddBookmark,
+    Back,
+    Forward,
+    FrecencyJump,
+    NewTab,
+    DuplicateTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+    Commander,
+    SwitchPane,
+    CopyToPane,
+    MoveToPane,
+    Profiles,
+    OpenBucket,
+    ErrorLog,
+    // 진행중인 목록 조회를 취소한다
+    Cancel,
+    // 기본 binding 을 없앨 때 사용한다
+    Noop,
+}
+
+const DEFAULT_BINDINGS: [(&str, Action); 34] = [
+    ("q", Action::Quit),
+    ("<C-c>", Action::Quit),
+    ("k", Action::Up),
+    ("<Up>", Action::Up),
+    ("j", Action::Down),
+    ("<Down>", Action::Down),
+    ("gg", Action::First),
+    ("G", Action::Last),
+    ("<Enter>", Action::Enter),
+    ("<C-r>", Action::Refresh),
+    ("y", Action::CopyUri),
+    ("/", Action::Search),
+    ("n", Action::SearchNext),
+    (":", Action::CommandLine),
+    ("<C-p>", Action::FuzzyFinder),
+    ("'", Action::Bookmarks),
+    ("m", Action::AddBookmark),
+    ("<C-o>", Action::Back),
+    // 대부분의 터미널에서 Ctrl-i 는 Tab 으로 입력된다
+    ("<Tab>", Action::Forward),
+    ("<C-i>", Action::Forward),
+    ("z", Action::FrecencyJump),
+    ("t", Action::NewTab),
+    ("T", Action::DuplicateTab),
+    ("<C-w>", Action::CloseTab),
+    ("gt", Action::NextTab),
+    ("gT", Action::PreviousTab),
+    ("|", Action::Commander),
+    ("<C-l>", Action::SwitchPane),
+    ("<F5>", Action::CopyToPane),
+    ("<F6>", Action::MoveToPane),
+    ("P", Action::Profiles),
+    ("o", Action::OpenBucket),
+    ("E", Action::ErrorLog),
+    ("<Esc>", Action::Cancel),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl From<KeyEvent> for Key {
+    // 문자 키는 shift 여부가 이미 문자에 반영되어 있으므로 shift 를 무시한다
+    fn from(key: KeyEvent) -> Self {
+        let modifiers = match key.code {
+            KeyCode::Char(_) => key.modifiers - KeyModifiers::SHIFT,
+            _ => key.modifiers,
+        };
+        Key {
+            code: key.code,
+            modifiers,
+        }
+    }
+}
+
+fn parse_named_key(name: &str) -> Result<Key> {
+    let (modifiers, name) = match name.split_once('-') {
+        Some((modifier, name)) if !name.is_empty() => {
+            let modifiers = match modifier {
+                "C" | "c" => KeyModifiers::CONTROL,
+                "A" | "a" | "M" | "m" => KeyModifiers::ALT,
+                "S" | "s" => KeyModifiers::SHIFT,
+                _ => return Err(eyre::eyre!("unknown modifier: {}", modifier)),
+            };
+            (modifiers, name)
+        }
+        _ => (KeyModifiers::NONE, name),
+    };
+
+    let code = match name.to_lowercase().as_str() {
+        "enter" | "cr" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "bs" | "backspace" => KeyCode::Backspace,
+        "del" | "delete" => KeyCode::Delete,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "space" => KeyCode::Char(' '),
+        "lt" => KeyCode::Char('<'),
+        f if f.starts_with('f') && f.len() > 1 => KeyCode::F(
+            f[1..]
+                .parse()
+                .map_err(|_| eyre::eyre!("unknown key: <{}>", name))?,
+        ),
+        _ if name.chars().count() == 1 => KeyCode::Char(name.chars().next().unwrap()),
+        _ => return Err(eyre::eyre!("unknown key: <{}>", name)),
+    };
+
+    Ok(Key::from(KeyEvent::new(code, modifiers)))
+}
+
+// "gg", "<C-r>", "<leader>f" 형태의 키 sequence 를 해석한다
+pub fn parse_key_sequence(sequence: &str, leader: Option<Key>) -> Result<Vec<Key>> {
+    let mut keys = vec![];
+    let mut rest = sequence;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some(end) = rest.find('>') {
+                let name = &rest[1..end];
+                if name.eq_ignore_ascii_case("leader") {
+                    keys.push(leader.ok_or_else(|| eyre::eyre!("<leader> is not allowed here"))?);
+                } else {
+                    keys.push(parse_named_key(name)?);
+                }
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        keys.push(Key::from(KeyEvent::new(
+            KeyCode::Char(c),
+            KeyModifiers::NONE,
+        )));
+        rest = &rest[c.len_utf8()..];
+    }
+    if keys.is_empty() {
+        Err(eyre::eyre!("empty key sequence"))
+    } else {
+        Ok(keys)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum KeymapResult {
+    Action(Action),
+    // 다음 키 혹은 timeout 을 기다린다
+    Pending,
+    Unbound,
+    // 기다리던 sequence 가 이어지지 않았다. 앞서 입력한 키의 action 을 실행한 뒤
+    // 마지막 키의 결과를 따른다
+    Interrupted(Action, Box<KeymapResult>),
+}
+
+impl KeymapResult {
+    // self 의 action 을 실행한 뒤 next 를 따르도록 합친다
+    fn then(self, next: KeymapResult) -> KeymapResult {
+        match self {
+            KeymapResult::Action(action) => KeymapResult::Interrupted(action, Box::new(next)),
+            KeymapResult::Interrupted(action, result) => {
+                KeymapResult::Interrupted(action, Box::new(result.then(next)))
+            }
+            KeymapResult::Pending | KeymapResult::Unbound => next,
+        }
+    }
+}
+
+pub struct Keymap {
+    bindings: HashMap<Vec<Key>, Action>,
+    timeout: Duration,
+    pending_keys: Vec<Key>,
+    deadline: Option<Instant>,
+}
+
+impl Keymap {
+    // 설정을 해석하고, 해석할 수 없는 binding 은 문제 목록으로 돌려준다
+    pub fn new(config: &KeymapConfig) -> (Keymap, Vec<String>) {
+        let mut problems = vec![];
+        let leader = parse_key_sequence(&config.leader, None)
+            .and_then(|keys| match keys[..] {
+                [key] => Ok(key),
+                _ => Err(eyre::eyre!("leader must be a single key")),
+            })
+            .map_err(|e| problems.push(format!("leader {}: {}", config.leader, e)))
+            .ok();
+
+        let mut bindings: HashMap<_, _> = DEFAULT_BINDINGS
+            .iter()
+            .map(|(sequence, action)| (parse_key_sequence(sequence, None).unwrap(), *action))
+            .collect();
+
+        // 설정 파일 안에서 같은 sequence 를 다른 action 에 연결했는지 확인한다
+        let mut configured: HashMap<Vec<Key>, &str> = HashMap::new();
+        for (sequence, action_name) in config.bindings.iter() {
+            let action = match Action::from_str(action_name) {
+                Ok(action) => action,
+                Err(_) => {
+                    problems.push(format!("{}: unknown action {}", sequence, action_name));
+                    continue;
+                }
+            };
+            let keys = match parse_key_sequence(sequence, leader) {
+                Ok(keys) => keys,
+                Err(e) => {
+                    problems.push(format!("{}: {}", sequence, e));
+                    continue;
+                }
+            };
+            if let Some(other) = configured.insert(keys.clone(), sequence) {
+                problems.push(format!("{} conflicts with {}", sequence, other));
+            }
+            bindings.insert(keys, action);
+        }
+
+        // 설정한 sequence 로 시작하는 기본 binding 은 timeout 전에 다음 키를 눌러야만 실행된다
+        for (sequence, action) in DEFAULT_BINDINGS.iter() {
+            let keys = parse_key_sequence(sequence, None).unwrap();
+            if configured.contains_key(&keys) || *action == Action::Noop {
+                continue;
+            }
+            let shadowing = configured
+                .iter()
+                .filter(|(prefix, _)| prefix.len() < keys.len() && keys.starts_with(prefix))
+                .filter(|(prefix, _)| bindings.get(*prefix) != Some(&Action::Noop));
+            for (_, user_sequence) in shadowing {
+                problems.push(format!(
+                    "{} shadows the default {} ({})",
+                    user_sequence, sequence, action
+                ));
+            }
+        }
+
+        let keymap = Keymap {
+            bindings,
+            timeout: Duration::from_millis(config.timeout_ms),
+            pending_keys: vec![],
+            deadline: None,
+        };
+        (keymap, problems)
+    }
+
+    // 다른 binding 이 keys 로 시작하는지 확인한다
+    fn has_longer_binding(&self, keys: &[Key]) -> bool {
+        self.bindings.iter().any(|(k, action)| {
+            k.len() > keys.len() && k.starts_with(keys) && *action != Action::Noop
+        })
+    }
+
+    fn lookup(&self, keys: &[Key]) -> Option<Action> {
+        self.bindings
+            .get(keys)
+            .copied()
+            .filter(|action| *action != Action::Noop)
+    }
+
+    // 다음 키 입력을 기다리는 기한
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    pub fn feed(&mut self, key: KeyEvent, now: Instant) -> KeymapResult {
+        self.pending_keys.push(Key::from(key));
+
+        if self.has_longer_binding(&self.pending_keys) {
+            self.deadline = Some(now + self.timeout);
+            return KeymapResult::Pending;
+        }
+
+        let keys = std::mem::take(&mut self.pending_keys);
+        self.deadline = None;
+        if let Some(action) = self.lookup(&keys) {
+            return KeymapResult::Action(action);
+        }
+        if keys.len() == 1 {
+            return KeymapResult::Unbound;
+        }
+        // 이어지지 않는 sequence 였다면 action 이 연결된 가장 긴 앞부분을 실행하고 나머지 키를 다시
+        // 해석한다. 그런 앞부분이 없다면 마지막 키만 다시 해석한다
+        let bound = (1..keys.len())
+            .rev()
+            .find_map(|len| self.lookup(&keys[..len]).map(|action| (len, action)));
+        match bound {
+            Some((len, action)) => {
+                KeymapResult::Interrupted(action, Box::new(self.replay(&keys[len..], now)))
+            }
+            None => self.feed(key, now),
+        }
+    }
+
+    fn replay(&mut self, keys: &[Key], now: Instant) -> KeymapResult {
+        keys.iter().fold(KeymapResult::Unbound, |result, key| {
+            let next = self.feed(KeyEvent::new(key.code, key.modifiers), now);
+            result.then(next)
+        })
+    }
+
+    // 기한이 지나면 지금까지 입력한 키에 연결된 action 을 실행한다
+    pub fn timeout(&mut self) -> Option<Action> {
+        self.deadline = None;
+        let keys = std::mem::take(&mut self.pending_keys);
+        self.lookup(&keys)
+    }
+
+    pub fn reset(&mut self) {
+        self.deadline = None;
+        self.pending_keys.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_parse_key_sequence() {
+        let leader = Key::from(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+        assert_eq!(
+            parse_key_sequence("<C-r>", None).unwrap(),
+            vec![Key::from(KeyEvent::new(
+                KeyCode::Char('r'),
+                KeyModifiers::CONTROL
+            ))]
+        );
+        assert_eq!(
+            parse_key_sequence("<leader>f", Some(leader)).unwrap(),
+            vec![leader, Key::from(key('f'))]
+        );
+        // 대문자는 shift 와 함께 입력되어도 같은 키다
+        assert_eq!(
+            parse_key_sequence("G", None).unwrap(),
+            vec![Key::from(KeyEvent::new(
+                KeyCode::Char('G'),
+                KeyModifiers::SHIFT
+            ))]
+        );
+        assert!(parse_key_sequence("<Foo>", None).is_err());
+        assert!(parse_key_sequence("<leader>", None).is_err());
+    }
+
+    #[test]
+    fn test_keymap() {
+        let mut config = KeymapConfig::default();
+        config.bindings.insert("g".to_owned(), "last".to_owned());
+        config
+            .bindings
+            .insert("<leader>f".to_owned(), "fuzzy-finder".to_owned());
+        config.bindings.insert("x".to_owned(), "explode".to_owned());
+        config
+            .bindings
+            .insert("<Space>f".to_owned(), "quit".to_owned());
+        let (mut keymap, problems) = Keymap::new(&config);
+        assert_eq!(
+            problems,
+            vec![
+                "<leader>f conflicts with <Space>f".to_owned(),
+                "x: unknown action explode".to_owned(),
+                "g shadows the default gg (first)".to_owned(),
+                "g shadows the default gt (next-tab)".to_owned(),
+                "g shadows the default gT (previous-tab)".to_owned(),
+            ]
+        );
+
+        let now = Instant::now();
+        assert_eq!(
+            keymap.feed(key('j'), now),
+            KeymapResult::Action(Action::Down)
+        );
+
+        // g 와 gg 가 모두 있다면 다음 키를 기다린다
+        assert_eq!(keymap.feed(key('g'), now), KeymapResult::Pending);
+        assert_eq!(keymap.deadline(), Some(now + Duration::from_millis(1000)));
+        assert_eq!(
+            keymap.feed(key('g'), now),
+            KeymapResult::Action(Action::First)
+        );
+
+        assert_eq!(keymap.feed(key('g'), now), KeymapResult::Pending);
+        assert_eq!(keymap.timeout(), Some(Action::Last));
+
+        // 이어지지 않는 키는 앞서 입력한 g 의 action 을 실행한 뒤 새로 해석한다
+        assert_eq!(keymap.feed(key('g'), now), KeymapResult::Pending);
+        assert_eq!(
+            keymap.feed(key('k'), now),
+            KeymapResult::Interrupted(Action::Last, Box::new(KeymapResult::Action(Action::Up)))
+        );
+        assert_eq!(keymap.feed(key('w'), now), KeymapResult::Unbound);
+    }
+
+    #[test]
+    fn test_interrupted_sequence() {
+        let (mut keymap, problems) = Keymap::new(&KeymapConfig::default());
+        assert!(problems.is_empty());
+
+        // 기본 binding 의 g 에는 action 이 없으므로 마지막 키만 해석한다
+        let now = Instant::now();
+        assert_eq!(keymap.feed(key('g'), now), KeymapResult::Pending);
+        assert_eq!(
+            keymap.feed(key('j'), now),
+            KeymapResult::Action(Action::Down)
+        );
+
+        // noop 으로 없앤 prefix 는 기본 binding 을 가리지 않는다
+        let mut config = KeymapConfig::default();
+        config.bindings.insert("g".to_owned(), "noop".to_owned());
+        config.bindings.insert("y".to_owned(), "noop".to_owned());
+        config
+            .bindings
+            .insert("yy".to_owned(), "copy-uri".to_owned());
+        let (mut keymap, problems) = Keymap::new(&config);
+        assert!(problems.is_empty());
+        assert_eq!(keymap.feed(key('y'), now), KeymapResult::Pending);
+        assert_eq!(
+            keymap.feed(key('j'), now),
+            KeymapResult::Action(Action::Down)
+        );
+
+        // 긴 sequence 가 끊기면 action 이 연결된 가장 긴 앞부분을 실행하고 나머지 키를 다시 해석한다
+        let mut config = KeymapConfig::default();
+        config.bindings.insert("a".to_owned(), "last".to_owned());
+        config.bindings.insert("abcd".to_owned(), "quit".to_owned());
+        config.bindings.insert("ajc".to_owned(), "quit".to_owned());
+        let (mut keymap, problems) = Keymap::new(&config);
+        assert!(problems.is_empty());
+        assert_eq!(keymap.feed(key('a'), now), KeymapResult::Pending);
+        assert_eq!(keymap.feed(key('b'), now), KeymapResult::Pending);
+        assert_eq!(keymap.feed(key('c'), now), KeymapResult::Pending);
+        assert_eq!(
+            keymap.feed(key('k'), now),
+            KeymapResult::Interrupted(Action::Last, Box::new(KeymapResult::Action(Action::Up)))
+        );
+        assert_eq!(keymap.feed(key('a'), now), KeymapResult::Pending);
+        assert_eq!(keymap.feed(key('j'), now), KeymapResult::Pending);
+        assert_eq!(
+            keymap.feed(key('x'), now),
+            KeymapResult::Interrupted(
+                Action::Last,
+                Box::new(KeymapResult::Interrupted(
+                    Action::Down,
+                    Box::new(KeymapResult::Unbound)
+                ))
+            )
+        );
+    }
+}
@@ -11,10 +11,11 @@ async fn main() -> Result<()> {
     let opt = Opt::from_args();
 
     let ui_task = tokio::task::spawn(async move {
-        let controller = Controller::new(opt).await.expect("TODO: error handle");
-        run_frontend(controller).await.expect("frontend error");
+        // 처음 조회에 실패하면 panic 대신 원인을 출력하고 종료한다
+        let controller = Controller::new(opt).await?;
+        run_frontend(controller).await
     });
 
-    ui_task.await?;
+    ui_task.await??;
     Ok(())
 }
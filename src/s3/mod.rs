@@ -1,6 +1,16 @@
+pub mod backend;
+pub mod bookmark;
 pub mod client;
+pub mod command;
+pub mod config;
+pub mod connection;
 pub mod controller;
+pub mod frecency;
+pub mod keymap;
 pub mod model;
+pub mod path;
+pub mod profile;
+pub mod theme;
 pub mod view_model;
 
 pub use model::*;
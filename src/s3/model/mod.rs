@@ -1,15 +1,19 @@
 use aws_sdk_s3::model::{CommonPrefix, Object};
-use aws_smithy_types::date_time::Format;
+use aws_smithy_types::{date_time::Format, DateTime};
 use bytesize::ByteSize;
+use std::cmp::Ordering;
 use strum_macros::EnumIter;
 
 use super::*;
 
 use client::BucketWithLocation;
+use command::SortKey;
+use connection::Connection;
 
-#[derive(Debug, PartialEq, EnumIter)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, EnumIter)]
 pub enum S3ItemType {
     Pop,
+    Connection,
     Bucket,
     CommonPrefix,
     Object,
@@ -18,17 +22,17 @@ pub enum S3ItemType {
 #[derive(Clone, Debug, PartialEq)]
 pub enum S3Item {
     Pop, //상위 디렉토리를 가리키는 객체
+    Connection(Connection),
     Bucket(BucketWithLocation),
     CommonPrefix(CommonPrefix),
     Object(Object),
 }
 
 fn last_component(key_or_prefix: &str) -> String {
-    let last_component_impl =
-        |str: &str| str.split("/").into_iter().last().unwrap_or("").to_owned();
+    let last_component_impl = |str: &str| str.rsplit('/').next().unwrap_or("").to_owned();
 
     // case of prefix
-    if let Some(str) = key_or_prefix.strip_suffix("/") {
+    if let Some(str) = key_or_prefix.strip_suffix('/') {
         last_component_impl(str) + "/"
     } else {
         last_component_impl(key_or_prefix)
@@ -40,6 +44,7 @@ impl S3Item {
         // TODO: use regex
         match self {
             S3Item::Pop => false,
+            S3Item::Connection(c) => c.name.contains(search_text),
             S3Item::Bucket(b) => b.bucket.name().unwrap_or_default().contains(search_text),
             S3Item::CommonPrefix(c) => {
                 last_component(c.prefix().unwrap_or("")).contains(search_text)
@@ -48,9 +53,51 @@ impl S3Item {
         }
     }
 
+    pub fn name(&self) -> String {
+        match self {
+            S3Item::Pop => String::default(),
+            S3Item::Connection(c) => c.name.clone(),
+            S3Item::Bucket(b) => b.bucket.name().unwrap_or_default().to_owned(),
+            S3Item::CommonPrefix(c) => last_component(c.prefix().unwrap_or("")),
+            S3Item::Object(k) => last_component(k.key().unwrap_or("")),
+        }
+    }
+
+    pub fn size(&self) -> i64 {
+        match self {
+            S3Item::Object(k) => k.size(),
+            _ => 0,
+        }
+    }
+
+    pub fn last_modified(&self) -> Option<&DateTime> {
+        match self {
+            S3Item::Bucket(b) => b.bucket.creation_date(),
+            S3Item::Object(k) => k.last_modified(),
+            _ => None,
+        }
+    }
+
+    // 같은 종류의 item 끼리 정렬한다. Pop 은 항상 처음에 온다
+    pub fn compare(&self, other: &S3Item, sort_key: SortKey) -> Ordering {
+        let by_key = match sort_key {
+            SortKey::Name => self.name().cmp(&other.name()),
+            SortKey::Size => self.size().cmp(&other.size()),
+            SortKey::Date => self
+                .last_modified()
+                .map(|d| d.as_nanos())
+                .cmp(&other.last_modified().map(|d| d.as_nanos())),
+        };
+        self.get_type()
+            .cmp(&other.get_type())
+            .then(by_key)
+            .then_with(|| self.name().cmp(&other.name()))
+    }
+
     pub fn get_type(&self) -> S3ItemType {
         match self {
             S3Item::Pop => S3ItemType::Pop,
+            S3Item::Connection(_) => S3ItemType::Connection,
             S3Item::Bucket(_) => S3ItemType::Bucket,
             S3Item::CommonPrefix(_) => S3ItemType::CommonPrefix,
             S3Item::Object(_) => S3ItemType::Object,
@@ -67,14 +114,14 @@ impl S3Item {
             S3Item::Object(k) => (
                 k.last_modified()
                     .map(|m| m.fmt(Format::DateTime).unwrap_or_default())
-                    .unwrap_or(String::default()),
+                    .unwrap_or_default(),
                 ByteSize(k.size() as u64).to_string_as(true),
                 last_component(k.key().unwrap_or("")),
             ),
             S3Item::Bucket(b) => {
                 let location = {
                     let location = b.location.as_str().to_owned();
-                    if location.len() > 0 {
+                    if !location.is_empty() {
                         location
                     } else {
                         "unknown".to_owned()
@@ -83,6 +130,7 @@ impl S3Item {
                 let bucket_name = b.bucket.name().unwrap_or("").to_owned();
                 (String::default(), location, bucket_name)
             }
+            S3Item::Connection(c) => (c.endpoint().to_owned(), c.credentials(), c.name.clone()),
             S3Item::Pop => ("..".to_owned(), String::default(), String::default()),
         }
     }
@@ -0,0 +1,232 @@
+use eyre::Result;
+
+// key 에서 마지막 '/' 까지를 prefix 로 구한다
+pub fn parent_prefix(key: &str) -> String {
+    key.rfind('/')
+        .map(|i| key[..i + 1].to_owned())
+        .unwrap_or_default()
+}
+
+// key 를 url 에 넣을 수 있도록 '/' 를 제외한 예약 문자를 %XX 로 바꾼다
+pub fn percent_encode(key: &str) -> String {
+    key.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+// %XX 로 encoding 된 문자열을 되돌린다
+fn percent_decode(str: &str) -> String {
+    let bytes = str.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                decoded.push(b);
+                i += 3;
+            }
+            (b'+', _) => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            (b, _) => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn split_bucket_and_key(str: &str) -> (String, String) {
+    match str.split_once('/') {
+        Some((bucket, key)) => (bucket.to_owned(), key.to_owned()),
+        None => (str.to_owned(), String::default()),
+    }
+}
+
+// https://bucket.s3.region.amazonaws.com/key 혹은 https://s3.region.amazonaws.com/bucket/key
+fn parse_https_url(url: &str) -> Option<(String, String)> {
+    let (host, path) = match url.split_once('/') {
+        Some((host, path)) => (host, path),
+        None => (url, ""),
+    };
+    let path = percent_decode(path.split(['?', '#']).next().unwrap_or(""));
+
+    if host.starts_with("s3.") || host.starts_with("s3-") {
+        // path style
+        Some(split_bucket_and_key(&path))
+    } else {
+        // virtual hosted style
+        host.find(".s3.")
+            .or_else(|| host.find(".s3-"))
+            .map(|i| (host[..i].to_owned(), path))
+    }
+}
+
+// s3://bucket/key, https://bucket.s3.amazonaws.com/key, arn:aws:s3:::bucket/key 형태의
+// 위치를 (bucket, key) 로 나눈다
+pub fn parse_s3_location(location: &str) -> Result<(String, String)> {
+    let location = location.trim();
+    let (bucket, key) = if let Some(str) = location.strip_prefix("s3://") {
+        split_bucket_and_key(str)
+    } else if let Some(url) = location
+        .strip_prefix("https://")
+        .or_else(|| location.strip_prefix("http://"))
+    {
+        parse_https_url(url).ok_or_else(|| eyre::eyre!("{} is not an s3 url", location))?
+    } else if location.starts_with("arn:") {
+        match location.splitn(6, ':').collect::<Vec<_>>()[..] {
+            ["arn", _, "s3", "", "", resource] => split_bucket_and_key(resource),
+            _ => return Err(eyre::eyre!("{} is not an s3 arn", location)),
+        }
+    } else {
+        return Err(eyre::eyre!(
+            "location must start with s3://, https:// or arn:aws:s3:::"
+        ));
+    };
+
+    if bucket.is_empty() {
+        Err(eyre::eyre!("bucket is missing in {}", location))
+    } else {
+        Ok((bucket, key))
+    }
+}
+
+// 위치를 (bucket, prefix) 로 나눈다. prefix 가 아닌 key 는 상위 prefix 로 바꾼다
+pub fn parse_s3_prefix(location: &str) -> Result<(String, String)> {
+    let (bucket, key) = parse_s3_location(location)?;
+    Ok((bucket, parent_prefix(&key)))
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CompletionTarget {
+    // 이름이 partial 로 시작하는 bucket
+    Buckets {
+        partial: String,
+    },
+    // bucket 의 prefix 아래에서 partial 로 시작하는 common prefix
+    Prefixes {
+        bucket: String,
+        prefix: String,
+        partial: String,
+    },
+}
+
+// 입력중인 s3:// 위치에서 완성할 대상을 구한다
+pub fn completion_target(input: &str) -> Option<CompletionTarget> {
+    if "s3://".starts_with(input) {
+        return Some(CompletionTarget::Buckets {
+            partial: String::default(),
+        });
+    }
+    let str = input.strip_prefix("s3://")?;
+    match str.split_once('/') {
+        None => Some(CompletionTarget::Buckets {
+            partial: str.to_owned(),
+        }),
+        Some((bucket, key)) => Some(CompletionTarget::Prefixes {
+            bucket: bucket.to_owned(),
+            prefix: parent_prefix(key),
+            partial: key.to_owned(),
+        }),
+    }
+}
+
+// 후보들이 공통으로 시작하는 문자열을 구한다
+pub fn longest_common_prefix(candidates: &[String]) -> String {
+    let first = match candidates.first() {
+        Some(first) => first,
+        None => return String::default(),
+    };
+    let len = candidates.iter().skip(1).fold(first.len(), |len, c| {
+        first
+            .char_indices()
+            .zip(c.chars())
+            .take_while(|((i, a), b)| *i < len && a == b)
+            .last()
+            .map(|((i, a), _)| i + a.len_utf8())
+            .unwrap_or(0)
+    });
+    first[..len].to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_s3_location() {
+        let parse = |s: &str| parse_s3_location(s).unwrap();
+        let expected = ("bucket".to_owned(), "a/b/c.txt".to_owned());
+
+        assert_eq!(parse("s3://bucket/a/b/c.txt"), expected);
+        assert_eq!(parse("https://bucket.s3.amazonaws.com/a/b/c.txt"), expected);
+        assert_eq!(
+            parse("https://bucket.s3.ap-northeast-2.amazonaws.com/a/b/c.txt?versionId=1"),
+            expected
+        );
+        assert_eq!(
+            parse("https://s3.eu-west-1.amazonaws.com/bucket/a/b/c.txt"),
+            expected
+        );
+        assert_eq!(parse("arn:aws:s3:::bucket/a/b/c.txt"), expected);
+        assert_eq!(
+            parse("https://bucket.s3.amazonaws.com/a%20b/c+d"),
+            ("bucket".to_owned(), "a b/c d".to_owned())
+        );
+        assert_eq!(
+            parse("arn:aws:s3:::bucket"),
+            ("bucket".to_owned(), "".to_owned())
+        );
+
+        assert_eq!(
+            percent_decode(&percent_encode("a b/c+d%/한")),
+            "a b/c+d%/한"
+        );
+
+        assert!(parse_s3_location("bucket/a").is_err());
+        assert!(parse_s3_location("s3://").is_err());
+        assert!(parse_s3_location("https://example.com/a").is_err());
+        assert!(parse_s3_location("arn:aws:iam:::role/a").is_err());
+    }
+
+    #[test]
+    fn test_completion_target() {
+        assert_eq!(
+            completion_target("s3:/"),
+            Some(CompletionTarget::Buckets {
+                partial: "".to_owned()
+            })
+        );
+        assert_eq!(
+            completion_target("s3://buc"),
+            Some(CompletionTarget::Buckets {
+                partial: "buc".to_owned()
+            })
+        );
+        assert_eq!(
+            completion_target("s3://bucket/a/b"),
+            Some(CompletionTarget::Prefixes {
+                bucket: "bucket".to_owned(),
+                prefix: "a/".to_owned(),
+                partial: "a/b".to_owned(),
+            })
+        );
+        assert_eq!(completion_target("https://"), None);
+
+        assert_eq!(
+            longest_common_prefix(&["s3://b/logs/".to_owned(), "s3://b/logs-old/".to_owned()]),
+            "s3://b/logs"
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use aws_config::profile::{Profile, ProfileSet};
+use aws_types::os_shim_internal::{Env, Fs};
+use strum_macros::Display;
+
+// profile 이 credential 을 얻는 방법
+#[derive(Clone, Copy, Debug, PartialEq, Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum ProfileKind {
+    // aws_access_key_id 를 직접 사용한다
+    Keys,
+    Role,
+    Sso,
+    Process,
+    // region 같은 설정만 있다
+    Settings,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AwsProfile {
+    pub name: String,
+    pub region: Option<String>,
+    pub kind: ProfileKind,
+    // role 이라면 role arn, sso 라면 account 와 role 이름
+    pub detail: Option<String>,
+    // role 을 assume 할 때 사용하는 mfa 장치와 credential 을 가진 profile
+    pub mfa_serial: Option<String>,
+    pub source_profile: Option<String>,
+}
+
+impl AwsProfile {
+    fn new(profile: &Profile) -> Self {
+        let get = |key: &str| profile.get(key).map(|v| v.to_owned());
+        // sdk 가 credential provider 를 고르는 순서와 같다
+        let (kind, detail) = if let Some(role_arn) = get("role_arn") {
+            (ProfileKind::Role, Some(role_arn))
+        } else if get("sso_start_url").is_some() || get("sso_session").is_some() {
+            let detail = match (get("sso_account_id"), get("sso_role_name")) {
+                (Some(account), Some(role)) => Some(format!("{}/{}", account, role)),
+                (account, role) => account.or(role),
+            };
+            (ProfileKind::Sso, detail)
+        } else if let Some(process) = get("credential_process") {
+            (ProfileKind::Process, Some(process))
+        } else if get("aws_access_key_id").is_some() {
+            (ProfileKind::Keys, None)
+        } else {
+            (ProfileKind::Settings, None)
+        };
+
+        Self {
+            name: profile.name().to_owned(),
+            region: get("region"),
+            kind,
+            detail,
+            mfa_serial: get("mfa_serial"),
+            source_profile: get("source_profile"),
+        }
+    }
+}
+
+// 이름 순서로 정렬한 profile 목록
+fn from_profile_set(profile_set: &ProfileSet) -> Vec<AwsProfile> {
+    let mut profiles: Vec<_> = profile_set
+        .profiles()
+        .filter_map(|name| profile_set.get_profile(name))
+        .map(AwsProfile::new)
+        .collect();
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    profiles
+}
+
+// ~/.aws/config 와 ~/.aws/credentials 에 정의된 profile. 읽을 수 없다면 빈 목록이다
+pub async fn load() -> Vec<AwsProfile> {
+    load_from(&Fs::real(), &Env::real()).await
+}
+
+async fn load_from(fs: &Fs, env: &Env) -> Vec<AwsProfile> {
+    aws_config::profile::load(fs, env)
+        .await
+        .map(|profile_set| from_profile_set(&profile_set))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_load_profiles() {
+        let fs = Fs::from_slice(&[
+            (
+                "/home/.aws/config",
+                "[default]\nregion = us-east-1\n\
+                 [profile admin]\nrole_arn = arn:aws:iam::123:role/admin\nsource_profile = default\n\
+                 mfa_serial = arn:aws:iam::123:mfa/me\n\
+                 [profile dev]\nsso_start_url = https://example.awsapps.com/start\n\
+                 sso_account_id = 456\nsso_role_name = Dev\nregion = eu-west-1\n\
+                 [profile tool]\ncredential_process = /bin/creds\n",
+            ),
+            (
+                "/home/.aws/credentials",
+                "[default]\naws_access_key_id = a\naws_secret_access_key = b\n",
+            ),
+        ]);
+        let env = Env::from_slice(&[("HOME", "/home")]);
+        let profiles = load_from(&fs, &env).await;
+
+        let summary: Vec<_> = profiles
+            .iter()
+            .map(|p| {
+                (
+                    p.name.as_str(),
+                    p.region.as_deref(),
+                    p.kind,
+                    p.detail.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "admin",
+                    None,
+                    ProfileKind::Role,
+                    Some("arn:aws:iam::123:role/admin")
+                ),
+                ("default", Some("us-east-1"), ProfileKind::Keys, None),
+                ("dev", Some("eu-west-1"), ProfileKind::Sso, Some("456/Dev")),
+                ("tool", None, ProfileKind::Process, Some("/bin/creds")),
+            ]
+        );
+        assert_eq!(ProfileKind::Keys.to_string(), "keys");
+        assert_eq!(
+            profiles[0].mfa_serial.as_deref(),
+            Some("arn:aws:iam::123:mfa/me")
+        );
+        assert_eq!(profiles[0].source_profile.as_deref(), Some("default"));
+
+        // 파일이 없다면 빈 목록이다
+        assert!(load_from(&Fs::from_slice(&[]), &env).await.is_empty());
+    }
+}
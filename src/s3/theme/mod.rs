@@ -0,0 +1,298 @@
+use eyre::Result;
+use std::collections::HashMap;
+use tui::style::{Color, Modifier, Style};
+
+use super::config::{StyleConfig, ThemeConfig};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub row: Style,
+    pub highlight: Style,
+    // commander 에서 현재 pane 이 아닌 pane 의 선택된 행
+    pub inactive_highlight: Style,
+    pub date: Style,
+    pub size: Style,
+    pub bucket: Style,
+    pub prefix: Style,
+    pub object: Style,
+    pub header: Style,
+    pub status_bar: Style,
+    pub info: Style,
+    pub error: Style,
+    pub prompt: Style,
+    // 소문자 storage class 이름(deep_archive 등)과 object 이름에 덧씌울 style
+    pub storage_classes: HashMap<String, Style>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            row: Style::default().fg(Color::White).bg(Color::Black),
+            highlight: Style::default()
+                .fg(Color::Black)
+                .bg(Color::White)
+                .add_modifier(Modifier::BOLD),
+            inactive_highlight: Style::default().fg(Color::White).bg(Color::DarkGray),
+            date: Style::default().fg(Color::Magenta),
+            size: Style::default().fg(Color::Blue),
+            bucket: Style::default(),
+            prefix: Style::default(),
+            object: Style::default(),
+            header: Style::default().fg(Color::Cyan),
+            status_bar: Style::default().fg(Color::Black).bg(Color::Yellow),
+            info: Style::default().fg(Color::Green),
+            error: Style::default().fg(Color::Red),
+            prompt: Style::default().fg(Color::Cyan),
+            storage_classes: [
+                ("glacier", Style::default().fg(Color::DarkGray)),
+                ("deep_archive", Style::default().fg(Color::DarkGray)),
+            ]
+            .into_iter()
+            .map(|(class, style)| (class.to_owned(), style))
+            .collect(),
+        }
+    }
+}
+
+impl Theme {
+    // 밝은 배경의 터미널을 위한 theme
+    fn high_contrast() -> Self {
+        Self {
+            row: Style::default().fg(Color::Black).bg(Color::White),
+            highlight: Style::default()
+                .fg(Color::White)
+                .bg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+            inactive_highlight: Style::default().add_modifier(Modifier::UNDERLINED),
+            date: Style::default().fg(Color::Blue),
+            size: Style::default().fg(Color::Black),
+            bucket: Style::default().add_modifier(Modifier::BOLD),
+            prefix: Style::default().add_modifier(Modifier::BOLD),
+            object: Style::default(),
+            header: Style::default()
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+            status_bar: Style::default().fg(Color::White).bg(Color::Blue),
+            info: Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+            error: Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            prompt: Style::default()
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+            storage_classes: [
+                ("glacier", Style::default().add_modifier(Modifier::ITALIC)),
+                (
+                    "deep_archive",
+                    Style::default().add_modifier(Modifier::ITALIC),
+                ),
+            ]
+            .into_iter()
+            .map(|(class, style)| (class.to_owned(), style))
+            .collect(),
+        }
+    }
+
+    // 색 없이 modifier 만 사용하는 theme
+    fn no_color() -> Self {
+        Self {
+            row: Style::default(),
+            highlight: Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            inactive_highlight: Style::default().add_modifier(Modifier::UNDERLINED),
+            date: Style::default(),
+            size: Style::default(),
+            bucket: Style::default(),
+            prefix: Style::default().add_modifier(Modifier::BOLD),
+            object: Style::default(),
+            header: Style::default().add_modifier(Modifier::BOLD),
+            status_bar: Style::default().add_modifier(Modifier::REVERSED),
+            info: Style::default(),
+            error: Style::default().add_modifier(Modifier::BOLD),
+            prompt: Style::default(),
+            storage_classes: HashMap::new(),
+        }
+    }
+
+    fn named(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "no-color" => Some(Self::no_color()),
+            _ => None,
+        }
+    }
+
+    fn element_mut(&mut self, element: &str) -> Option<&mut Style> {
+        let style = match element {
+            "row" => &mut self.row,
+            "highlight" => &mut self.highlight,
+            "inactive-highlight" => &mut self.inactive_highlight,
+            "date" => &mut self.date,
+            "size" => &mut self.size,
+            "bucket" => &mut self.bucket,
+            "prefix" => &mut self.prefix,
+            "object" => &mut self.object,
+            "header" => &mut self.header,
+            "status-bar" => &mut self.status_bar,
+            "info" => &mut self.info,
+            "error" => &mut self.error,
+            "prompt" => &mut self.prompt,
+            _ => {
+                let class = element.strip_prefix("storage-class.")?;
+                self.storage_classes
+                    .entry(class.to_lowercase().replace('-', "_"))
+                    .or_default()
+            }
+        };
+        Some(style)
+    }
+
+    // no_color 라면 설정과 관계없이 색을 사용하지 않는다 (NO_COLOR 환경 변수)
+    pub fn new(config: &ThemeConfig, no_color: bool) -> (Theme, Vec<String>) {
+        let mut problems = vec![];
+        let mut theme = if no_color {
+            Self::no_color()
+        } else {
+            Self::named(&config.name).unwrap_or_else(|| {
+                problems.push(format!("unknown theme {}", config.name));
+                Self::default()
+            })
+        };
+
+        for (element, style_config) in config.styles.iter() {
+            let style = match parse_style(style_config) {
+                Ok(style) if no_color => Style {
+                    fg: None,
+                    bg: None,
+                    ..style
+                },
+                Ok(style) => style,
+                Err(e) => {
+                    problems.push(format!("{}: {}", element, e));
+                    continue;
+                }
+            };
+            match theme.element_mut(element) {
+                Some(target) => *target = target.patch(style),
+                None => problems.push(format!("unknown theme element {}", element)),
+            }
+        }
+        (theme, problems)
+    }
+
+    // object 의 storage class 에 따른 이름 style
+    pub fn object_style(&self, storage_class: Option<&str>) -> Style {
+        storage_class
+            .and_then(|class| self.storage_classes.get(&class.to_lowercase()))
+            .map(|style| self.object.patch(*style))
+            .unwrap_or(self.object)
+    }
+}
+
+// black, light-red, #rrggbb, 0-255 형태의 색
+pub fn parse_color(str: &str) -> Result<Color> {
+    let color = match str.to_lowercase().replace('_', "-").as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "dark-gray" | "dark-grey" => Color::DarkGray,
+        "light-red" => Color::LightRed,
+        "light-green" => Color::LightGreen,
+        "light-yellow" => Color::LightYellow,
+        "light-blue" => Color::LightBlue,
+        "light-magenta" => Color::LightMagenta,
+        "light-cyan" => Color::LightCyan,
+        "white" => Color::White,
+        hex if hex.starts_with('#') && hex.len() == 7 => {
+            let component = |i: usize| {
+                u8::from_str_radix(&hex[i..i + 2], 16)
+                    .map_err(|_| eyre::eyre!("invalid color: {}", str))
+            };
+            Color::Rgb(component(1)?, component(3)?, component(5)?)
+        }
+        index => Color::Indexed(
+            index
+                .parse()
+                .map_err(|_| eyre::eyre!("invalid color: {}", str))?,
+        ),
+    };
+    Ok(color)
+}
+
+fn parse_style(config: &StyleConfig) -> Result<Style> {
+    let mut style = Style::default();
+    if let Some(fg) = config.fg.as_deref() {
+        style = style.fg(parse_color(fg)?);
+    }
+    if let Some(bg) = config.bg.as_deref() {
+        style = style.bg(parse_color(bg)?);
+    }
+    for modifier in config.modifiers.iter() {
+        style = style.add_modifier(match modifier.to_lowercase().as_str() {
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underlined" => Modifier::UNDERLINED,
+            "reversed" => Modifier::REVERSED,
+            _ => return Err(eyre::eyre!("unknown modifier: {}", modifier)),
+        });
+    }
+    Ok(style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme() {
+        assert_eq!(parse_color("Light-Red").unwrap(), Color::LightRed);
+        assert_eq!(parse_color("#ff8000").unwrap(), Color::Rgb(255, 128, 0));
+        assert_eq!(parse_color("244").unwrap(), Color::Indexed(244));
+        assert!(parse_color("#ff80").is_err());
+
+        let mut config = ThemeConfig {
+            name: "high-contrast".to_owned(),
+            ..Default::default()
+        };
+        config.styles.insert(
+            "header".to_owned(),
+            StyleConfig {
+                fg: Some("red".to_owned()),
+                ..Default::default()
+            },
+        );
+        config.styles.insert(
+            "storage-class.standard-ia".to_owned(),
+            StyleConfig {
+                modifiers: vec!["dim".to_owned()],
+                ..Default::default()
+            },
+        );
+        config
+            .styles
+            .insert("footer".to_owned(), StyleConfig::default());
+
+        let (theme, problems) = Theme::new(&config, false);
+        assert_eq!(problems, vec!["unknown theme element footer".to_owned()]);
+        // 덮어쓰지 않은 속성은 theme 의 값을 유지한다
+        assert_eq!(
+            theme.header,
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(
+            theme.object_style(Some("STANDARD_IA")),
+            Style::default().add_modifier(Modifier::DIM)
+        );
+
+        let (theme, problems) = Theme::new(&config, true);
+        assert!(problems.len() == 1);
+        assert_eq!(theme.header, Style::default().add_modifier(Modifier::BOLD));
+    }
+}
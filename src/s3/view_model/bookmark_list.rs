@@ -0,0 +1,86 @@
+use tui::{
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, ListState},
+};
+
+use super::theme::Theme;
+use crate::s3::bookmark::Bookmark;
+use crate::StatefulList;
+
+pub struct BookmarkListViewModel {
+    list: StatefulList<Bookmark>,
+}
+
+impl BookmarkListViewModel {
+    pub fn new(bookmarks: Vec<Bookmark>) -> Self {
+        Self {
+            list: StatefulList::new(bookmarks),
+        }
+    }
+
+    pub fn next(&mut self) {
+        self.list.next();
+    }
+
+    pub fn previous(&mut self) {
+        self.list.previous();
+    }
+
+    pub fn selected(&self) -> Option<&Bookmark> {
+        self.list.selected()
+    }
+
+    // 목록을 다시 만들고 가능한 같은 위치를 선택한다
+    pub fn update(&mut self, bookmarks: Vec<Bookmark>) {
+        self.list.update(bookmarks);
+    }
+
+    pub fn make_list_view(&self, theme: &Theme) -> (List<'static>, ListState) {
+        let name_width = self
+            .list
+            .items()
+            .iter()
+            .map(|b| b.name.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let list_items: Vec<_> = self
+            .list
+            .items()
+            .iter()
+            .map(|b| {
+                let mut spans = vec![
+                    Span::styled(
+                        format!("{:width$} ", b.name, width = name_width),
+                        theme.prefix,
+                    ),
+                    Span::raw(b.s3_uri()),
+                ];
+                if let Some(profile) = b.profile.as_ref() {
+                    spans.push(Span::styled(format!(" ({})", profile), theme.date));
+                }
+                if let Some(endpoint_url) = b.endpoint_url.as_ref() {
+                    spans.push(Span::styled(format!(" [{}]", endpoint_url), theme.size));
+                }
+                if b.no_sign_request {
+                    spans.push(Span::styled(" (anonymous)", theme.date));
+                }
+                ListItem::new(Spans::from(spans))
+            })
+            .collect();
+
+        let list = List::new(list_items)
+            .block(
+                Block::default()
+                    .title("bookmarks (enter: open, d: delete, esc: close)")
+                    .borders(Borders::ALL),
+            )
+            .highlight_style(theme.highlight);
+
+        (list, self.list.state())
+    }
+
+    pub fn reset_state(&mut self, state: ListState) {
+        self.list.state = state;
+    }
+}
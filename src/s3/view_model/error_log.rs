@@ -0,0 +1,155 @@
+use aws_smithy_types::date_time::{DateTime, Format};
+use std::time::SystemTime;
+use tui::{
+    text::{Span, Spans, Text},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+};
+
+use super::theme::Theme;
+use crate::s3::client::request_error::RequestError;
+use crate::StatefulList;
+
+// status line 에 보여준 error. 다음 키를 누르면 status 는 사라지지만 error log 에는 남는다
+#[derive(Clone, Debug, PartialEq)]
+pub struct ErrorLogEntry {
+    pub time: String,
+    pub message: String,
+    // 응답의 code, http status, request id 와 원인
+    pub details: Vec<String>,
+}
+
+impl ErrorLogEntry {
+    pub fn new(message: String) -> Self {
+        Self {
+            time: DateTime::from(SystemTime::now())
+                .fmt(Format::DateTime)
+                .unwrap_or_default(),
+            message,
+            details: vec![],
+        }
+    }
+
+    pub fn from_report(e: &eyre::Report) -> Self {
+        let mut entry = Self::new(e.to_string());
+        if let Some(request) = e.chain().find_map(|c| c.downcast_ref::<RequestError>()) {
+            let fields = [
+                ("code", request.code.clone()),
+                ("http status", request.status.map(|s| s.to_string())),
+                ("request id", request.request_id.clone()),
+                ("extended request id", request.extended_request_id.clone()),
+                ("denied action", request.action.clone()),
+                ("message", request.service_message.clone()),
+            ];
+            entry.details.extend(
+                fields
+                    .into_iter()
+                    .filter_map(|(name, value)| Some(format!("{}: {}", name, value?))),
+            );
+        }
+        entry.details.extend(
+            e.chain()
+                .skip(1)
+                .map(|cause| format!("caused by: {}", cause)),
+        );
+        entry
+    }
+
+    // clipboard 에 복사할 내용
+    pub fn text(&self) -> String {
+        std::iter::once(format!("{} {}", self.time, self.message))
+            .chain(self.details.iter().cloned())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+pub struct ErrorLogViewModel {
+    list: StatefulList<ErrorLogEntry>,
+}
+
+impl ErrorLogViewModel {
+    // 최근 error 부터 보여준다
+    pub fn new(entries: &[ErrorLogEntry]) -> Self {
+        Self {
+            list: StatefulList::new(entries.iter().rev().cloned().collect()),
+        }
+    }
+
+    pub fn next(&mut self) {
+        self.list.next();
+    }
+
+    pub fn previous(&mut self) {
+        self.list.previous();
+    }
+
+    pub fn selected(&self) -> Option<&ErrorLogEntry> {
+        self.list.selected()
+    }
+
+    pub fn make_list_view(&self, theme: &Theme) -> (List<'static>, ListState) {
+        let list_items: Vec<_> = self
+            .list
+            .items()
+            .iter()
+            .map(|e| {
+                ListItem::new(Spans::from(vec![
+                    Span::styled(format!("{} ", e.time), theme.date),
+                    Span::styled(e.message.clone(), theme.error),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(list_items)
+            .block(
+                Block::default()
+                    .title("errors (y: copy details, esc: close)")
+                    .borders(Borders::ALL),
+            )
+            .highlight_style(theme.highlight);
+
+        (list, self.list.state())
+    }
+
+    // 선택한 error 의 전체 내용
+    pub fn make_details_view(&self, theme: &Theme) -> Paragraph<'static> {
+        let text = match self.selected() {
+            Some(entry) => {
+                let mut lines = vec![Spans::from(Span::styled(
+                    entry.message.clone(),
+                    theme.error,
+                ))];
+                lines.extend(entry.details.iter().map(|d| Spans::from(d.clone())));
+                Text::from(lines)
+            }
+            None => Text::from("no errors"),
+        };
+        Paragraph::new(text)
+            .block(Block::default().title("details").borders(Borders::ALL))
+            .wrap(Wrap { trim: false })
+    }
+
+    pub fn reset_state(&mut self, state: ListState) {
+        self.list.state = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_log_entry() {
+        let e = eyre::eyre!("connection refused").wrap_err("can't list buckets");
+        let entry = ErrorLogEntry::from_report(&e);
+        assert_eq!(entry.message, "can't list buckets");
+        assert_eq!(entry.details, vec!["caused by: connection refused"]);
+        assert!(entry
+            .text()
+            .ends_with("can't list buckets\ncaused by: connection refused"));
+
+        // 최근 error 를 먼저 선택한다
+        let vm = ErrorLogViewModel::new(&[entry, ErrorLogEntry::new("latest".to_owned())]);
+        assert_eq!(vm.selected().map(|e| e.message.as_str()), Some("latest"));
+    }
+}
@@ -0,0 +1,114 @@
+use tui::{
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+
+use super::theme::Theme;
+use crate::s3::frecency::Visit;
+use crate::StatefulList;
+
+// 방문 기록을 keyword 로 걸러 이동할 prefix 를 고른다
+pub struct FrecencyJumpViewModel {
+    // 점수 순서로 정렬된 전체 방문 기록
+    visits: Vec<Visit>,
+    query: String,
+    // visits 의 index
+    matched: StatefulList<usize>,
+}
+
+impl FrecencyJumpViewModel {
+    pub fn new(visits: Vec<Visit>) -> Self {
+        let mut vm = Self {
+            visits,
+            query: String::default(),
+            matched: StatefulList::new(vec![]),
+        };
+        vm.filter();
+        vm
+    }
+
+    pub fn keywords(&self) -> Vec<String> {
+        self.query
+            .split_whitespace()
+            .map(|k| k.to_owned())
+            .collect()
+    }
+
+    pub fn push_query(&mut self, c: char) {
+        self.query.push(c);
+        self.filter();
+    }
+
+    pub fn pop_query(&mut self) {
+        self.query.pop();
+        self.filter();
+    }
+
+    pub fn next(&mut self) {
+        self.matched.next();
+    }
+
+    pub fn previous(&mut self) {
+        self.matched.previous();
+    }
+
+    pub fn selected(&self) -> Option<&Visit> {
+        self.matched.selected().map(|i| &self.visits[*i])
+    }
+
+    fn filter(&mut self) {
+        let keywords = self.keywords();
+        let matched = self
+            .visits
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.matches(&keywords))
+            .map(|(i, _)| i)
+            .collect();
+        self.matched.update(matched);
+        self.matched.first();
+    }
+
+    pub fn make_query_view(&self, theme: &Theme) -> Paragraph<'_> {
+        let title = format!(
+            "jump ({}/{})",
+            self.matched.items().len(),
+            self.visits.len()
+        );
+
+        Paragraph::new(Spans::from(vec![
+            Span::styled("z ", theme.prompt),
+            Span::raw(self.query.as_str()),
+        ]))
+        .block(Block::default().title(title).borders(Borders::ALL))
+    }
+
+    pub fn make_matched_list_view(&self, theme: &Theme) -> (List<'static>, ListState) {
+        let list_items: Vec<_> = self
+            .matched
+            .items()
+            .iter()
+            .map(|i| {
+                let visit = &self.visits[*i];
+                let mut spans = vec![Span::raw(visit.s3_uri())];
+                if let Some(profile) = visit.profile.as_ref() {
+                    spans.push(Span::styled(format!(" ({})", profile), theme.date));
+                }
+                if let Some(endpoint_url) = visit.endpoint_url.as_ref() {
+                    spans.push(Span::styled(format!(" [{}]", endpoint_url), theme.size));
+                }
+                ListItem::new(Spans::from(spans))
+            })
+            .collect();
+
+        let list = List::new(list_items)
+            .block(Block::default().borders(Borders::LEFT | Borders::RIGHT | Borders::BOTTOM))
+            .highlight_style(theme.highlight);
+
+        (list, self.matched.state())
+    }
+
+    pub fn reset_state(&mut self, state: ListState) {
+        self.matched.state = state;
+    }
+}
@@ -0,0 +1,218 @@
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use std::cmp::Reverse;
+use tui::{
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+
+use super::theme::Theme;
+use crate::StatefulList;
+
+// 화면에 보여줄 최대 검색 결과 수
+const MAX_MATCHED_KEYS: usize = 1000;
+
+// bucket 혹은 prefix 아래의 모든 key 를 fuzzy 하게 검색한다
+pub struct FuzzyFinderViewModel {
+    bucket: String,
+    prefix: String,
+    keys: Vec<String>,
+    query: String,
+    // query 에 맞는 key 의 (점수, 길이, keys 의 index). 점수가 높고 짧은 key 가 앞에 오며
+    // 보여줄 수만큼만 가지고 있는다
+    ranked: Vec<(Reverse<i64>, usize, usize)>,
+    // ranked 의 keys index
+    matched: StatefulList<usize>,
+    walking: bool,
+    // 조회하지 못한 이유. 그때까지 찾은 key 만 검색한다
+    error: Option<String>,
+    matcher: SkimMatcherV2,
+}
+
+impl FuzzyFinderViewModel {
+    pub fn new(bucket: String, prefix: String) -> Self {
+        Self {
+            bucket,
+            prefix,
+            keys: vec![],
+            query: String::default(),
+            ranked: vec![],
+            matched: StatefulList::new(vec![]),
+            walking: true,
+            error: None,
+            matcher: SkimMatcherV2::default().smart_case(),
+        }
+    }
+
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    // 새로 받은 key 만 점수를 매겨 결과에 합친다
+    pub fn extend_keys(&mut self, keys: Vec<String>, done: bool) {
+        let start = self.keys.len();
+        self.keys.extend(keys);
+        self.walking = !done;
+        self.rank(start);
+    }
+
+    pub fn fail(&mut self, error: String) {
+        self.walking = false;
+        self.error = Some(error);
+    }
+
+    pub fn push_query(&mut self, c: char) {
+        self.query.push(c);
+        self.rerank();
+    }
+
+    pub fn pop_query(&mut self) {
+        self.query.pop();
+        self.rerank();
+    }
+
+    pub fn next(&mut self) {
+        self.matched.next();
+    }
+
+    pub fn previous(&mut self) {
+        self.matched.previous();
+    }
+
+    pub fn selected_key(&self) -> Option<&str> {
+        self.matched.selected().map(|i| self.keys[*i].as_str())
+    }
+
+    fn relative_key<'a>(&self, key: &'a str) -> &'a str {
+        key.strip_prefix(self.prefix.as_str()).unwrap_or(key)
+    }
+
+    // query 가 없다면 조회된 순서대로 보여준다
+    fn score(&self, i: usize) -> Option<(Reverse<i64>, usize, usize)> {
+        if self.query.is_empty() {
+            return Some((Reverse(0), 0, i));
+        }
+        let key = &self.keys[i];
+        self.matcher
+            .fuzzy_match(self.relative_key(key), &self.query)
+            .map(|score| (Reverse(score), key.len(), i))
+    }
+
+    // query 가 바뀌면 모든 key 의 점수를 다시 매기고 첫 결과를 선택한다
+    fn rerank(&mut self) {
+        self.ranked.clear();
+        self.rank(0);
+        self.matched.first();
+    }
+
+    // start 번째부터의 key 를 결과에 합친다. 선택한 key 가 결과에 남아있다면 계속 선택한다
+    fn rank(&mut self, start: usize) {
+        let selected = self.matched.selected().copied();
+        let scored: Vec<_> = (start..self.keys.len())
+            .filter_map(|i| self.score(i))
+            .collect();
+        self.ranked.extend(scored);
+        self.ranked.sort_unstable();
+        self.ranked.truncate(MAX_MATCHED_KEYS);
+
+        self.matched
+            .update(self.ranked.iter().map(|(_, _, i)| *i).collect());
+        let position = selected.and_then(|s| self.matched.items().iter().position(|i| *i == s));
+        match position {
+            Some(position) => self.matched.state.select(Some(position)),
+            None => self.matched.first(),
+        }
+    }
+
+    pub fn make_query_view(&self, theme: &Theme) -> Paragraph<'_> {
+        let walk_state = match (&self.error, self.walking) {
+            (Some(error), _) => format!(", failed: {}", error),
+            (None, true) => ", walking…".to_owned(),
+            (None, false) => String::default(),
+        };
+        let title = format!(
+            "s3://{}/{} ({}/{}{})",
+            self.bucket,
+            self.prefix,
+            self.matched.items().len(),
+            self.keys.len(),
+            walk_state
+        );
+
+        Paragraph::new(Spans::from(vec![
+            Span::styled("> ", theme.prompt),
+            Span::raw(self.query.as_str()),
+        ]))
+        .block(Block::default().title(title).borders(Borders::ALL))
+    }
+
+    pub fn make_matched_list_view(&self, theme: &Theme) -> (List<'static>, ListState) {
+        let list_items: Vec<_> = self
+            .matched
+            .items()
+            .iter()
+            .map(|i| ListItem::new(self.relative_key(&self.keys[*i]).to_owned()))
+            .collect();
+
+        let list = List::new(list_items)
+            .block(Block::default().borders(Borders::LEFT | Borders::RIGHT | Borders::BOTTOM))
+            .highlight_style(theme.highlight);
+
+        (list, self.matched.state())
+    }
+
+    pub fn reset_state(&mut self, state: ListState) {
+        self.matched.state = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_finder_rank() {
+        let mut vm = FuzzyFinderViewModel::new("bucket".to_owned(), "logs/".to_owned());
+        assert_eq!(vm.selected_key(), None);
+
+        vm.extend_keys(
+            vec![
+                "logs/2021/app.log".to_owned(),
+                "logs/2021/12/error.log".to_owned(),
+            ],
+            false,
+        );
+        // query 가 없다면 조회된 순서대로 보여준다
+        assert_eq!(vm.selected_key(), Some("logs/2021/app.log"));
+
+        vm.push_query('e');
+        vm.push_query('r');
+        vm.push_query('r');
+        assert_eq!(vm.selected_key(), Some("logs/2021/12/error.log"));
+
+        // 조회가 진행되는 동안 추가된 key 도 검색 결과에 반영된다. 더 앞에 오더라도 선택은 그대로다
+        vm.extend_keys(vec!["logs/err".to_owned()], true);
+        assert_eq!(vm.matched.items().len(), 2);
+        assert_eq!(vm.selected_key(), Some("logs/2021/12/error.log"));
+        vm.previous();
+        assert_eq!(vm.selected_key(), Some("logs/err"));
+
+        // prefix 는 검색 대상이 아니다
+        vm.pop_query();
+        vm.pop_query();
+        vm.pop_query();
+        vm.push_query('l');
+        vm.push_query('o');
+        vm.push_query('g');
+        vm.push_query('s');
+        assert_eq!(vm.selected_key(), None);
+
+        // 실패하면 조회를 끝내고 이유를 제목에 보여준다
+        vm.fail("AccessDenied".to_owned());
+        assert!(!vm.walking);
+        assert_eq!(vm.error.as_deref(), Some("AccessDenied"));
+    }
+}
@@ -0,0 +1,82 @@
+use tui::widgets::ListState;
+
+// 기억할 최대 history 수
+const MAX_HISTORY: usize = 100;
+
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+    // None 은 bucket 목록이다
+    pub location: Option<(String, String)>,
+    // 선택된 행과 scroll 위치
+    pub state: ListState,
+}
+
+// 상위/하위 관계와 관계없이 방문한 순서대로 이동하는 history
+#[derive(Default)]
+pub struct NavigationHistory {
+    back: Vec<HistoryEntry>,
+    forward: Vec<HistoryEntry>,
+}
+
+impl NavigationHistory {
+    // 새로운 위치로 이동하기 전의 위치를 기록한다
+    pub fn record(&mut self, entry: HistoryEntry) {
+        if self.back.last().map(|e| &e.location) != Some(&entry.location) {
+            self.back.push(entry);
+            if self.back.len() > MAX_HISTORY {
+                self.back.remove(0);
+            }
+        }
+        self.forward.clear();
+    }
+
+    pub fn back(&mut self, current: HistoryEntry) -> Option<HistoryEntry> {
+        let entry = self.back.pop()?;
+        self.forward.push(current);
+        Some(entry)
+    }
+
+    pub fn forward(&mut self, current: HistoryEntry) -> Option<HistoryEntry> {
+        let entry = self.forward.pop()?;
+        self.back.push(current);
+        Some(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_navigation_history() {
+        let entry = |prefix: &str, selected: usize| {
+            let mut state = ListState::default();
+            state.select(Some(selected));
+            HistoryEntry {
+                location: Some(("bucket".to_owned(), prefix.to_owned())),
+                state,
+            }
+        };
+
+        // (prefix, 선택된 행)
+        let summary = |entry: Option<HistoryEntry>| {
+            entry.map(|e| (e.location.unwrap().1, e.state.selected().unwrap()))
+        };
+        let expected = |prefix: &str, selected: usize| Some((prefix.to_owned(), selected));
+
+        let mut history = NavigationHistory::default();
+        assert_eq!(summary(history.back(entry("a/", 0))), None);
+
+        history.record(entry("a/", 3));
+        history.record(entry("b/", 1));
+        assert_eq!(summary(history.back(entry("c/", 2))), expected("b/", 1));
+        assert_eq!(summary(history.back(entry("b/", 1))), expected("a/", 3));
+        assert_eq!(summary(history.forward(entry("a/", 4))), expected("b/", 1));
+
+        // 새로운 위치로 이동하면 forward history 는 사라진다
+        history.record(entry("b/", 1));
+        assert_eq!(summary(history.forward(entry("d/", 0))), None);
+        assert_eq!(summary(history.back(entry("d/", 0))), expected("b/", 1));
+        assert_eq!(summary(history.back(entry("b/", 1))), expected("a/", 4));
+    }
+}
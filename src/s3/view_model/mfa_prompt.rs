@@ -0,0 +1,63 @@
+use tui::{
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use super::theme::Theme;
+use crate::s3::client::S3ClientConfig;
+
+// mfa 장치의 code 는 6 자리 숫자다
+const CODE_LENGTH: usize = 6;
+
+// role 을 assume 하기 전에 mfa code 를 입력받는다
+pub struct MfaPromptViewModel {
+    pub mfa_serial: String,
+    // code 를 입력받은 뒤 tab_id 번 tab 을 config 로 연결하고 location 으로 이동한다
+    pub tab_id: usize,
+    pub config: S3ClientConfig,
+    pub location: Option<String>,
+    code: String,
+}
+
+impl MfaPromptViewModel {
+    pub fn new(
+        mfa_serial: String,
+        tab_id: usize,
+        config: S3ClientConfig,
+        location: Option<String>,
+    ) -> Self {
+        Self {
+            mfa_serial,
+            tab_id,
+            config,
+            location,
+            code: String::default(),
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        if c.is_ascii_digit() && self.code.len() < CODE_LENGTH {
+            self.code.push(c);
+        }
+    }
+
+    pub fn pop(&mut self) {
+        self.code.pop();
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn make_view(&self, theme: &Theme) -> Paragraph<'_> {
+        let title = format!(
+            "MFA code for {} (enter: submit, esc: cancel)",
+            self.mfa_serial
+        );
+        Paragraph::new(Spans::from(vec![
+            Span::styled("> ", theme.prompt),
+            Span::raw(self.code.as_str()),
+        ]))
+        .block(Block::default().title(title).borders(Borders::ALL))
+    }
+}
@@ -1,52 +1,84 @@
-use aws_sdk_s3::output::ListObjectsV2Output;
+use aws_sdk_s3::{model::BucketLocationConstraint, output::ListObjectsV2Output};
 use tui::{
-    style::{Color, Style},
-    text::{Span, Text},
+    layout::Rect,
+    text::{Span, Spans, Text},
     widgets::{Block, Borders, List, ListState, Paragraph},
 };
 
 pub use super::*;
-use super::{client::BucketWithLocation, S3Item};
-
+use backend::Scheme;
+use command::SortKey;
+use connection::Connection;
+use history::HistoryEntry;
+use theme::Theme;
+
+pub mod bookmark_list;
+pub mod error_log;
+pub mod frecency_jump;
+pub mod fuzzy_finder;
+pub mod history;
+pub mod mfa_prompt;
+pub mod profile_list;
 pub mod ui_converter;
 use crate::StatefulList;
 
+// area 의 가운데에 popup 을 그릴 영역을 구한다
+pub fn popup_rect(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let width = area.width * percent_x / 100;
+    let height = area.height * percent_y / 100;
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
 #[derive(Debug, PartialEq)]
 pub enum S3OutputType {
+    Connections,
     Buckets,
     Objects,
 }
 
 #[derive(Debug)]
 pub enum S3Output {
-    Buckets(Vec<BucketWithLocation>),
-    Objects(ListObjectsV2Output),
+    // 설정 파일의 연결 목록. bucket 목록보다 위에 있다
+    Connections(Vec<Connection>),
+    Buckets(Vec<client::BucketWithLocation>),
+    Objects(Box<ListObjectsV2Output>),
 }
 
 impl S3Output {
     fn output_type(&self) -> S3OutputType {
         match self {
-            &S3Output::Buckets(_) => S3OutputType::Buckets,
-            &S3Output::Objects(_) => S3OutputType::Objects,
+            S3Output::Connections(_) => S3OutputType::Connections,
+            S3Output::Buckets(_) => S3OutputType::Buckets,
+            S3Output::Objects(_) => S3OutputType::Objects,
         }
     }
 
     pub fn bucket_and_prefix(&self) -> Option<(String, String)> {
         match &self {
-            S3Output::Buckets(_) => None,
+            S3Output::Connections(_) | S3Output::Buckets(_) => None,
             S3Output::Objects(o) => Some((
-                o.name().map(|o| o.to_owned()).unwrap_or(String::default()),
-                o.prefix()
-                    .map(|o| o.to_owned())
-                    .unwrap_or(String::default()),
+                o.name().map(|o| o.to_owned()).unwrap_or_default(),
+                o.prefix().map(|o| o.to_owned()).unwrap_or_default(),
             )),
         }
     }
 }
 
+// (정렬 기준, 역순 여부)
+pub type SortOrder = (SortKey, bool);
+
 pub struct S3ItemViewModel {
     list: StatefulList<S3Item>,
     output: S3Output,
+    sort_order: Option<SortOrder>,
+    filter: Option<String>,
+    // bucket 목록 위에 연결 목록이 있다면 .. 을 보여준다
+    has_connections: bool,
 }
 
 impl S3ItemViewModel {
@@ -54,10 +86,14 @@ impl S3ItemViewModel {
         &self.list
     }
 
-    fn make_s3_item_from_buckets(output: &Vec<BucketWithLocation>) -> Vec<S3Item> {
-        output
-            .iter()
-            .map(|b| S3Item::Bucket(b.to_owned()))
+    fn make_s3_item_from_buckets(
+        output: &[client::BucketWithLocation],
+        has_connections: bool,
+    ) -> Vec<S3Item> {
+        has_connections
+            .then_some(S3Item::Pop)
+            .into_iter()
+            .chain(output.iter().map(|b| S3Item::Bucket(b.to_owned())))
             .collect()
     }
 
@@ -80,20 +116,119 @@ impl S3ItemViewModel {
             .collect()
     }
 
-    fn make_s3_item_from_output(s3_output: &S3Output) -> Vec<S3Item> {
+    fn make_s3_item_from_output(s3_output: &S3Output, has_connections: bool) -> Vec<S3Item> {
         match s3_output {
-            S3Output::Buckets(output) => Self::make_s3_item_from_buckets(&output),
-            S3Output::Objects(output) => Self::make_s3_item_from_objects(&output),
+            S3Output::Connections(connections) => connections
+                .iter()
+                .map(|c| S3Item::Connection(c.clone()))
+                .collect(),
+            S3Output::Buckets(output) => Self::make_s3_item_from_buckets(output, has_connections),
+            S3Output::Objects(output) => Self::make_s3_item_from_objects(output),
         }
     }
 
-    pub fn new(s3_output: S3Output) -> Self {
-        Self {
-            list: StatefulList::new(Self::make_s3_item_from_output(&s3_output)),
+    pub fn new(s3_output: S3Output, sort_order: Option<SortOrder>, has_connections: bool) -> Self {
+        let mut vm = Self {
+            list: StatefulList::new(vec![]),
             output: s3_output,
+            sort_order,
+            filter: None,
+            has_connections,
+        };
+        vm.list = StatefulList::new(vm.make_s3_items());
+        vm
+    }
+
+    fn make_s3_items(&self) -> Vec<S3Item> {
+        let mut items: Vec<_> = Self::make_s3_item_from_output(&self.output, self.has_connections)
+            .into_iter()
+            .filter(|item| self.is_shown(item))
+            .collect();
+        self.sort_items(&mut items);
+        items
+    }
+
+    fn is_shown(&self, item: &S3Item) -> bool {
+        match (&self.filter, item) {
+            (_, S3Item::Pop) | (None, _) => true,
+            (Some(filter), item) => item.is_matched(filter),
+        }
+    }
+
+    // 정렬된 목록 뒤에 더한 항목도 제자리를 찾는다. 이미 정렬된 부분은 다시 비교하지 않는다
+    fn sort_items(&self, items: &mut [S3Item]) {
+        match self.sort_order {
+            Some((sort_key, reverse)) => items.sort_by(|a, b| {
+                let ordering = a.compare(b, sort_key);
+                if reverse && a.get_type() == b.get_type() {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            }),
+            // 조회한 순서를 따르되 prefix 는 object 보다 앞에 온다
+            None => items.sort_by_key(|item| item.get_type()),
+        }
+    }
+
+    // 이어서 받은 페이지의 항목을 더한다. 선택한 항목은 그대로 선택한다
+    fn append_page(&mut self, page: ListObjectsV2Output) {
+        let new_items: Vec<_> = Self::make_s3_item_from_objects(&page)
+            .into_iter()
+            .filter(|item| *item != S3Item::Pop && self.is_shown(item))
+            .collect();
+        let output = match &mut self.output {
+            S3Output::Objects(output) => output,
+            _ => return,
+        };
+        output
+            .common_prefixes
+            .get_or_insert_with(Vec::new)
+            .extend(page.common_prefixes.unwrap_or_default());
+        output
+            .contents
+            .get_or_insert_with(Vec::new)
+            .extend(page.contents.unwrap_or_default());
+
+        let selected = self.selected().cloned();
+        let mut items = std::mem::take(&mut self.list.items);
+        items.extend(new_items);
+        self.sort_items(&mut items);
+        self.list.update(items);
+        // 앞에 prefix 가 더해졌다면 선택한 항목이 밀려났다
+        if let (Some(selected), Some(i)) = (selected, self.list.selected_index()) {
+            if self.list.items()[i] != selected {
+                let position = self.list.items().iter().position(|item| *item == selected);
+                self.list.state.select(position.or(Some(i)));
+            }
+        }
+    }
+
+    // 정렬 혹은 filter 가 바뀌면 선택된 item 을 유지한 채 목록을 다시 만든다
+    fn rebuild_items(&mut self) {
+        let selected = self.selected().cloned();
+        self.list.update(self.make_s3_items());
+        if let Some(i) = selected.and_then(|s| self.list.items().iter().position(|i| *i == s)) {
+            self.list.state.select(Some(i));
+        } else if self.list.selected_index().is_none() {
+            self.list.first();
         }
     }
 
+    pub fn set_sort_order(&mut self, sort_order: Option<SortOrder>) {
+        self.sort_order = sort_order;
+        self.rebuild_items();
+    }
+
+    pub fn set_filter(&mut self, filter: Option<String>) {
+        self.filter = filter;
+        self.rebuild_items();
+    }
+
+    pub fn filter(&self) -> Option<&str> {
+        self.filter.as_deref()
+    }
+
     pub fn search_next(&mut self, search_text: &str) {
         let matched_indexes: Vec<_> = self
             .list
@@ -111,68 +246,312 @@ impl S3ItemViewModel {
 
         let next_matched = matched_indexes
             .iter()
-            .filter(|i| **i > self.list.selected_index().unwrap_or_default())
-            .next();
+            .find(|i| **i > self.list.selected_index().unwrap_or_default());
 
-        let first_matched = matched_indexes.iter().next();
+        let first_matched = matched_indexes.first();
 
-        if let Some(i) = next_matched.or(first_matched).map(|i| *i) {
+        if let Some(i) = next_matched.or(first_matched).copied() {
             self.list.state.select(Some(i));
         }
     }
 
     pub fn update_output(&mut self, s3_output: S3Output) {
         assert_eq!(self.output.output_type(), s3_output.output_type());
-        self.list.update(Self::make_s3_item_from_output(&s3_output));
         self.output = s3_output;
+        self.list.update(self.make_s3_items());
     }
 
     pub fn output(&self) -> &S3Output {
         &self.output
     }
 
+    // 조회중이던 bucket 의 location 을 바꾼다. 정렬에 location 을 쓰지 않으므로 선택 위치는 그대로다
+    fn set_bucket_location(&mut self, bucket: &str, location: &BucketLocationConstraint) -> bool {
+        let buckets = match &mut self.output {
+            S3Output::Buckets(buckets) => buckets,
+            _ => return false,
+        };
+        let resolving = buckets
+            .iter_mut()
+            .find(|b| b.bucket.name() == Some(bucket) && b.is_resolving());
+        match resolving {
+            Some(b) => b.location = location.clone(),
+            None => return false,
+        }
+        self.list.update(self.make_s3_items());
+        true
+    }
+
     pub fn selected(&self) -> Option<&S3Item> {
         self.list.selected()
     }
+
+    pub fn select_key(&mut self, key: &str) -> bool {
+        let position = self
+            .list
+            .items()
+            .iter()
+            .position(|item| matches!(item, S3Item::Object(o) if o.key() == Some(key)));
+
+        if let Some(i) = position {
+            self.list.state.select(Some(i));
+        }
+        position.is_some()
+    }
+
+    pub fn select_connection(&mut self, name: &str) {
+        let position = self
+            .list
+            .items()
+            .iter()
+            .position(|item| matches!(item, S3Item::Connection(c) if c.name == name));
+        if let Some(i) = position {
+            self.list.state.select(Some(i));
+        }
+    }
 }
 
 pub struct S3ItemsViewModel {
     pub list_stack: Vec<S3ItemViewModel>,
+    // 모든 목록에 적용되는 정렬 기준
+    sort_order: Option<SortOrder>,
+    // uri 를 만들 때 사용한다
+    scheme: Scheme,
+    // header 에 보여줄 s3 profile. s3 가 아니라면 None
+    profile: Option<String>,
+    // credential 을 준 provider. 아직 모른다면 None
+    credentials_source: Option<String>,
+    // tls 인증서를 검증하지 않는 연결이라면 header 에서 경고한다
+    tls_unverified: bool,
+    // 설정 파일에 연결이 있다면 bucket 목록에서 .. 으로 연결 목록에 갈 수 있다
+    has_connections: bool,
+    // 현재 저장소가 설정 파일의 연결이라면 그 연결
+    connection: Option<Connection>,
+    // 진행중인 목록 조회. header 에 spinner 와 받은 페이지 수를 보여준다
+    loading: Option<Loading>,
+}
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Loading {
+    pages: usize,
+    frame: usize,
+}
+
+impl Default for S3ItemsViewModel {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl S3ItemsViewModel {
     pub fn new() -> Self {
-        Self { list_stack: vec![] }
+        Self {
+            list_stack: vec![],
+            sort_order: None,
+            scheme: Scheme::S3,
+            profile: None,
+            credentials_source: None,
+            tls_unverified: false,
+            has_connections: false,
+            connection: None,
+            loading: None,
+        }
+    }
+
+    pub fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    pub fn set_scheme(&mut self, scheme: Scheme) {
+        self.scheme = scheme;
+    }
+
+    pub fn set_profile(&mut self, profile: Option<String>) {
+        self.profile = profile;
+    }
+
+    pub fn set_credentials_source(&mut self, credentials_source: Option<String>) {
+        self.credentials_source = credentials_source;
+    }
+
+    pub fn set_tls_unverified(&mut self, tls_unverified: bool) {
+        self.tls_unverified = tls_unverified;
+    }
+
+    // 조회를 시작하면 Some(0), 페이지를 받을 때마다 받은 페이지 수, 끝나면 None
+    pub fn set_loading(&mut self, pages: Option<usize>) {
+        let frame = self.loading.map(|l| l.frame).unwrap_or(0);
+        self.loading = pages.map(|pages| Loading { pages, frame });
+    }
+
+    // spinner 를 한 칸 돌린다. 조회중이 아니라면 false
+    pub fn tick_loading(&mut self) -> bool {
+        match self.loading.as_mut() {
+            Some(loading) => {
+                loading.frame = (loading.frame + 1) % SPINNER.len();
+                true
+            }
+            None => false,
+        }
+    }
+
+    // 지금 조회중인 페이지를 보여준다
+    fn loading_label(&self) -> Option<String> {
+        self.loading.map(|l| {
+            format!(
+                "[{} loading page {} (esc: cancel)]    ",
+                SPINNER[l.frame],
+                l.pages + 1
+            )
+        })
+    }
+
+    pub fn set_has_connections(&mut self, has_connections: bool) {
+        self.has_connections = has_connections;
+        for item in self.list_stack.iter_mut() {
+            if item.has_connections != has_connections {
+                item.has_connections = has_connections;
+                item.rebuild_items();
+            }
+        }
+    }
+
+    pub fn connection(&self) -> Option<&Connection> {
+        self.connection.as_ref()
+    }
+
+    pub fn set_connection(&mut self, connection: Option<Connection>) {
+        self.connection = connection;
+    }
+
+    // 연결 목록에서 현재 연결을 선택한다
+    pub fn select_connection(&mut self) {
+        if let (Some(connection), Some(item)) =
+            (self.connection.as_ref(), self.list_stack.last_mut())
+        {
+            item.select_connection(&connection.name);
+        }
+    }
+
+    // location 을 아직 조회중인 bucket
+    pub fn resolving_buckets(&self) -> Vec<String> {
+        self.list_stack
+            .iter()
+            .flat_map(|i| match i.output() {
+                S3Output::Buckets(buckets) => buckets.as_slice(),
+                _ => &[],
+            })
+            .filter(|b| b.is_resolving())
+            .filter_map(|b| b.bucket.name().map(|n| n.to_owned()))
+            .collect()
+    }
+
+    // 조회가 끝난 location 을 bucket 목록에 반영하고 다시 그려야 하는지 돌려준다
+    pub fn set_bucket_location(
+        &mut self,
+        bucket: &str,
+        location: &BucketLocationConstraint,
+    ) -> bool {
+        let mut updated = false;
+        for item in self.list_stack.iter_mut() {
+            updated |= item.set_bucket_location(bucket, location);
+        }
+        updated
+    }
+
+    // 연결 목록을 보여주고 있는지
+    pub fn showing_connections(&self) -> bool {
+        self.list_stack
+            .last()
+            .is_some_and(|i| i.output().output_type() == S3OutputType::Connections)
+    }
+
+    pub fn sort_order(&self) -> Option<SortOrder> {
+        self.sort_order
+    }
+
+    pub fn set_sort_order(&mut self, sort_order: Option<SortOrder>) {
+        self.sort_order = sort_order;
+        for item in self.list_stack.iter_mut() {
+            item.set_sort_order(sort_order);
+        }
+    }
+
+    // 현재 목록에만 filter 를 적용한다
+    pub fn set_filter(&mut self, filter: Option<String>) {
+        if let Some(item) = self.list_stack.last_mut() {
+            item.set_filter(filter);
+        }
+    }
+
+    pub fn items(&self) -> Vec<S3Item> {
+        self.list_stack
+            .last()
+            .map(|i| i.items().items().clone())
+            .unwrap_or_default()
     }
 
-    pub fn make_selected_s3_item_view(&self) -> Paragraph {
+    pub fn make_selected_s3_item_view(&self, theme: &Theme) -> Paragraph<'_> {
         // selected_s3_uri_view
-        let selected_s3_uri_view = Text::from(Span::styled(
-            self.selected_s3_uri(),
-            Style::default().fg(Color::Black),
-        ));
+        let selected_s3_uri_view = Text::from(Span::raw(self.selected_s3_uri()));
 
-        Paragraph::new(selected_s3_uri_view).style(Style::default().bg(Color::Yellow))
+        Paragraph::new(selected_s3_uri_view).style(theme.status_bar)
     }
 
-    pub fn make_currenent_common_prefix_view(&self) -> Paragraph {
-        let current_search_target = if let Some((bucket, prefix)) = self.bucket_and_prefix() {
-            format!("s3://{}/{}    ", bucket, prefix)
+    pub fn make_currenent_common_prefix_view(&self, theme: &Theme) -> Paragraph<'_> {
+        let mut current_search_target = if let Some((bucket, prefix)) = self.bucket_and_prefix() {
+            format!("{}    ", self.scheme.uri(&bucket, &prefix))
+        } else if self.showing_connections() {
+            "connection selection    ".to_owned()
         } else {
             "bucket selection    ".to_owned()
         };
+        if let Some(loading) = self.loading_label() {
+            current_search_target += &loading;
+        }
+        if let Some(profile) = self.profile.as_ref() {
+            current_search_target += &format!("[profile: {}]    ", profile);
+        }
+        if let Some(source) = self.credentials_source.as_ref() {
+            current_search_target += &format!("[credentials: {}]    ", source);
+        }
+        if self.tls_unverified {
+            current_search_target += "[WARNING: TLS certificates aren't verified]    ";
+        }
+        if let Some((sort_key, reverse)) = self.sort_order {
+            current_search_target += &format!(
+                "[sort: {}{}]    ",
+                sort_key,
+                if reverse { " desc" } else { "" }
+            );
+        }
+        if let Some(filter) = self.list_stack.last().and_then(|i| i.filter()) {
+            current_search_target += &format!("[filter: {}]    ", filter);
+        }
 
-        Paragraph::new("")
-            .style(Style::default().fg(Color::Cyan))
-            .block(
-                Block::default()
-                    .title(current_search_target)
-                    .borders(Borders::BOTTOM),
-            )
+        // 연결의 이름은 그 연결의 색으로 보여준다
+        let mut title = vec![];
+        if let Some(connection) = self.connection.as_ref() {
+            let style = match connection.color {
+                Some(color) => theme.header.fg(color),
+                None => theme.header,
+            };
+            title.push(Span::styled(format!("[{}]", connection.name), style));
+            title.push(Span::styled(" ", theme.header));
+        }
+        title.push(Span::styled(current_search_target, theme.header));
+
+        Paragraph::new("").style(theme.header).block(
+            Block::default()
+                .title(Spans::from(title))
+                .borders(Borders::BOTTOM),
+        )
     }
 
-    pub fn make_item_list_view(&self) -> Option<(List<'static>, ListState)> {
-        self.list_stack.last().map(|i| i.into())
+    pub fn make_item_list_view(&self, theme: &Theme) -> Option<(List<'static>, ListState)> {
+        self.list_stack.last().map(|i| i.make_list_view(theme))
     }
 
     pub fn search_next(&mut self, search_text: &str) {
@@ -189,24 +568,27 @@ impl S3ItemsViewModel {
 
     pub fn selected_s3_uri(&self) -> String {
         match self.selected() {
-            Some(S3Item::Bucket(b)) => format!("s3://{}", b.bucket.name().as_ref().unwrap_or(&"")),
+            Some(S3Item::Bucket(b)) => match self.scheme {
+                Scheme::S3 => format!("s3://{}", b.bucket.name().unwrap_or_default()),
+                Scheme::File => self.scheme.uri(b.bucket.name().unwrap_or_default(), ""),
+            },
             Some(S3Item::CommonPrefix(d)) => {
                 if let Some((bucket, _)) = self.bucket_and_prefix() {
-                    format!("s3://{}/{}", bucket, d.prefix().as_ref().unwrap_or(&""))
+                    self.scheme.uri(&bucket, d.prefix().unwrap_or_default())
                 } else {
                     String::default()
                 }
             }
             Some(S3Item::Object(k)) => {
                 if let Some((bucket, _)) = self.bucket_and_prefix() {
-                    format!("s3://{}/{}", bucket, k.key().as_ref().unwrap_or(&""))
+                    self.scheme.uri(&bucket, k.key().unwrap_or_default())
                 } else {
                     String::default()
                 }
             }
             Some(S3Item::Pop) => {
                 if let Some((bucket, prefix)) = self.bucket_and_prefix() {
-                    format!("s3://{}/{}", bucket, prefix)
+                    self.scheme.uri(&bucket, &prefix)
                 } else {
                     String::default()
                 }
@@ -217,17 +599,13 @@ impl S3ItemsViewModel {
 
     pub fn last(&mut self) {
         if let Some(i) = self.list_stack.last_mut() {
-            if i.list.items.len() > 0 {
-                i.list.state.select(Some(i.list.items.len() - 1))
-            }
+            i.list.last();
         }
     }
 
     pub fn first(&mut self) {
         if let Some(i) = self.list_stack.last_mut() {
-            if i.list.items.len() > 0 {
-                i.list.state.select(Some(0))
-            }
+            i.list.first();
         }
     }
 
@@ -243,18 +621,53 @@ impl S3ItemsViewModel {
         }
     }
 
+    pub fn select_key(&mut self, key: &str) -> bool {
+        self.list_stack
+            .last_mut()
+            .map(|i| i.select_key(key))
+            .unwrap_or(false)
+    }
+
+    // 현재 위치와 선택 상태
+    pub fn history_entry(&self) -> Option<HistoryEntry> {
+        self.list_stack.last().map(|i| HistoryEntry {
+            location: i.output().bucket_and_prefix(),
+            state: i.list.state(),
+        })
+    }
+
+    // history 에 기록된 선택 상태를 되돌린다. 목록이 줄었다면 마지막 행을 선택한다
+    pub fn restore_state(&mut self, mut state: ListState) {
+        if let Some(item) = self.list_stack.last_mut() {
+            let len = item.list.items().len();
+            match state.selected() {
+                Some(_) if len == 0 => state.select(None),
+                Some(i) if i >= len => state.select(Some(len - 1)),
+                _ => {}
+            }
+            item.list.state = state;
+        }
+    }
+
     pub fn pop(&mut self) -> Option<S3ItemViewModel> {
         self.list_stack.pop()
     }
 
     pub fn push(&mut self, s3_output: S3Output) {
-        self.list_stack.push(S3ItemViewModel::new(s3_output));
+        self.list_stack.push(S3ItemViewModel::new(
+            s3_output,
+            self.sort_order,
+            self.has_connections,
+        ));
     }
 
     pub fn update(&mut self, s3_output: S3Output) {
         let bucket_and_prefix = self.bucket_and_prefix();
         if let Some(item) = self.list_stack.last_mut() {
-            if s3_output.bucket_and_prefix() == bucket_and_prefix {
+            // 연결 목록과 bucket 목록은 위치가 같지만 다른 목록이다
+            if s3_output.bucket_and_prefix() == bucket_and_prefix
+                && s3_output.output_type() == item.output().output_type()
+            {
                 item.update_output(s3_output);
             } else {
                 self.push(s3_output);
@@ -265,14 +678,28 @@ impl S3ItemsViewModel {
     }
 
     pub fn selected(&self) -> Option<&S3Item> {
-        self.list_stack.last().map(|i| i.selected()).flatten()
+        self.list_stack.last().and_then(|i| i.selected())
+    }
+
+    // 보고 있는 목록의 다음 페이지라면 목록에 더한다
+    pub fn append_page(&mut self, page: ListObjectsV2Output) -> bool {
+        let location = Some((
+            page.name().unwrap_or_default().to_owned(),
+            page.prefix().unwrap_or_default().to_owned(),
+        ));
+        match self.list_stack.last_mut() {
+            Some(item) if item.output().bucket_and_prefix() == location => {
+                item.append_page(page);
+                true
+            }
+            _ => false,
+        }
     }
 
     pub fn bucket_and_prefix(&self) -> Option<(String, String)> {
         self.list_stack
             .last()
-            .map(|i| i.output().bucket_and_prefix())
-            .flatten()
+            .and_then(|i| i.output().bucket_and_prefix())
     }
 }
 
@@ -280,7 +707,8 @@ impl S3ItemsViewModel {
 mod tests {
     use super::*;
 
-    use aws_sdk_s3::model::{Bucket, BucketLocationConstraint, Object};
+    use crate::s3::client::BucketWithLocation;
+    use aws_sdk_s3::model::{Bucket, BucketLocationConstraint, CommonPrefix, Object};
 
     #[test]
     fn test_s3items_view_model() {
@@ -304,7 +732,7 @@ mod tests {
 
             location_list
                 .into_iter()
-                .zip(bucket_list.into_iter())
+                .zip(bucket_list)
                 .map(|(l, b)| BucketWithLocation {
                     location: l,
                     bucket: b,
@@ -351,7 +779,7 @@ mod tests {
                 .build()
         };
         // enter to bucket
-        vm.push(S3Output::Objects(object_list_output.clone()));
+        vm.push(S3Output::Objects(Box::new(object_list_output.clone())));
 
         assert_eq!(vm.selected(), Some(&S3Item::Pop));
         vm.next();
@@ -364,4 +792,116 @@ mod tests {
 
         assert_eq!(vm.selected(), Some(&S3Item::Object(expect_selected)));
     }
+
+    #[test]
+    fn test_loading() {
+        let mut vm = S3ItemsViewModel::new();
+        assert!(!vm.tick_loading());
+        assert_eq!(vm.loading_label(), None);
+
+        vm.set_loading(Some(0));
+        assert_eq!(
+            vm.loading_label().as_deref(),
+            Some("[⠋ loading page 1 (esc: cancel)]    ")
+        );
+        // 페이지를 받아도 spinner 는 이어서 돈다
+        assert!(vm.tick_loading());
+        vm.set_loading(Some(2));
+        assert_eq!(
+            vm.loading_label().as_deref(),
+            Some("[⠙ loading page 3 (esc: cancel)]    ")
+        );
+
+        vm.set_loading(None);
+        assert_eq!(vm.loading_label(), None);
+    }
+
+    #[test]
+    fn test_append_page() {
+        let page = |prefixes: &[&str], keys: &[&str]| {
+            ListObjectsV2Output::builder()
+                .name("bucket")
+                .prefix("")
+                .set_common_prefixes(Some(
+                    prefixes
+                        .iter()
+                        .map(|p| CommonPrefix::builder().prefix(*p).build())
+                        .collect(),
+                ))
+                .set_contents(Some(
+                    keys.iter()
+                        .map(|k| Object::builder().key(*k).build())
+                        .collect(),
+                ))
+                .build()
+        };
+        let mut vm = S3ItemsViewModel::new();
+        vm.push(S3Output::Objects(Box::new(page(&["a/"], &["b", "c"]))));
+        assert!(vm.select_key("b"));
+
+        // 다음 페이지의 prefix 는 object 보다 앞에 오고 선택한 항목은 그대로다
+        assert!(vm.append_page(page(&["d/"], &["e"])));
+        let names: Vec<_> = vm.items().iter().map(|i| i.as_row().2).collect();
+        assert_eq!(names, vec!["", "a/", "d/", "b", "c", "e"]);
+        assert_eq!(vm.selected().map(|i| i.name()), Some("b".to_owned()));
+
+        // 다른 위치의 페이지는 더하지 않는다
+        let mut other = page(&[], &["x"]);
+        other.prefix = Some("other/".to_owned());
+        assert!(!vm.append_page(other));
+        assert_eq!(vm.items().len(), 6);
+    }
+
+    #[test]
+    fn test_bucket_location() {
+        let bucket = |name: &str, location: &str| BucketWithLocation {
+            location: BucketLocationConstraint::from(location),
+            bucket: Bucket::builder().name(name).build(),
+        };
+        let mut vm = S3ItemsViewModel::new();
+        vm.push(S3Output::Buckets(vec![
+            bucket("logs", client::LOCATION_RESOLVING),
+            bucket("photos", "eu-west-1"),
+        ]));
+        vm.next();
+        assert_eq!(vm.resolving_buckets(), vec!["logs"]);
+
+        let location = BucketLocationConstraint::from("ap-northeast-2");
+        assert!(vm.set_bucket_location("logs", &location));
+        // 이미 찾은 location 은 바꾸지 않는다
+        assert!(!vm.set_bucket_location("photos", &location));
+        assert!(vm.resolving_buckets().is_empty());
+        assert_eq!(vm.items()[0].as_row().1, "ap-northeast-2");
+        assert_eq!(vm.items()[1].as_row().1, "eu-west-1");
+        assert_eq!(vm.selected().map(|i| i.name()), Some("photos".to_owned()));
+    }
+
+    #[test]
+    fn test_connection_list() {
+        let connection = |name: &str| Connection {
+            name: name.to_owned(),
+            config: crate::s3::client::S3ClientConfig::default(),
+            color: None,
+            pinned_buckets: vec![],
+        };
+        let mut vm = S3ItemsViewModel::new();
+        vm.set_has_connections(true);
+        vm.push(S3Output::Connections(vec![
+            connection("aws"),
+            connection("minio"),
+        ]));
+        assert!(vm.showing_connections());
+        vm.set_connection(Some(connection("minio")));
+        vm.select_connection();
+        assert_eq!(
+            vm.selected(),
+            Some(&S3Item::Connection(connection("minio")))
+        );
+
+        // 연결을 고르면 bucket 목록이 연결 목록 위에 쌓이고 .. 으로 돌아갈 수 있다
+        vm.update(S3Output::Buckets(vec![]));
+        assert!(!vm.showing_connections());
+        assert_eq!(vm.list_stack.len(), 2);
+        assert_eq!(vm.selected(), Some(&S3Item::Pop));
+    }
 }
@@ -0,0 +1,100 @@
+use tui::{
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, ListState},
+};
+
+use super::theme::Theme;
+use crate::s3::profile::AwsProfile;
+use crate::StatefulList;
+
+pub struct ProfileListViewModel {
+    list: StatefulList<AwsProfile>,
+    // 현재 tab 이 사용하는 profile
+    active: String,
+}
+
+impl ProfileListViewModel {
+    // 현재 profile 을 선택한 상태로 시작한다
+    pub fn new(profiles: Vec<AwsProfile>, active: String) -> Self {
+        let mut list = StatefulList::new(profiles);
+        if let Some(i) = list.items().iter().position(|p| p.name == active) {
+            list.state.select(Some(i));
+        }
+        Self { list, active }
+    }
+
+    pub fn next(&mut self) {
+        self.list.next();
+    }
+
+    pub fn previous(&mut self) {
+        self.list.previous();
+    }
+
+    pub fn selected(&self) -> Option<&AwsProfile> {
+        self.list.selected()
+    }
+
+    pub fn make_list_view(&self, theme: &Theme) -> (List<'static>, ListState) {
+        let name_width = self
+            .list
+            .items()
+            .iter()
+            .map(|p| p.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let region_width = self
+            .list
+            .items()
+            .iter()
+            .map(|p| p.region.as_ref().map_or(1, |r| r.chars().count()))
+            .max()
+            .unwrap_or(0);
+
+        let list_items: Vec<_> = self
+            .list
+            .items()
+            .iter()
+            .map(|p| {
+                let marker = if p.name == self.active { "* " } else { "  " };
+                let mut spans = vec![
+                    Span::raw(marker),
+                    Span::styled(
+                        format!("{:width$} ", p.name, width = name_width),
+                        theme.prefix,
+                    ),
+                    Span::styled(
+                        format!(
+                            "{:width$} ",
+                            p.region.as_deref().unwrap_or("-"),
+                            width = region_width
+                        ),
+                        theme.date,
+                    ),
+                    Span::styled(p.kind.to_string(), theme.size),
+                ];
+                if let Some(detail) = p.detail.as_ref() {
+                    spans.push(Span::raw(format!(" {}", detail)));
+                }
+                if p.mfa_serial.is_some() {
+                    spans.push(Span::styled(" (mfa)", theme.date));
+                }
+                ListItem::new(Spans::from(spans))
+            })
+            .collect();
+
+        let list = List::new(list_items)
+            .block(
+                Block::default()
+                    .title("profiles (enter: switch, esc: close)")
+                    .borders(Borders::ALL),
+            )
+            .highlight_style(theme.highlight);
+
+        (list, self.list.state())
+    }
+
+    pub fn reset_state(&mut self, state: ListState) {
+        self.list.state = state;
+    }
+}
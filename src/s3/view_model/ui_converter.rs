@@ -1,5 +1,5 @@
 use tui::{
-    style::{Color, Modifier, Style},
+    style::Style,
     text::{Span, Spans},
     widgets::{List, ListItem},
 };
@@ -7,65 +7,72 @@ use tui::{
 use strum::IntoEnumIterator;
 
 use super::*;
+use theme::Theme;
 
-fn rows_into_list_item(columns: Vec<(String, String, String)>) -> Vec<ListItem<'static>> {
+// (date, size, name) 와 이름의 style
+fn rows_into_list_item(
+    columns: Vec<((String, String, String), Style)>,
+    theme: &Theme,
+) -> Vec<ListItem<'static>> {
     if columns.is_empty() {
         return vec![];
     }
-    let first_column_hint = columns.iter().map(|t| t.0.len()).max().unwrap();
-    let second_column_hint = columns.iter().map(|t| t.1.len()).max().unwrap();
+    let first_column_hint = columns.iter().map(|t| t.0 .0.len()).max().unwrap();
+    let second_column_hint = columns.iter().map(|t| t.0 .1.len()).max().unwrap();
 
-    let get_left_padding = |width_hint, len| {
-        std::iter::repeat(" ")
-            .take(width_hint - len)
-            .fold(String::new(), |f, s| f + s)
-    };
+    let get_left_padding = |width_hint, len| " ".repeat(width_hint - len);
 
     columns
         .into_iter()
-        .map(|i| {
+        .map(|(i, name_style)| {
             ListItem::new(Spans::from(vec![
                 Span::styled(
                     get_left_padding(first_column_hint, i.0.len()) + &i.0 + " ",
-                    Style::default().fg(Color::Magenta),
+                    theme.date,
                 ),
                 Span::styled(
                     get_left_padding(second_column_hint, i.1.len()) + &i.1 + " ",
-                    Style::default().fg(Color::Blue),
+                    theme.size,
                 ),
-                Span::styled(i.2, Style::default()),
+                Span::styled(i.2, name_style),
             ]))
-            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .style(theme.row)
         })
         .collect()
 }
 
-impl Into<(List<'static>, ListState)> for &S3ItemViewModel {
-    fn into(self) -> (List<'static>, ListState) {
+fn name_style(item: &S3Item, theme: &Theme) -> Style {
+    match item {
+        S3Item::Connection(c) => match c.color {
+            Some(color) => theme.bucket.fg(color),
+            None => theme.bucket,
+        },
+        S3Item::Bucket(_) => theme.bucket,
+        S3Item::CommonPrefix(_) | S3Item::Pop => theme.prefix,
+        S3Item::Object(o) => theme.object_style(o.storage_class().map(|c| c.as_str())),
+    }
+}
+
+impl S3ItemViewModel {
+    pub fn make_list_view(&self, theme: &Theme) -> (List<'static>, ListState) {
         let list_state = self.items().state();
         let s3items = self.items().items();
 
         let list_items: Vec<_> = S3ItemType::iter()
-            .map(|t| {
+            .flat_map(|t| {
                 let vec = s3items
                     .iter()
                     .filter(|i| i.get_type() == t)
-                    .map(|s| s.as_row())
+                    .map(|s| (s.as_row(), name_style(s, theme)))
                     .collect();
-                rows_into_list_item(vec)
+                rows_into_list_item(vec, theme)
             })
-            .flatten()
             .collect();
 
         let items = List::new(list_items)
-            .highlight_style(
-                Style::default()
-                    .bg(Color::White)
-                    .fg(Color::Black)
-                    .add_modifier(Modifier::BOLD),
-            )
+            .highlight_style(theme.highlight)
             .highlight_symbol("");
 
-        (items, list_state.clone())
+        (items, list_state)
     }
 }
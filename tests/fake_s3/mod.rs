@@ -0,0 +1,621 @@
+use aws_sdk_s3::{model::Object, Endpoint, Region};
+use aws_smithy_types::date_time::Format;
+use aws_types::Credentials;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use percent_encoding::percent_decode_str;
+use std::{
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+    net::{SocketAddr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{Arc, Mutex, Once},
+    time::Duration,
+};
+use tui_aws::s3::{
+    backend::{memory::MemoryBackend, StorageBackend},
+    client::{endpoint::Endpoints, S3ClientConfig},
+};
+
+// 테스트 서버에서 사용하는 access key. minio 의 root 계정으로도 사용한다
+const ACCESS_KEY: &str = "tui-s3-test";
+const SECRET_KEY: &str = "tui-s3-test-secret";
+
+// list-type=2 요청의 max-keys 기본값
+const MAX_KEYS: usize = 1000;
+
+// mfa profile 이 사용하는 장치와 가짜 STS 가 받아주는 code
+pub const MFA_SERIAL: &str = "arn:aws:iam::123456789012:mfa/tester";
+pub const MFA_CODE: &str = "123456";
+// 가짜 STS 가 mfa session 으로 주는 access key
+const SESSION_ACCESS_KEY: &str = "ASIA-MFA-SESSION";
+
+// S3Client 가 읽는 credentials 파일을 만든다. 모든 테스트가 같은 파일을 사용한다
+fn init_credentials() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        let dir = std::env::temp_dir().join(format!("tui-s3-it-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let credentials = dir.join("credentials");
+        std::fs::write(
+            &credentials,
+            format!(
+                "[default]\naws_access_key_id = {}\naws_secret_access_key = {}\n",
+                ACCESS_KEY, SECRET_KEY
+            ),
+        )
+        .unwrap();
+        // default profile 의 credential 과 mfa 로 assume 하는 role
+        let config = dir.join("config");
+        std::fs::write(
+            &config,
+            format!(
+                "[profile mfa]\nrole_arn = arn:aws:iam::123456789012:role/tester\n\
+                 mfa_serial = {}\nsource_profile = default\n",
+                MFA_SERIAL
+            ),
+        )
+        .unwrap();
+        // 환경 변수의 credential 과 profile 이 위의 파일보다 먼저 쓰이지 않도록 한다
+        for name in [
+            "AWS_ACCESS_KEY_ID",
+            "AWS_SECRET_ACCESS_KEY",
+            "AWS_SESSION_TOKEN",
+            "AWS_PROFILE",
+            "AWS_DEFAULT_PROFILE",
+            "AWS_ROLE_ARN",
+            "AWS_WEB_IDENTITY_TOKEN_FILE",
+            "AWS_CONTAINER_CREDENTIALS_RELATIVE_URI",
+            "AWS_CONTAINER_CREDENTIALS_FULL_URI",
+        ] {
+            std::env::remove_var(name);
+        }
+        // EC2 instance metadata 를 조회하지 않는다
+        std::env::set_var("AWS_EC2_METADATA_DISABLED", "true");
+        std::env::set_var("AWS_SHARED_CREDENTIALS_FILE", &credentials);
+        std::env::set_var("AWS_CONFIG_FILE", &config);
+        // 찾은 bucket region 을 사용자의 cache 에 남기지 않는다
+        std::env::set_var("XDG_DATA_HOME", &dir);
+    });
+}
+
+// PATH 에 minio 가 있다면 그것을 사용한다
+fn minio_path() -> Option<PathBuf> {
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join("minio"))
+        .find(|path| path.is_file())
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+// 다른 region 의 bucket 에 요청했을 때의 응답
+#[derive(Clone, Copy)]
+pub enum WrongRegion {
+    // 301 과 x-amz-bucket-region header
+    PermanentRedirect,
+    // 400 과 body 의 <Region>
+    AuthorizationHeaderMalformed,
+}
+
+// bucket 이름 -> (region, 다른 region 으로 요청했을 때의 응답)
+type BucketRegions = Mutex<HashMap<String, (String, WrongRegion)>>;
+
+// 서명하지 않은 요청을 받아주는 bucket
+type PublicBuckets = Mutex<HashSet<String>>;
+
+// 테스트마다 비어있는 s3 호환 서버를 띄운다
+pub struct S3Server {
+    pub endpoint_url: String,
+    minio: Option<(Child, PathBuf)>,
+    // 가짜 서버에서만 bucket 의 region 과 공개 여부를 정할 수 있다
+    regions: Option<Arc<BucketRegions>>,
+    public: Option<Arc<PublicBuckets>>,
+}
+
+impl S3Server {
+    pub async fn start() -> Self {
+        init_credentials();
+        match minio_path() {
+            Some(minio) => Self::start_minio(&minio).await,
+            None => Self::start_fake(),
+        }
+    }
+
+    // minio 가 있더라도 가짜 서버를 띄운다
+    pub fn start_fake() -> Self {
+        init_credentials();
+        let backend = Arc::new(MemoryBackend::new());
+        let regions = Arc::new(BucketRegions::default());
+        let public = Arc::new(PublicBuckets::default());
+        let (regions_copy, public_copy) = (regions.clone(), public.clone());
+        let make_service = make_service_fn(move |_| {
+            let (backend, regions, public) =
+                (backend.clone(), regions_copy.clone(), public_copy.clone());
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let (backend, regions, public) =
+                        (backend.clone(), regions.clone(), public.clone());
+                    async move {
+                        Ok::<_, Infallible>(handle(&backend, &regions, &public, request).await)
+                    }
+                }))
+            }
+        });
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let endpoint_url = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+        Self {
+            endpoint_url,
+            minio: None,
+            regions: Some(regions),
+            public: Some(public),
+        }
+    }
+
+    async fn start_minio(minio: &Path) -> Self {
+        let port = free_port();
+        let dir =
+            std::env::temp_dir().join(format!("tui-s3-minio-{}-{}", std::process::id(), port));
+        let child = Command::new(minio)
+            .arg("server")
+            .arg(&dir)
+            .arg("--address")
+            .arg(format!("127.0.0.1:{}", port))
+            .env("MINIO_ROOT_USER", ACCESS_KEY)
+            .env("MINIO_ROOT_PASSWORD", SECRET_KEY)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        for _ in 0..100 {
+            if TcpStream::connect(("127.0.0.1", port)).is_ok() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        Self {
+            endpoint_url: format!("http://127.0.0.1:{}", port),
+            minio: Some((child, dir)),
+            regions: None,
+            public: None,
+        }
+    }
+
+    // S3Client 에는 bucket 을 만드는 기능이 없으므로 sdk 의 client 를 직접 사용한다
+    pub async fn create_bucket(&self, bucket: &str) {
+        let config = aws_sdk_s3::Config::builder()
+            .region(Region::new("us-east-1"))
+            .endpoint_resolver(Endpoint::immutable(self.endpoint_url.parse().unwrap()))
+            .credentials_provider(Credentials::new(ACCESS_KEY, SECRET_KEY, None, None, "test"))
+            .build();
+        aws_sdk_s3::Client::from_conf(config)
+            .create_bucket()
+            .bucket(bucket)
+            .send()
+            .await
+            .unwrap();
+    }
+
+    // bucket 을 다른 region 으로 옮긴다. 그 region 으로 서명하지 않은 요청은 실패한다
+    pub fn set_bucket_region(&self, bucket: &str, region: &str, wrong_region: WrongRegion) {
+        self.regions
+            .as_ref()
+            .expect("only the fake server has regions")
+            .lock()
+            .unwrap()
+            .insert(bucket.to_owned(), (region.to_owned(), wrong_region));
+    }
+
+    // 서명하지 않은 요청으로도 bucket 을 읽을 수 있게 한다
+    pub fn set_public(&self, bucket: &str) {
+        self.public
+            .as_ref()
+            .expect("only the fake server has public buckets")
+            .lock()
+            .unwrap()
+            .insert(bucket.to_owned());
+    }
+
+    pub fn config(&self) -> S3ClientConfig {
+        S3ClientConfig {
+            profile: None,
+            endpoint_url: Some(self.endpoint_url.clone()),
+            region: Some("us-east-1".to_owned()),
+            assume_role: None,
+            no_sign_request: false,
+            endpoints: Endpoints::default(),
+        }
+    }
+}
+
+impl Drop for S3Server {
+    fn drop(&mut self) {
+        if let Some((child, dir)) = self.minio.as_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
+
+fn escape(str: &str) -> String {
+    str.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn decode(str: &str) -> String {
+    percent_decode_str(str).decode_utf8_lossy().into_owned()
+}
+
+fn xml(body: String) -> Response<Body> {
+    Response::builder()
+        .header("Content-Type", "application/xml")
+        .body(Body::from(format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>{}",
+            body
+        )))
+        .unwrap()
+}
+
+// MemoryBackend 의 오류는 "<Code>: <Message>" 형태다
+fn error(status: StatusCode, e: eyre::Report) -> Response<Body> {
+    let message = e.to_string();
+    let code = message
+        .split(':')
+        .next()
+        .unwrap_or("InternalError")
+        .to_owned();
+    let mut response = xml(format!(
+        "<Error><Code>{}</Code><Message>{}</Message></Error>",
+        escape(&code),
+        escape(&message)
+    ));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert("x-amz-request-id", "FAKE-REQUEST-ID".parse().unwrap());
+    response
+}
+
+fn last_modified(object: &Object, format: Format) -> String {
+    object
+        .last_modified()
+        .and_then(|t| t.fmt(format).ok())
+        .unwrap_or_default()
+}
+
+// Authorization header 의 Credential=<key>/<date>/<region>/s3/aws4_request
+fn signed_credential(request: &Request<Body>, index: usize) -> Option<String> {
+    let authorization = request.headers().get("Authorization")?.to_str().ok()?;
+    let credential = authorization.split("Credential=").nth(1)?;
+    credential.split('/').nth(index).map(|r| r.to_owned())
+}
+
+fn signed_region(request: &Request<Body>) -> Option<String> {
+    signed_credential(request, 2)
+}
+
+fn sts_error(code: &str, message: &str) -> Response<Body> {
+    let mut response = xml(format!(
+        "<ErrorResponse><Error><Type>Sender</Type><Code>{}</Code><Message>{}</Message></Error><RequestId>fake</RequestId></ErrorResponse>",
+        code, message
+    ));
+    *response.status_mut() = StatusCode::FORBIDDEN;
+    response
+}
+
+// GetSessionToken 은 MFA_CODE 를, AssumeRole 은 mfa session 의 credential 을 요구한다
+async fn sts(request: Request<Body>) -> Response<Body> {
+    let access_key = signed_credential(&request, 0).unwrap_or_default();
+    let body = hyper::body::to_bytes(request.into_body())
+        .await
+        .unwrap_or_default();
+    let form: HashMap<String, String> = form_urlencoded::parse(&body).into_owned().collect();
+    let get = |key: &str| form.get(key).map(|v| v.as_str()).unwrap_or_default();
+
+    let (action, access_key_id) = match get("Action") {
+        "GetSessionToken" if get("SerialNumber") != MFA_SERIAL || get("TokenCode") != MFA_CODE => {
+            return sts_error("AccessDenied", "MultiFactorAuthentication failed");
+        }
+        "GetSessionToken" => ("GetSessionToken", SESSION_ACCESS_KEY),
+        "AssumeRole" if access_key != SESSION_ACCESS_KEY => {
+            return sts_error("AccessDenied", "MultiFactorAuthentication is required");
+        }
+        "AssumeRole" => ("AssumeRole", "ASIA-ROLE"),
+        action => return sts_error("InvalidAction", action),
+    };
+    let expiration =
+        aws_smithy_types::DateTime::from(std::time::SystemTime::now() + Duration::from_secs(3600))
+            .fmt(Format::DateTime)
+            .unwrap();
+    xml(format!(
+        "<{action}Response xmlns=\"https://sts.amazonaws.com/doc/2011-06-15/\"><{action}Result>\
+         <Credentials><AccessKeyId>{}</AccessKeyId><SecretAccessKey>secret</SecretAccessKey>\
+         <SessionToken>token</SessionToken><Expiration>{}</Expiration></Credentials>\
+         </{action}Result></{action}Response>",
+        access_key_id,
+        expiration,
+        action = action
+    ))
+}
+
+// bucket 의 region 과 다른 region 으로 서명한 요청에 대한 응답
+fn wrong_region(
+    regions: &BucketRegions,
+    bucket: &str,
+    request: &Request<Body>,
+) -> Option<Response<Body>> {
+    let (region, wrong_region) = regions.lock().unwrap().get(bucket).cloned()?;
+    let signed = signed_region(request).unwrap_or_default();
+    if signed == region {
+        return None;
+    }
+    let mut response = match wrong_region {
+        WrongRegion::PermanentRedirect => {
+            let mut response = error(
+                StatusCode::MOVED_PERMANENTLY,
+                eyre::eyre!("PermanentRedirect: use the {} endpoint", region),
+            );
+            response
+                .headers_mut()
+                .insert("x-amz-bucket-region", region.parse().unwrap());
+            response
+        }
+        WrongRegion::AuthorizationHeaderMalformed => xml(format!(
+            "<Error><Code>AuthorizationHeaderMalformed</Code><Message>the region '{}' is wrong; expecting '{}'</Message><Region>{}</Region></Error>",
+            signed, region, region
+        )),
+    };
+    if let WrongRegion::AuthorizationHeaderMalformed = wrong_region {
+        *response.status_mut() = StatusCode::BAD_REQUEST;
+        // HEAD 응답에는 body 가 없으므로 header 로 알려준다
+        if request.method() == Method::HEAD {
+            response
+                .headers_mut()
+                .insert("x-amz-bucket-region", region.parse().unwrap());
+        }
+    }
+    Some(response)
+}
+
+async fn handle(
+    backend: &MemoryBackend,
+    regions: &BucketRegions,
+    public: &PublicBuckets,
+    request: Request<Body>,
+) -> Response<Body> {
+    let path = request.uri().path().trim_start_matches('/').to_owned();
+    let (bucket, key) = match path.split_once('/') {
+        Some((bucket, key)) => (decode(bucket), decode(key)),
+        None => (decode(&path), String::new()),
+    };
+    let query: HashMap<String, String> = request
+        .uri()
+        .query()
+        .map(|q| form_urlencoded::parse(q.as_bytes()).into_owned().collect())
+        .unwrap_or_default();
+
+    if request.method() == Method::POST && path.is_empty() {
+        return sts(request).await;
+    }
+    // 서명하지 않은 요청은 공개된 bucket 만 읽을 수 있다
+    if request.headers().get("Authorization").is_none()
+        && (request.method() != Method::GET || !public.lock().unwrap().contains(&bucket))
+    {
+        return error(
+            StatusCode::FORBIDDEN,
+            eyre::eyre!("AccessDenied: Access Denied"),
+        );
+    }
+    // GetBucketLocation 은 어느 region 에서나 요청할 수 있다
+    if query.contains_key("location") {
+        let region = regions
+            .lock()
+            .unwrap()
+            .get(&bucket)
+            .map(|(region, _)| region.clone())
+            .filter(|region| region != "us-east-1")
+            .unwrap_or_default();
+        return xml(format!(
+            "<LocationConstraint xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\">{}</LocationConstraint>",
+            escape(&region)
+        ));
+    }
+    if !bucket.is_empty() {
+        if let Some(response) = wrong_region(regions, &bucket, &request) {
+            return response;
+        }
+    }
+
+    let result = match (request.method().clone(), bucket.is_empty(), key.is_empty()) {
+        (Method::GET, true, _) => list_buckets(backend).await,
+        (Method::PUT, false, true) => {
+            backend.create_bucket(&bucket);
+            Ok(Response::new(Body::empty()))
+        }
+        (Method::GET, false, true) => list_objects_v2(backend, &bucket, &query).await,
+        (Method::HEAD, false, true) => backend
+            .list_objects(&bucket, "")
+            .await
+            .map(|_| Response::new(Body::empty())),
+        (Method::HEAD, false, false) => backend.head_object(&bucket, &key).await.map(|object| {
+            Response::builder()
+                .header("Content-Length", object.size())
+                .header("Last-Modified", last_modified(&object, Format::HttpDate))
+                .body(Body::empty())
+                .unwrap()
+        }),
+        (Method::GET, false, false) => backend
+            .get_object(&bucket, &key)
+            .await
+            .map(|body| Response::new(Body::from(body))),
+        (Method::PUT, false, false) => put_object(backend, &bucket, &key, request).await,
+        (Method::DELETE, false, false) => backend.delete_object(&bucket, &key).await.map(|_| {
+            let mut response = Response::new(Body::empty());
+            *response.status_mut() = StatusCode::NO_CONTENT;
+            response
+        }),
+        (method, _, _) => Err(eyre::eyre!("NotImplemented: {} {}", method, path)),
+    };
+    result.unwrap_or_else(|e| {
+        let status = if e.to_string().starts_with("NotImplemented") {
+            StatusCode::NOT_IMPLEMENTED
+        } else {
+            StatusCode::NOT_FOUND
+        };
+        error(status, e)
+    })
+}
+
+async fn list_buckets(backend: &MemoryBackend) -> eyre::Result<Response<Body>> {
+    let buckets: String = backend
+        .list_bucket_names()
+        .await?
+        .iter()
+        .map(|name| {
+            format!(
+                "<Bucket><Name>{}</Name><CreationDate>2022-01-01T00:00:00.000Z</CreationDate></Bucket>",
+                escape(name)
+            )
+        })
+        .collect();
+    Ok(xml(format!(
+        "<ListAllMyBucketsResult><Owner><ID>tui-s3</ID></Owner><Buckets>{}</Buckets></ListAllMyBucketsResult>",
+        buckets
+    )))
+}
+
+// delimiter 로 묶은 prefix 와 object 를 key 순서로 max-keys 개씩 나눠서 돌려준다.
+// continuation token 은 이전 페이지의 마지막 항목이다
+async fn list_objects_v2(
+    backend: &MemoryBackend,
+    bucket: &str,
+    query: &HashMap<String, String>,
+) -> eyre::Result<Response<Body>> {
+    let prefix = query.get("prefix").cloned().unwrap_or_default();
+    let delimiter = query.get("delimiter").cloned().unwrap_or_default();
+    let max_keys = query
+        .get("max-keys")
+        .and_then(|m| m.parse().ok())
+        .unwrap_or(MAX_KEYS);
+
+    // (key, prefix 가 아니라면 object)
+    let mut entries: Vec<(String, Option<Object>)> = vec![];
+    if delimiter == "/" {
+        let output = backend.list_objects(bucket, &prefix).await?;
+        for p in output.common_prefixes().unwrap_or_default() {
+            entries.push((p.prefix().unwrap_or_default().to_owned(), None));
+        }
+        for o in output.contents().unwrap_or_default() {
+            entries.push((o.key().unwrap_or_default().to_owned(), Some(o.clone())));
+        }
+    } else {
+        let mut continuation_token = None;
+        loop {
+            let (keys, next_continuation_token) = backend
+                .list_keys_page(bucket, &prefix, continuation_token)
+                .await?;
+            for key in keys {
+                let object = backend.head_object(bucket, &key).await?;
+                entries.push((key, Some(object)));
+            }
+            if next_continuation_token.is_none() {
+                break;
+            }
+            continuation_token = next_continuation_token;
+        }
+    }
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut page: Vec<_> = entries
+        .into_iter()
+        .filter(|(key, _)| query.get("continuation-token").is_none_or(|t| key > t))
+        .take(max_keys + 1)
+        .collect();
+    let next_continuation_token = if page.len() > max_keys {
+        page.pop();
+        page.last().map(|(key, _)| key.clone())
+    } else {
+        None
+    };
+
+    let mut body = format!(
+        "<ListBucketResult><Name>{}</Name><Prefix>{}</Prefix><KeyCount>{}</KeyCount><MaxKeys>{}</MaxKeys><IsTruncated>{}</IsTruncated>",
+        escape(bucket),
+        escape(&prefix),
+        page.len(),
+        max_keys,
+        next_continuation_token.is_some()
+    );
+    if !delimiter.is_empty() {
+        body += &format!("<Delimiter>{}</Delimiter>", escape(&delimiter));
+    }
+    if let Some(token) = query.get("continuation-token") {
+        body += &format!("<ContinuationToken>{}</ContinuationToken>", escape(token));
+    }
+    if let Some(token) = next_continuation_token.as_ref() {
+        body += &format!(
+            "<NextContinuationToken>{}</NextContinuationToken>",
+            escape(token)
+        );
+    }
+    for (key, object) in page {
+        body += &match object {
+            Some(object) => format!(
+                "<Contents><Key>{}</Key><LastModified>{}</LastModified><Size>{}</Size><StorageClass>STANDARD</StorageClass></Contents>",
+                escape(&key),
+                last_modified(&object, Format::DateTime),
+                object.size()
+            ),
+            None => format!(
+                "<CommonPrefixes><Prefix>{}</Prefix></CommonPrefixes>",
+                escape(&key)
+            ),
+        };
+    }
+    body += "</ListBucketResult>";
+    Ok(xml(body))
+}
+
+// x-amz-copy-source 가 있다면 복사한다
+async fn put_object(
+    backend: &MemoryBackend,
+    bucket: &str,
+    key: &str,
+    request: Request<Body>,
+) -> eyre::Result<Response<Body>> {
+    let copy_source = request
+        .headers()
+        .get("x-amz-copy-source")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| decode(v.trim_start_matches('/')));
+    if let Some(copy_source) = copy_source {
+        let (source_bucket, source_key) = copy_source
+            .split_once('/')
+            .ok_or_else(|| eyre::eyre!("InvalidArgument: {}", copy_source))?;
+        backend
+            .copy_object(source_bucket, source_key, bucket, key)
+            .await?;
+        let object = backend.head_object(bucket, key).await?;
+        return Ok(xml(format!(
+            "<CopyObjectResult><LastModified>{}</LastModified></CopyObjectResult>",
+            last_modified(&object, Format::DateTime)
+        )));
+    }
+
+    let body = hyper::body::to_bytes(request.into_body()).await?;
+    backend.put_object(bucket, key, body.to_vec()).await?;
+    Ok(Response::new(Body::empty()))
+}
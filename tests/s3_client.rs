@@ -0,0 +1,422 @@
+use std::time::Duration;
+use tui_aws::s3::{
+    backend::StorageBackend,
+    client::{
+        assume_role::MfaRequired, endpoint::Endpoints, request_error::RequestError,
+        BucketWithLocation, S3Client, S3ClientConfig,
+    },
+    config::EndpointConfig,
+};
+
+mod fake_s3;
+use fake_s3::{S3Server, WrongRegion, MFA_CODE, MFA_SERIAL};
+
+// 공백, 한글, url 과 xml 에서 특별한 의미를 갖는 문자가 들어간 key
+const ODD_KEYS: [&str; 5] = [
+    "odd/with space.txt",
+    "odd/한글 파일.txt",
+    "odd/a+b=c&d.txt",
+    "odd/<tag> \"quoted\" 'single'.txt",
+    "odd/100%.txt",
+];
+
+// bucket 과 중첩된 prefix, 1000 개가 넘는 key 를 넣은 서버
+async fn seeded() -> (S3Server, S3Client) {
+    let server = S3Server::start().await;
+    server.create_bucket("alpha").await;
+    server.create_bucket("beta").await;
+    let client = S3Client::new(server.config()).await.unwrap();
+
+    for key in ["a/b/c/deep.txt", "a/b/mid.txt", "a/top.txt", "root.txt"] {
+        client
+            .put_object("alpha", key, key.as_bytes().to_vec())
+            .await
+            .unwrap();
+    }
+    for key in ODD_KEYS {
+        client
+            .put_object("alpha", key, key.as_bytes().to_vec())
+            .await
+            .unwrap();
+    }
+    let many: Vec<_> = (0..1005).map(|i| format!("many/{:05}", i)).collect();
+    futures::future::try_join_all(
+        many.iter()
+            .map(|key| client.put_object("beta", key, vec![])),
+    )
+    .await
+    .unwrap();
+    (server, client)
+}
+
+fn object_keys(output: &aws_sdk_s3::output::ListObjectsV2Output) -> Vec<String> {
+    output
+        .contents()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|o| o.key().map(|k| k.to_owned()))
+        .collect()
+}
+
+async fn all_keys(client: &S3Client, bucket: &str, prefix: &str) -> Vec<String> {
+    let mut keys = vec![];
+    let mut continuation_token = None;
+    loop {
+        let (page, next_continuation_token) = client
+            .list_keys_page(bucket, prefix, continuation_token)
+            .await
+            .unwrap();
+        keys.extend(page);
+        if next_continuation_token.is_none() {
+            return keys;
+        }
+        continuation_token = next_continuation_token;
+    }
+}
+
+#[tokio::test]
+async fn test_list_buckets_and_objects() {
+    let (_server, client) = seeded().await;
+
+    let buckets = client.list_buckets().await.unwrap();
+    let names: Vec<_> = buckets.iter().filter_map(|b| b.bucket.name()).collect();
+    assert_eq!(names, vec!["alpha", "beta"]);
+    assert_eq!(client.list_bucket_names().await.unwrap(), names);
+
+    let output = client.list_objects("alpha", "").await.unwrap();
+    assert_eq!(object_keys(&output), vec!["root.txt"]);
+    assert_eq!(
+        client.list_common_prefixes("alpha", "").await.unwrap(),
+        vec!["a/", "odd/"]
+    );
+
+    let output = client.list_objects("alpha", "a/b/").await.unwrap();
+    assert_eq!(object_keys(&output), vec!["a/b/mid.txt"]);
+    assert_eq!(output.contents().unwrap()[0].size(), 11);
+    assert!(output.contents().unwrap()[0].last_modified().is_some());
+    assert_eq!(
+        client.list_common_prefixes("alpha", "a/b/").await.unwrap(),
+        vec!["a/b/c/"]
+    );
+
+    // 이름에 특수 문자가 있어도 그대로 돌아온다
+    let mut odd_keys = ODD_KEYS.to_vec();
+    odd_keys.sort_unstable();
+    let output = client.list_objects("alpha", "odd/").await.unwrap();
+    assert_eq!(object_keys(&output), odd_keys);
+}
+
+#[tokio::test]
+async fn test_paginated_listing() {
+    let (_server, client) = seeded().await;
+
+    // list_objects 는 모든 페이지를 이어 붙인다
+    let output = client.list_objects("beta", "many/").await.unwrap();
+    let keys = object_keys(&output);
+    assert_eq!(keys.len(), 1005);
+    assert_eq!(keys.first().unwrap(), "many/00000");
+    assert_eq!(keys.last().unwrap(), "many/01004");
+
+    // 목록은 페이지마다 보여줄 수 있다
+    let (page, token) = client
+        .list_objects_page("beta", "many/", None)
+        .await
+        .unwrap();
+    assert_eq!(object_keys(&page), keys[..1000]);
+    assert_eq!(page.prefix(), Some("many/"));
+    let (page, token) = client
+        .list_objects_page("beta", "many/", token)
+        .await
+        .unwrap();
+    assert_eq!(object_keys(&page), keys[1000..]);
+    assert_eq!(token, None);
+
+    let (first, token) = client.list_keys_page("beta", "many/", None).await.unwrap();
+    assert_eq!(first.len(), 1000);
+    assert!(token.is_some());
+    assert_eq!(all_keys(&client, "beta", "").await, keys);
+}
+
+#[tokio::test]
+async fn test_mutating_operations() {
+    let (_server, client) = seeded().await;
+
+    for key in ODD_KEYS {
+        let head = client.head_object("alpha", key).await.unwrap();
+        assert_eq!(head.size(), key.len() as i64);
+        assert_eq!(
+            client.get_object("alpha", key).await.unwrap(),
+            key.as_bytes()
+        );
+
+        // 다른 bucket 으로 복사한 뒤 원본을 지운다
+        let copied = format!("copied/{}", key);
+        client
+            .copy_object("alpha", key, "beta", &copied)
+            .await
+            .unwrap();
+        client.delete_object("alpha", key).await.unwrap();
+        assert_eq!(
+            client.get_object("beta", &copied).await.unwrap(),
+            key.as_bytes()
+        );
+        assert!(client.head_object("alpha", key).await.is_err());
+    }
+    assert!(client
+        .list_objects("alpha", "odd/")
+        .await
+        .unwrap()
+        .contents()
+        .unwrap_or_default()
+        .is_empty());
+
+    client.create_folder("alpha", "new folder/").await.unwrap();
+    assert!(client
+        .list_common_prefixes("alpha", "")
+        .await
+        .unwrap()
+        .contains(&"new folder/".to_owned()));
+    assert_eq!(
+        client
+            .head_object("alpha", "new folder/")
+            .await
+            .unwrap()
+            .size(),
+        0
+    );
+
+    // 덮어쓰기
+    client
+        .put_object("alpha", "root.txt", b"changed".to_vec())
+        .await
+        .unwrap();
+    assert_eq!(
+        client.get_object("alpha", "root.txt").await.unwrap(),
+        b"changed"
+    );
+
+    assert!(client.get_object("alpha", "nothing").await.is_err());
+    // 응답의 code, status 와 request id 는 error log 에 남는다
+    let e = client.list_objects("nothing", "").await.unwrap_err();
+    let request = e.downcast_ref::<RequestError>().unwrap();
+    assert_eq!(request.code.as_deref(), Some("NoSuchBucket"));
+    assert_eq!(request.status, Some(404));
+    assert_eq!(request.request_id.as_deref(), Some("FAKE-REQUEST-ID"));
+    assert!(e.to_string().starts_with("NoSuchBucket: "));
+
+    let url = client
+        .presign_get_object("alpha", "a/top.txt", Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert!(url.contains("/alpha/a/top.txt?"));
+    assert!(url.contains("X-Amz-Expires=60"));
+}
+
+#[tokio::test]
+async fn test_cross_region_buckets() {
+    let server = S3Server::start_fake();
+    for bucket in ["east", "west", "europe"] {
+        server.create_bucket(bucket).await;
+    }
+    let client = S3Client::new(server.config()).await.unwrap();
+    for bucket in ["east", "west", "europe"] {
+        client
+            .put_object(bucket, "dir/file.txt", bucket.as_bytes().to_vec())
+            .await
+            .unwrap();
+    }
+    server.set_bucket_region("west", "us-west-2", WrongRegion::PermanentRedirect);
+    server.set_bucket_region(
+        "europe",
+        "eu-central-1",
+        WrongRegion::AuthorizationHeaderMalformed,
+    );
+
+    // region 을 모르는 bucket 은 서버가 알려준 region 으로 다시 요청한다
+    let client = S3Client::new(server.config()).await.unwrap();
+    for bucket in ["east", "west", "europe"] {
+        assert_eq!(
+            client.list_common_prefixes(bucket, "").await.unwrap(),
+            vec!["dir/"]
+        );
+        assert_eq!(
+            client
+                .head_object(bucket, "dir/file.txt")
+                .await
+                .unwrap()
+                .size(),
+            bucket.len() as i64
+        );
+    }
+
+    // bucket 목록은 location 을 기다리지 않는다. 찾은 region 은 다음 client 도 바로 보여준다
+    let locations = |buckets: Vec<BucketWithLocation>| -> Vec<String> {
+        buckets
+            .iter()
+            .map(|b| b.location.as_str().to_owned())
+            .collect()
+    };
+    let client = S3Client::new(server.config()).await.unwrap();
+    let buckets = client.list_buckets().await.unwrap();
+    assert!(buckets.iter().all(|b| b.is_resolving()));
+    for bucket in ["east", "europe", "west"] {
+        client.bucket_location(bucket).await.unwrap();
+    }
+    let client = S3Client::new(server.config()).await.unwrap();
+    assert_eq!(
+        locations(client.list_buckets().await.unwrap()),
+        vec!["us-east-1", "eu-central-1", "us-west-2"]
+    );
+    // 처음부터 bucket 의 region 으로 요청한다
+    let url = client
+        .presign_get_object("west", "dir/file.txt", Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert!(url.contains("us-west-2"));
+    client
+        .copy_object("west", "dir/file.txt", "europe", "copied.txt")
+        .await
+        .unwrap();
+    assert_eq!(
+        client.get_object("europe", "copied.txt").await.unwrap(),
+        b"west"
+    );
+
+    // 다시 보낼 수 없는 body 는 region 을 먼저 찾은 뒤 한 번만 보낸다
+    let client = S3Client::new(server.config()).await.unwrap();
+    let (body, size) = client
+        .get_object_stream("west", "dir/file.txt")
+        .await
+        .unwrap();
+    assert_eq!(size, 4);
+    client
+        .put_object_stream("europe", "streamed.txt", body, size)
+        .await
+        .unwrap();
+    assert_eq!(
+        client.get_object("europe", "streamed.txt").await.unwrap(),
+        b"west"
+    );
+}
+
+#[tokio::test]
+async fn test_default_credential_chain() {
+    let server = S3Server::start().await;
+    server.create_bucket("alpha").await;
+    // profile 을 지정하지 않아도 기본 순서로 찾은 default profile 의 key 를 사용한다
+    let client = S3Client::new(server.config()).await.unwrap();
+    assert_eq!(client.credentials_source(), None);
+
+    client.list_buckets().await.unwrap();
+    assert_eq!(client.credentials_source().as_deref(), Some("keys"));
+}
+
+#[tokio::test]
+async fn test_mfa_profile() {
+    let server = S3Server::start_fake();
+    server.create_bucket("alpha").await;
+    let config = S3ClientConfig {
+        profile: Some("mfa".to_owned()),
+        ..server.config()
+    };
+
+    // code 를 입력받기 전에는 연결할 수 없다
+    let required = S3Client::new(config.clone())
+        .await
+        .err()
+        .unwrap()
+        .downcast::<MfaRequired>()
+        .unwrap();
+    assert_eq!(required.mfa_serial, MFA_SERIAL);
+    assert_eq!(required.config, config);
+    assert!(S3Client::start_mfa_session(&config, "000000")
+        .await
+        .is_err());
+
+    S3Client::start_mfa_session(&config, MFA_CODE)
+        .await
+        .unwrap();
+    let client = S3Client::new(config).await.unwrap();
+    assert_eq!(client.list_bucket_names().await.unwrap(), vec!["alpha"]);
+    assert_eq!(client.credentials_source().as_deref(), Some("role+mfa"));
+}
+
+#[tokio::test]
+async fn test_anonymous_access() {
+    let server = S3Server::start_fake();
+    server.create_bucket("alpha").await;
+    server.create_bucket("beta").await;
+    let signed = S3Client::new(server.config()).await.unwrap();
+    signed
+        .put_object("alpha", "public.txt", b"hello".to_vec())
+        .await
+        .unwrap();
+    server.set_public("alpha");
+
+    let client = S3Client::new(S3ClientConfig {
+        no_sign_request: true,
+        ..server.config()
+    })
+    .await
+    .unwrap();
+    assert_eq!(client.credentials_source().as_deref(), Some("anonymous"));
+
+    // ListBuckets 는 요청하지 않고 방문한 bucket 만 보여준다
+    assert!(client.list_buckets().await.unwrap().is_empty());
+    let output = client.list_objects("alpha", "").await.unwrap();
+    assert_eq!(object_keys(&output), vec!["public.txt"]);
+    assert_eq!(client.list_bucket_names().await.unwrap(), vec!["alpha"]);
+
+    let error = client.list_objects("beta", "").await.unwrap_err();
+    assert!(error.to_string().contains("isn't public"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_named_endpoint_with_proxy() {
+    let server = S3Server::start_fake();
+    server.create_bucket("alpha").await;
+    // 이 주소로는 연결할 수 없으므로 proxy 인 가짜 서버를 거쳐야만 요청에 성공한다
+    let proxied = EndpointConfig {
+        url: "http://s3.proxied.invalid".to_owned(),
+        region: Some("us-east-1".to_owned()),
+        force_path_style: Some(true),
+        no_verify_ssl: Some(false),
+        proxy: Some(server.endpoint_url.clone()),
+        ..EndpointConfig::default()
+    };
+    let virtual_hosted = EndpointConfig {
+        force_path_style: Some(false),
+        ..proxied.clone()
+    };
+    let unverified = EndpointConfig {
+        no_verify_ssl: None,
+        ..proxied.clone()
+    };
+    // --no-verify-ssl 이어도 endpoint 가 false 라면 검증한다
+    let endpoints = Endpoints::new(
+        [
+            ("proxied".to_owned(), proxied),
+            ("virtual-hosted".to_owned(), virtual_hosted),
+            ("unverified".to_owned(), unverified),
+        ]
+        .into(),
+        EndpointConfig {
+            no_verify_ssl: Some(true),
+            ..EndpointConfig::default()
+        },
+    );
+    let config = |endpoint_url: &str| S3ClientConfig {
+        endpoint_url: Some(endpoint_url.to_owned()),
+        region: None,
+        endpoints: endpoints.clone(),
+        ..server.config()
+    };
+
+    let client = S3Client::new(config("proxied")).await.unwrap();
+    assert_eq!(client.list_bucket_names().await.unwrap(), vec!["alpha"]);
+    assert!(client.verifies_tls());
+    let client = S3Client::new(config("unverified")).await.unwrap();
+    assert!(!client.verifies_tls());
+
+    assert!(S3Client::new(config("virtual-hosted")).await.is_err());
+}
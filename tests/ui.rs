@@ -0,0 +1,223 @@
+use aws_smithy_types::DateTime;
+use crossterm::event::{Event as TerminalEvent, KeyCode, KeyEvent, KeyModifiers};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tui::{backend::TestBackend, Terminal};
+use tui_aws::{
+    s3::{backend::memory::MemoryBackend, controller::Controller},
+    App, EventAction, FrontendEvent,
+};
+
+// 2022-01-01T00:00:00Z
+const LAST_MODIFIED: i64 = 1640995200;
+
+// 가짜 저장소 위에서 controller 에 키를 보내고 TestBackend 에 그린 화면을 확인한다
+struct Harness {
+    controller: Controller,
+    terminal: Terminal<TestBackend>,
+    tx: Sender<FrontendEvent>,
+    rx: Receiver<FrontendEvent>,
+    exited: bool,
+}
+
+impl Harness {
+    async fn new(backend: Arc<MemoryBackend>, width: u16, height: u16) -> Self {
+        let (tx, rx) = channel(10);
+        let mut harness = Self {
+            controller: Controller::with_backend(backend).await.unwrap(),
+            terminal: Terminal::new(TestBackend::new(width, height)).unwrap(),
+            tx,
+            rx,
+            exited: false,
+        };
+        harness.controller.draw(&mut harness.terminal).unwrap();
+        harness
+    }
+
+    // 키와 그 키로 인한 조회 결과를 모두 처리한 뒤 다시 그린다
+    async fn press_with(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        let key = KeyEvent::new(code, modifiers);
+        self.tx
+            .send(FrontendEvent::TerminalEvent(TerminalEvent::Key(key)))
+            .await
+            .unwrap();
+        while let Ok(action) = tokio::time::timeout(
+            Duration::from_millis(100),
+            self.controller.handle_front_event(&mut self.rx),
+        )
+        .await
+        {
+            if let EventAction::Exit = action {
+                self.exited = true;
+                return;
+            }
+        }
+        self.controller.draw(&mut self.terminal).unwrap();
+    }
+
+    async fn press(&mut self, code: KeyCode) {
+        self.press_with(code, KeyModifiers::NONE).await;
+    }
+
+    // 문자마다 키를 누른다
+    async fn keys(&mut self, keys: &str) {
+        for c in keys.chars() {
+            self.press(KeyCode::Char(c)).await;
+        }
+    }
+
+    async fn resize(&mut self, width: u16, height: u16) {
+        self.terminal.backend_mut().resize(width, height);
+        self.terminal
+            .resize(tui::layout::Rect::new(0, 0, width, height))
+            .unwrap();
+        self.controller.draw(&mut self.terminal).unwrap();
+    }
+
+    // 화면의 각 줄. 끝의 공백은 지운다
+    fn lines(&self) -> Vec<String> {
+        let buffer = self.terminal.backend().buffer();
+        let width = buffer.area().width as usize;
+        buffer
+            .content()
+            .chunks(width)
+            .map(|cells| {
+                let line: String = cells.iter().map(|c| c.symbol.as_str()).collect();
+                line.trim_end().to_owned()
+            })
+            .collect()
+    }
+
+    #[track_caller]
+    fn assert_screen(&self, expected: &[&str]) {
+        assert_eq!(self.lines(), expected);
+    }
+}
+
+fn backend() -> Arc<MemoryBackend> {
+    let backend = MemoryBackend::new();
+    let insert = |bucket: &str, key: &str, size: usize| {
+        backend.insert_at(
+            bucket,
+            key,
+            vec![0; size],
+            DateTime::from_secs(LAST_MODIFIED),
+        );
+    };
+    insert("logs", "2024/01.log", 2048);
+    insert("logs", "2024/02.log", 10);
+    insert("logs", "readme.txt", 5);
+    insert("photos", "cat.jpg", 1);
+    Arc::new(backend)
+}
+
+#[tokio::test]
+async fn test_navigation() {
+    let mut ui = Harness::new(backend(), 60, 8).await;
+    ui.assert_screen(&[
+        "bucket selection    ────────────────────────────────────────",
+        " memory logs",
+        " memory photos",
+        "",
+        "",
+        "",
+        "s3://logs",
+        "",
+    ]);
+
+    ui.press(KeyCode::Enter).await;
+    ui.keys("j").await;
+    ui.press(KeyCode::Enter).await;
+    ui.assert_screen(&[
+        "s3://logs/2024/    ─────────────────────────────────────────",
+        "..",
+        "2022-01-01T00:00:00Z 2.0 kiB 01.log",
+        "2022-01-01T00:00:00Z    10 B 02.log",
+        "",
+        "",
+        "s3://logs/2024/",
+        "",
+    ]);
+
+    // .. 으로 돌아가면 들어갔던 prefix 가 선택되어 있다
+    ui.keys("gg").await;
+    ui.press(KeyCode::Enter).await;
+    assert_eq!(ui.lines()[6], "s3://logs/2024/");
+
+    ui.keys("q").await;
+    assert!(ui.exited);
+}
+
+#[tokio::test]
+async fn test_search_and_command_line() {
+    let mut ui = Harness::new(backend(), 60, 8).await;
+    ui.press(KeyCode::Enter).await;
+
+    ui.keys("/read").await;
+    assert_eq!(ui.lines()[6], "s3://logs/readme.txt");
+    assert_eq!(ui.lines()[7], "/read");
+    ui.press(KeyCode::Esc).await;
+
+    ui.keys(":sort size desc").await;
+    ui.press(KeyCode::Enter).await;
+    ui.assert_screen(&[
+        "s3://logs/    [sort: size desc]    ─────────────────────────",
+        "..",
+        "PRE  2024/",
+        "2022-01-01T00:00:00Z 5 B readme.txt",
+        "",
+        "",
+        "s3://logs/readme.txt",
+        "",
+    ]);
+
+    ui.keys(":cd s3://nowhere/").await;
+    ui.press(KeyCode::Enter).await;
+    // 없는 bucket 은 목록을 바꾸지 않는다
+    assert!(ui.lines()[0].starts_with("s3://logs/    [sort: size desc]"));
+}
+
+#[tokio::test]
+async fn test_layout_on_small_and_resized_terminals() {
+    let header = "s3://logs/    ────────────────";
+    let mut ui = Harness::new(backend(), 30, 3).await;
+    ui.press(KeyCode::Enter).await;
+    // 세 줄이면 목록 없이 header, status, 입력줄을 그린다
+    ui.assert_screen(&[header, "s3://logs/", ""]);
+
+    // 한 줄이면 header 만 그린다
+    ui.resize(30, 1).await;
+    ui.assert_screen(&[header]);
+    ui.keys("j").await;
+    ui.assert_screen(&[header]);
+
+    // 두 줄이면 header 와 status 를 그린다. 앞서 누른 j 로 선택이 옮겨져 있다
+    ui.resize(30, 2).await;
+    ui.assert_screen(&[header, "s3://logs/2024/"]);
+    ui.keys("j").await;
+    ui.assert_screen(&[header, "s3://logs/readme.txt"]);
+
+    // 네 줄이면 header, 목록 한 줄, status, 입력줄을 그린다
+    ui.resize(30, 4).await;
+    ui.assert_screen(&[
+        header,
+        "2022-01-01T00:00:00Z 5 B readm",
+        "s3://logs/readme.txt",
+        "",
+    ]);
+    ui.keys("k").await;
+    ui.assert_screen(&[header, "PRE  2024/", "s3://logs/2024/", ""]);
+
+    // 여러 tab 이 열리면 맨 위에 tab bar 가 생긴다
+    ui.resize(60, 8).await;
+    ui.keys("t").await;
+    assert_eq!(ui.lines()[0], " 1: logs/ │ 2: buckets");
+    assert!(ui.lines()[1].starts_with("bucket selection"));
+
+    // 두 줄이면 tab bar 와 header 만 그린다
+    ui.resize(60, 2).await;
+    ui.assert_screen(&[
+        " 1: logs/ │ 2: buckets",
+        "bucket selection    ────────────────────────────────────────",
+    ]);
+}